@@ -0,0 +1,100 @@
+use super::data::{DataType, Value};
+
+/// Reads the text form of an array, `{1,2,NULL}`, with elements of type
+/// `element`. Elements may be double-quoted, with `\` escaping the next
+/// character; an unquoted NULL is the NULL value. Arrays of arrays nest
+/// braces, as in `{{1,2},{3}}`.
+pub fn parse_array(text: &str, element: &DataType) -> Result<Vec<Value>, String> {
+    let invalid = || format!("Expected array such as '{{1,2,3}}', got '{}'.", text);
+    let inner = text
+        .trim()
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+        .ok_or_else(invalid)?;
+    if inner.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut values = Vec::new();
+    let mut chars = inner.chars().peekable();
+    loop {
+        while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+        let value = match chars.peek() {
+            Some('"') => {
+                chars.next(); // Consume the opening quote
+                let mut item = String::new();
+                loop {
+                    match chars.next().ok_or_else(invalid)? {
+                        '"' => break,
+                        '\\' => item.push(chars.next().ok_or_else(invalid)?),
+                        ch => item.push(ch),
+                    }
+                }
+                Value::from_str(element, &item)?
+            }
+            Some('{') => {
+                // Keep the nested array whole, quotes and all, for the element type
+                let mut item = String::new();
+                let (mut depth, mut quoted) = (0, false);
+                loop {
+                    let ch = chars.next().ok_or_else(invalid)?;
+                    item.push(ch);
+                    match ch {
+                        '\\' if quoted => item.push(chars.next().ok_or_else(invalid)?),
+                        '"' => quoted = !quoted,
+                        '{' if !quoted => depth += 1,
+                        '}' if !quoted => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                Value::from_str(element, &item)?
+            }
+            _ => {
+                let mut item = String::new();
+                while let Some(ch) = chars.next_if(|ch| *ch != ',') {
+                    item.push(ch);
+                }
+                match item.trim() {
+                    "" => return Err(invalid()),
+                    bare if bare.eq_ignore_ascii_case("NULL") => Value::Null,
+                    bare => Value::from_str(element, bare)?,
+                }
+            }
+        };
+        values.push(value);
+        while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+        match chars.next() {
+            Some(',') => continue,
+            None => return Ok(values),
+            Some(_) => return Err(invalid()),
+        }
+    }
+}
+
+/// Writes the text form `parse_array` reads, quoting elements that would
+/// otherwise be misread.
+pub fn format_array(values: &[Value]) -> String {
+    let items = values
+        .iter()
+        .map(|value| match value {
+            Value::Null => "NULL".to_string(),
+            Value::Array(values) => format_array(values),
+            value => {
+                let text = value.to_string();
+                let plain = !text.is_empty()
+                    && !text.eq_ignore_ascii_case("NULL")
+                    && !text
+                        .chars()
+                        .any(|ch| matches!(ch, ',' | '{' | '}' | '"' | '\\') || ch.is_whitespace());
+                match plain {
+                    true => text,
+                    false => format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\"")),
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("{{{}}}", items.join(","))
+}
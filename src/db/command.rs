@@ -1,5 +1,19 @@
 #[derive(Debug)]
 pub enum Command {
     Exit,
+    Import {
+        file_name: String,
+        table_name: String,
+    },
+    Export {
+        table_name: String,
+        file_name: String,
+    },
+    Dump {
+        table_name: Option<String>,
+    },
+    Read {
+        file_name: String,
+    },
     Unknown(String),
 }
@@ -1,24 +1,90 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Utc};
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::fmt::Display;
+use std::sync::Arc;
+use unicode_normalization::UnicodeNormalization;
+
+use super::array::{format_array, parse_array};
+use super::datetime::{parse_time, parse_timestamp, Interval};
+use super::decimal::{Decimal, MAX_PRECISION};
+use super::expression::Expression;
+use super::json::Json;
+use super::sequence::Sequence;
+use super::uuid::Uuid;
+use crate::parser::quote_identifier;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum DataType {
+    SmallInt,
     Int,
-    Char,
+    BigInt,
+    /// Exact, with precision (total digits) and scale (digits after the point).
+    Decimal(u32, u32),
+    /// DECIMAL or NUMERIC without a precision: exact, up to `MAX_PRECISION`
+    /// digits, at whatever scale each value has.
+    Numeric,
+    /// Fixed width: shorter values are padded with spaces to the length.
+    Char(usize),
     Boolean,
+    /// Single precision, also spelled REAL.
     Float,
+    Double,
     Varchar(usize),
+    /// Character data of any length, held in VARCHAR values.
+    Text,
+    /// Raw bytes, also spelled BYTEA.
+    Blob,
     Date,
+    Timestamp,
+    /// An instant, stored and shown in UTC.
+    TimestampTz,
+    Time,
+    Interval,
+    /// A JSON document, validated on the way in; also spelled JSONB.
+    Json,
+    Uuid,
+    /// A type from CREATE TYPE ... AS ENUM. Statements name it with empty
+    /// labels; the database fills them in when the statement runs.
+    Enum(Arc<EnumType>),
+    /// A list of elements of one type, written `INT[]`.
+    Array(Box<DataType>),
+}
+
+/// The labels of an enum type, in declaration order, which is also the
+/// order its values sort in.
+#[derive(Debug, PartialEq)]
+pub struct EnumType {
+    pub name: String,
+    pub labels: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
+    SmallInt(i16),
     Int(i32),
-    Char(char),
+    BigInt(i64),
+    Decimal(Decimal),
+    /// Text padded to its column's width; the padding doesn't count when
+    /// comparing.
+    Char(String),
     Boolean(bool),
     Float(f32),
+    Double(f64),
     Varchar(String),
+    Blob(Vec<u8>),
     Date(NaiveDate),
+    Timestamp(NaiveDateTime),
+    TimestampTz(DateTime<Utc>),
+    Time(NaiveTime),
+    Interval(Interval),
+    Json(Json),
+    Uuid(Uuid),
+    /// A label of an enum type, kept as its position among the labels.
+    Enum(Arc<EnumType>, u16),
+    /// Elements of one type, any of which may be NULL.
+    Array(Vec<Value>),
+    Null,
     // Add other value types as needed
 }
 
@@ -26,6 +92,55 @@ pub enum Value {
 pub struct Column {
     pub name: String,
     pub data_type: DataType,
+    /// Evaluated for every inserted row that doesn't supply this column.
+    pub default: Option<Expression>,
+    pub not_null: bool,
+    pub identity: Option<Identity>,
+    pub collation: Collation,
+}
+
+/// How a text column compares: byte by byte, ignoring case, or after
+/// Unicode normalisation (NFC), so a precomposed `é` equals `e` followed by a
+/// combining accent.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Collation {
+    #[default]
+    Binary,
+    NoCase,
+    Unicode,
+}
+
+/// Fills an INT column from its own sequence when a row leaves it out.
+/// GENERATED ALWAYS also rejects values given by the user; BY DEFAULT, which
+/// AUTOINCREMENT is a synonym for, accepts them.
+#[derive(Debug, Clone)]
+pub struct Identity {
+    pub always: bool,
+    pub sequence: Sequence,
+}
+
+#[derive(Debug, Clone)]
+pub enum ConstraintKind {
+    Unique(Vec<String>),
+    /// Rejects rows for which the expression is FALSE; NULL passes.
+    Check(Expression),
+    ForeignKey(ForeignKey),
+}
+
+/// Requires `columns` to match `referenced` columns of some row in `table`,
+/// unless one of them is NULL. The referenced columns must be UNIQUE.
+#[derive(Debug, Clone)]
+pub struct ForeignKey {
+    pub columns: Vec<String>,
+    pub table: String,
+    pub referenced: Vec<String>,
+}
+
+/// A named table constraint; column-level UNIQUE and CHECK are stored here too.
+#[derive(Debug, Clone)]
+pub struct Constraint {
+    pub name: String,
+    pub kind: ConstraintKind,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -36,12 +151,27 @@ pub struct Row {
 impl Display for DataType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            DataType::SmallInt => write!(f, "SMALLINT"),
             DataType::Int => write!(f, "INT"),
-            DataType::Char => write!(f, "CHAR"),
+            DataType::BigInt => write!(f, "BIGINT"),
+            DataType::Decimal(precision, scale) => write!(f, "DECIMAL({}, {})", precision, scale),
+            DataType::Numeric => write!(f, "NUMERIC"),
+            DataType::Char(len) => write!(f, "CHAR({})", len),
             DataType::Boolean => write!(f, "BOOLEAN"),
             DataType::Float => write!(f, "FLOAT"),
+            DataType::Double => write!(f, "DOUBLE"),
             DataType::Varchar(len) => write!(f, "VARCHAR({})", len),
+            DataType::Text => write!(f, "TEXT"),
+            DataType::Blob => write!(f, "BLOB"),
             DataType::Date => write!(f, "DATE"),
+            DataType::Timestamp => write!(f, "TIMESTAMP"),
+            DataType::TimestampTz => write!(f, "TIMESTAMPTZ"),
+            DataType::Time => write!(f, "TIME"),
+            DataType::Interval => write!(f, "INTERVAL"),
+            DataType::Json => write!(f, "JSON"),
+            DataType::Uuid => write!(f, "UUID"),
+            DataType::Enum(enum_type) => write!(f, "{}", quote_identifier(&enum_type.name)),
+            DataType::Array(element) => write!(f, "{}[]", element),
         }
     }
 }
@@ -49,49 +179,763 @@ impl Display for DataType {
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Value::SmallInt(value) => write!(f, "{}", value),
             Value::Int(value) => write!(f, "{}", value),
+            Value::BigInt(value) => write!(f, "{}", value),
+            Value::Decimal(value) => write!(f, "{}", value),
             Value::Char(value) => write!(f, "{}", value),
             Value::Boolean(value) => write!(f, "{}", value),
             Value::Float(value) => write!(f, "{}", value),
+            Value::Double(value) => write!(f, "{}", value),
             Value::Varchar(value) => write!(f, "{}", value),
+            Value::Blob(value) => write!(f, "\\x{}", hex(value)),
             Value::Date(value) => write!(f, "{}", value),
+            Value::Timestamp(value) => write!(f, "{}", value.format("%Y-%m-%d %H:%M:%S%.f")),
+            Value::TimestampTz(value) => {
+                write!(f, "{}", value.format("%Y-%m-%d %H:%M:%S%.f%:z"))
+            }
+            Value::Time(value) => write!(f, "{}", value.format("%H:%M:%S%.f")),
+            Value::Interval(value) => write!(f, "{}", value),
+            Value::Json(value) => write!(f, "{}", value),
+            Value::Uuid(value) => write!(f, "{}", value),
+            Value::Enum(enum_type, index) => write!(f, "{}", enum_type.labels[*index as usize]),
+            Value::Array(values) => write!(f, "{}", format_array(values)),
+            Value::Null => write!(f, "NULL"),
+        }
+    }
+}
+
+impl DataType {
+    /// Picks the narrowest type that accepts every sample, falling back to VARCHAR.
+    /// Empty samples are NULL and fit any type.
+    pub fn infer(samples: &[&str]) -> DataType {
+        let samples = samples
+            .iter()
+            .copied()
+            .filter(|sample| !sample.is_empty())
+            .collect::<Vec<_>>();
+        let candidates = [
+            DataType::Int,
+            DataType::BigInt,
+            DataType::Double,
+            DataType::Date,
+            DataType::Timestamp,
+        ];
+        for candidate in candidates {
+            if !samples.is_empty()
+                && samples
+                    .iter()
+                    .all(|sample| Value::from_str(&candidate, sample).is_ok())
+            {
+                return candidate;
+            }
+        }
+        if !samples.is_empty()
+            && samples
+                .iter()
+                .all(|sample| matches!(sample.to_lowercase().as_str(), "true" | "false"))
+        {
+            return DataType::Boolean;
         }
+        let max_len = samples
+            .iter()
+            .map(|sample| sample.chars().count())
+            .max()
+            .unwrap_or(0);
+        DataType::Varchar(max_len.max(1))
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, DataType::SmallInt | DataType::Int | DataType::BigInt)
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer()
+            || matches!(
+                self,
+                DataType::Decimal(..) | DataType::Numeric | DataType::Float | DataType::Double
+            )
+    }
+
+    /// Precision and scale of an exact numeric type; integers have as many
+    /// digits as their largest value.
+    pub fn decimal_digits(&self) -> Option<(u32, u32)> {
+        match self {
+            DataType::SmallInt => Some((5, 0)),
+            DataType::Int => Some((10, 0)),
+            DataType::BigInt => Some((19, 0)),
+            DataType::Decimal(precision, scale) => Some((*precision, *scale)),
+            _ => None,
+        }
+    }
+
+    /// The type arithmetic between the two numeric types yields: the wider
+    /// integer type, DECIMAL once either side is DECIMAL, and a floating type
+    /// once either side is floating. BIGINT or DECIMAL with FLOAT gives DOUBLE,
+    /// as FLOAT can't hold all their digits.
+    pub fn common_numeric(&self, other: &DataType) -> DataType {
+        let rank = |data_type: &DataType| match data_type {
+            DataType::SmallInt => 0,
+            DataType::Int => 1,
+            DataType::BigInt => 2,
+            DataType::Decimal(..) | DataType::Numeric => 3,
+            DataType::Float => 4,
+            _ => 5,
+        };
+        match (self, other) {
+            (DataType::BigInt | DataType::Decimal(..) | DataType::Numeric, DataType::Float)
+            | (DataType::Float, DataType::BigInt | DataType::Decimal(..) | DataType::Numeric) => {
+                DataType::Double
+            }
+            // Without a precision on one side, there's none to work one out from
+            (DataType::Numeric, _) | (_, DataType::Numeric)
+                if rank(self) <= 3 && rank(other) <= 3 =>
+            {
+                DataType::Numeric
+            }
+            (DataType::Decimal(..), _) | (_, DataType::Decimal(..))
+                if rank(self) <= 3 && rank(other) <= 3 =>
+            {
+                // Room for the longer whole part and the longer fraction
+                let (l_precision, l_scale) = self.decimal_digits().unwrap();
+                let (r_precision, r_scale) = other.decimal_digits().unwrap();
+                let scale = l_scale.max(r_scale);
+                let whole = (l_precision - l_scale).max(r_precision - r_scale);
+                DataType::Decimal((whole + scale).min(MAX_PRECISION), scale)
+            }
+            _ if rank(self) >= rank(other) => self.clone(),
+            _ => other.clone(),
+        }
+    }
+
+    /// Picks a type for a computed column or an array from its values.
+    /// Numbers widen to their common numeric type, strings take the longest
+    /// length, and values that are all NULL fall back to VARCHAR.
+    pub fn of_values<'a>(values: impl Iterator<Item = &'a Value>) -> DataType {
+        values
+            .filter_map(|value| value.data_type())
+            .reduce(|current, next| match (current, next) {
+                (DataType::Varchar(l), DataType::Varchar(r)) => DataType::Varchar(l.max(r)),
+                (current, next) if current.is_numeric() && next.is_numeric() => {
+                    current.common_numeric(&next)
+                }
+                (current, _) => current,
+            })
+            .map(|data_type| match data_type {
+                DataType::Varchar(len) => DataType::Varchar(len.max(1)),
+                data_type => data_type,
+            })
+            .unwrap_or(DataType::Varchar(1))
     }
 }
 
 impl Value {
-    pub fn from_str(data_type: &DataType, value: &str) -> Result<Value, String> {
+    /// The type of a non-NULL value; NULL has no type of its own.
+    pub fn data_type(&self) -> Option<DataType> {
+        match self {
+            Value::SmallInt(_) => Some(DataType::SmallInt),
+            Value::Int(_) => Some(DataType::Int),
+            Value::BigInt(_) => Some(DataType::BigInt),
+            Value::Decimal(value) => Some(DataType::Decimal(value.precision(), value.scale())),
+            Value::Char(value) => Some(DataType::Char(value.chars().count())),
+            Value::Boolean(_) => Some(DataType::Boolean),
+            Value::Float(_) => Some(DataType::Float),
+            Value::Double(_) => Some(DataType::Double),
+            Value::Varchar(value) => Some(DataType::Varchar(value.chars().count())),
+            Value::Blob(_) => Some(DataType::Blob),
+            Value::Date(_) => Some(DataType::Date),
+            Value::Timestamp(_) => Some(DataType::Timestamp),
+            Value::TimestampTz(_) => Some(DataType::TimestampTz),
+            Value::Time(_) => Some(DataType::Time),
+            Value::Interval(_) => Some(DataType::Interval),
+            Value::Json(_) => Some(DataType::Json),
+            Value::Uuid(_) => Some(DataType::Uuid),
+            Value::Enum(enum_type, _) => Some(DataType::Enum(enum_type.clone())),
+            Value::Array(values) => Some(DataType::Array(Box::new(DataType::of_values(
+                values.iter(),
+            )))),
+            Value::Null => None,
+        }
+    }
+
+    /// Reads a numeric literal: INT, then BIGINT for whole numbers too large
+    /// for it, DECIMAL for fractions, and DOUBLE beyond DECIMAL's precision or
+    /// with an exponent.
+    pub fn parse_number(text: &str) -> Result<Value, String> {
+        if !text.contains('.') {
+            if let Ok(value) = text.parse::<i32>() {
+                return Ok(Value::Int(value));
+            }
+            if let Ok(value) = text.parse::<i64>() {
+                return Ok(Value::BigInt(value));
+            }
+        }
+        if let Some(value) = Decimal::parse(text) {
+            return Ok(Value::Decimal(value));
+        }
+        text.parse::<f64>()
+            .map(Value::Double)
+            .map_err(|_| format!("Invalid number '{}'", text))
+    }
+
+    /// The SQL value a JSON scalar holds: numbers as by `parse_number`, strings
+    /// as VARCHAR and JSON null as NULL. Arrays and objects stay JSON.
+    pub fn from_json(json: &Json) -> Value {
+        match json {
+            Json::Null => Value::Null,
+            Json::Bool(value) => Value::Boolean(*value),
+            Json::Number(text) => Value::parse_number(text).unwrap_or(Value::Null),
+            Json::String(text) => Value::Varchar(text.clone()),
+            json => Value::Json(json.clone()),
+        }
+    }
+
+    /// The document a JSON value holds; text is parsed as JSON.
+    pub fn into_json(self) -> Result<Json, String> {
+        match self {
+            Value::Json(json) => Ok(json),
+            Value::Varchar(text) => Json::parse(&text),
+            value => Err(format!("Expected JSON, got '{}'.", value)),
+        }
+    }
+
+    /// The value of any integer type.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::SmallInt(value) => Some(*value as i64),
+            Value::Int(value) => Some(*value as i64),
+            Value::BigInt(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value of any integer type or DECIMAL.
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        match self {
+            Value::Decimal(value) => Some(*value),
+            value => value.as_i64().map(Decimal::from_i64),
+        }
+    }
+
+    /// A date or timestamp as a point on the calendar; dates start at
+    /// midnight and TIMESTAMPTZ is read in UTC.
+    pub fn as_datetime(&self) -> Option<NaiveDateTime> {
+        match self {
+            Value::Date(value) => Some(value.and_time(NaiveTime::MIN)),
+            Value::Timestamp(value) => Some(*value),
+            Value::TimestampTz(value) => Some(value.naive_utc()),
+            _ => None,
+        }
+    }
+
+    /// The value of any numeric type.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Decimal(value) => Some(value.to_f64()),
+            Value::Float(value) => Some(*value as f64),
+            Value::Double(value) => Some(*value),
+            value => value.as_i64().map(|value| value as f64),
+        }
+    }
+
+    /// Makes a value of the integer type `data_type`, or of DECIMAL or a
+    /// floating type, failing when the number doesn't fit.
+    pub fn from_i64(data_type: &DataType, value: i64) -> Result<Value, String> {
+        let out_of_range = || format!("Value {} is out of range for {}.", value, data_type);
         match data_type {
-            DataType::Int => value
-                .parse::<i32>()
+            DataType::SmallInt => i16::try_from(value)
+                .map(Value::SmallInt)
+                .map_err(|_| out_of_range()),
+            DataType::Int => i32::try_from(value)
                 .map(Value::Int)
-                .map_err(|_| format!("Expected integer, got '{}'.", value)),
-            DataType::Char => {
-                if value.len() != 1 {
-                    Err(format!("Expected char, got '{}'.", value))
+                .map_err(|_| out_of_range()),
+            DataType::BigInt => Ok(Value::BigInt(value)),
+            DataType::Decimal(..) | DataType::Numeric => {
+                Value::from_decimal(data_type, Decimal::from_i64(value))
+            }
+            data_type => Value::from_f64(data_type, value as f64),
+        }
+    }
+
+    /// Makes a value of the DECIMAL type `data_type`, rounding to its scale and
+    /// failing when the whole part has too many digits for its precision.
+    /// NUMERIC takes the value as it is.
+    pub fn from_decimal(data_type: &DataType, value: Decimal) -> Result<Value, String> {
+        let Some((precision, scale)) = data_type.decimal_digits() else {
+            return Ok(Value::Decimal(value));
+        };
+        value
+            .rescale(scale)
+            .filter(|value| value.precision() <= precision)
+            .map(Value::Decimal)
+            .ok_or_else(|| format!("Value {} is out of range for {}.", value, data_type))
+    }
+
+    /// Makes a value of the floating type `data_type`, failing when a finite
+    /// number is too large for it.
+    pub fn from_f64(data_type: &DataType, value: f64) -> Result<Value, String> {
+        match data_type {
+            DataType::Float if value.is_finite() && (value as f32).is_infinite() => Err(format!(
+                "Value {} is out of range for {}.",
+                value, data_type
+            )),
+            DataType::Float => Ok(Value::Float(value as f32)),
+            _ => Ok(Value::Double(value)),
+        }
+    }
+
+    /// Orders two non-NULL values. Numbers compare across numeric types, and a
+    /// string compared with another type is first converted to that type, so
+    /// `born > '2000-01-01'` compares dates.
+    ///
+    /// Callers decide what NULL means for them; comparing it is an error here.
+    pub fn compare(&self, other: &Value) -> Result<Ordering, String> {
+        self.compare_with(other, Collation::Binary)
+    }
+
+    /// Like `compare`, with strings ordered by `collation`. CHAR padding is
+    /// ignored, so `'ab'` in a CHAR(4) column equals `'ab'`.
+    pub fn compare_with(&self, other: &Value, collation: Collation) -> Result<Ordering, String> {
+        match (self, other) {
+            (Value::Null, _) | (_, Value::Null) => Err("Cannot compare NULL.".to_string()),
+            _ if self.as_i64().is_some() && other.as_i64().is_some() => {
+                Ok(self.as_i64().unwrap().cmp(&other.as_i64().unwrap()))
+            }
+            _ if self.as_decimal().is_some() && other.as_decimal().is_some() => {
+                Ok(self.as_decimal().unwrap().cmp(&other.as_decimal().unwrap()))
+            }
+            _ if self.as_f64().is_some() && other.as_f64().is_some() => {
+                Ok(self.as_f64().unwrap().total_cmp(&other.as_f64().unwrap()))
+            }
+            (Value::Boolean(l), Value::Boolean(r)) => Ok(l.cmp(r)),
+            _ if self.as_datetime().is_some() && other.as_datetime().is_some() => Ok(self
+                .as_datetime()
+                .unwrap()
+                .cmp(&other.as_datetime().unwrap())),
+            (Value::Time(l), Value::Time(r)) => Ok(l.cmp(r)),
+            (Value::Blob(l), Value::Blob(r)) => Ok(l.cmp(r)),
+            (Value::Interval(l), Value::Interval(r)) => Ok(l.cmp(r)),
+            (Value::Uuid(l), Value::Uuid(r)) => Ok(l.cmp(r)),
+            (Value::Enum(l_type, l), Value::Enum(r_type, r)) if l_type == r_type => Ok(l.cmp(r)),
+            // Element by element, then by length; NULL elements sort last
+            (Value::Array(l), Value::Array(r)) => {
+                for (l, r) in l.iter().zip(r) {
+                    let ordering = match (l, r) {
+                        (Value::Null, Value::Null) => Ordering::Equal,
+                        (Value::Null, _) => Ordering::Greater,
+                        (_, Value::Null) => Ordering::Less,
+                        _ => l.compare_with(r, collation)?,
+                    };
+                    if ordering != Ordering::Equal {
+                        return Ok(ordering);
+                    }
+                }
+                Ok(l.len().cmp(&r.len()))
+            }
+            // Documents are only equal or not; there is no order between them
+            (Value::Json(l), Value::Json(r)) if l == r => Ok(Ordering::Equal),
+            (Value::Json(_), Value::Json(_)) => Err(format!(
+                "Cannot order JSON values '{}' and '{}'.",
+                self, other
+            )),
+            (Value::Varchar(l) | Value::Char(l), Value::Varchar(r) | Value::Char(r)) => {
+                let (l, r) = (l.trim_end_matches(' '), r.trim_end_matches(' '));
+                Ok(collation.key(l).cmp(&collation.key(r)))
+            }
+            // Text is read at its own scale, so '1.24' doesn't round to equal 1.2
+            (Value::Varchar(text), Value::Decimal(decimal)) => Decimal::parse(text.trim())
+                .map(|parsed| parsed.cmp(decimal))
+                .ok_or_else(|| format!("Expected decimal, got '{}'.", text)),
+            (Value::Decimal(_), Value::Varchar(_)) => {
+                other.compare_with(self, collation).map(Ordering::reverse)
+            }
+            (Value::Varchar(text), _) => {
+                Value::from_str(&other.data_type().unwrap(), text)?.compare_with(other, collation)
+            }
+            (_, Value::Varchar(text)) => self.compare_with(
+                &Value::from_str(&self.data_type().unwrap(), text)?,
+                collation,
+            ),
+            _ => Err(format!("Cannot compare '{}' with '{}'.", self, other)),
+        }
+    }
+
+    /// Renders the value as a SQL literal that parses back to the same value.
+    pub fn to_sql_literal(&self) -> String {
+        match self {
+            Value::SmallInt(value) => value.to_string(),
+            Value::Int(value) => value.to_string(),
+            Value::BigInt(value) => value.to_string(),
+            Value::Decimal(value) => value.to_string(),
+            Value::Float(value) if value.is_finite() => value.to_string(),
+            Value::Float(value) => format!("'{}'", value),
+            Value::Double(value) if value.is_finite() => value.to_string(),
+            Value::Double(value) => format!("'{}'", value),
+            Value::Boolean(value) => value.to_string().to_uppercase(),
+            Value::Char(value) => quote_literal(value),
+            Value::Varchar(value) => quote_literal(value),
+            Value::Blob(value) => format!("X'{}'", hex(value)),
+            Value::Date(value) => format!("DATE '{}'", value),
+            Value::Timestamp(_) => format!("TIMESTAMP '{}'", self),
+            Value::TimestampTz(_) => format!("TIMESTAMPTZ '{}'", self),
+            Value::Time(_) => format!("TIME '{}'", self),
+            Value::Interval(value) => format!("INTERVAL '{}'", value),
+            Value::Json(value) => format!("JSON {}", quote_literal(&value.to_string())),
+            Value::Uuid(value) => format!("UUID '{}'", value),
+            Value::Enum(..) => quote_literal(&self.to_string()),
+            // ARRAY[] has no element type, while '{}' takes the column's
+            Value::Array(values) if values.is_empty() => "'{}'".to_string(),
+            Value::Array(values) => format!(
+                "ARRAY[{}]",
+                values
+                    .iter()
+                    .map(|value| value.to_sql_literal())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Value::Null => "NULL".to_string(),
+        }
+    }
+
+    /// Converts a value for storage in a column of `data_type`.
+    ///
+    /// Implicit conversions are deliberately narrow:
+    /// - NULL is accepted by every type.
+    /// - Any integer fits any integer type whose range holds it, integers and
+    ///   DECIMALs fit a DECIMAL type with room for their whole part, and any
+    ///   number fits a floating type; a floating value never becomes an
+    ///   integer or DECIMAL. DECIMAL rounds to the column's scale.
+    /// - A string (VARCHAR or CHAR) is read as the target type's text input,
+    ///   so `'2024-01-01'` fills a DATE and `'42'` an INT. BOOLEAN text must be
+    ///   'true' or 'false' — `'1'` is not a boolean.
+    /// - A date or timestamp fits either timestamp type, TIMESTAMP being read
+    ///   as UTC; a timestamp never becomes a DATE.
+    /// - An array fits an array type when each of its elements fits the
+    ///   element type.
+    /// - Everything else, such as a number into VARCHAR or a boolean into INT,
+    ///   is rejected.
+    pub fn coerce_to(self, data_type: &DataType) -> Result<Value, String> {
+        match (self, data_type) {
+            (Value::Null, _) => Ok(Value::Null),
+            (value, data_type) if value.as_i64().is_some() && data_type.is_numeric() => {
+                Value::from_i64(data_type, value.as_i64().unwrap())
+            }
+            (Value::Decimal(value), DataType::Decimal(..) | DataType::Numeric) => {
+                Value::from_decimal(data_type, value)
+            }
+            (value @ Value::Decimal(_), DataType::Float | DataType::Double) => {
+                Value::from_f64(data_type, value.as_f64().unwrap())
+            }
+            (value @ (Value::Float(_) | Value::Double(_)), DataType::Float | DataType::Double) => {
+                Value::from_f64(data_type, value.as_f64().unwrap())
+            }
+            (Value::Boolean(value), DataType::Boolean) => Ok(Value::Boolean(value)),
+            (Value::Blob(value), DataType::Blob) => Ok(Value::Blob(value)),
+            (Value::Date(value), DataType::Date) => Ok(Value::Date(value)),
+            (value, DataType::Timestamp) if value.as_datetime().is_some() => {
+                Ok(Value::Timestamp(value.as_datetime().unwrap()))
+            }
+            (value, DataType::TimestampTz) if value.as_datetime().is_some() => {
+                Ok(Value::TimestampTz(value.as_datetime().unwrap().and_utc()))
+            }
+            (Value::Time(value), DataType::Time) => Ok(Value::Time(value)),
+            (Value::Interval(value), DataType::Interval) => Ok(Value::Interval(value)),
+            (Value::Json(value), DataType::Json) => Ok(Value::Json(value)),
+            (Value::Uuid(value), DataType::Uuid) => Ok(Value::Uuid(value)),
+            (Value::Enum(enum_type, index), DataType::Enum(target)) if enum_type == *target => {
+                Ok(Value::Enum(enum_type, index))
+            }
+            (Value::Array(values), DataType::Array(element)) => values
+                .into_iter()
+                .map(|value| value.coerce_to(element))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Array),
+            (Value::Varchar(text), _) => Value::from_str(data_type, &text),
+            (Value::Char(text), _) => Value::from_str(data_type, text.trim_end_matches(' ')),
+            (value, _) => Err(format!(
+                "Cannot store {} value '{}' in a {} column.",
+                value.data_type().unwrap(),
+                value,
+                data_type
+            )),
+        }
+    }
+
+    /// Explicit conversion, as done by CAST and when a column changes type.
+    ///
+    /// - NULL stays NULL.
+    /// - Numbers convert to every numeric type, rounding half away from zero
+    ///   when a fraction is dropped.
+    /// - Integers and BOOLEAN convert both ways, zero being FALSE.
+    /// - Text is read as the target type's text input; BOOLEAN also takes
+    ///   t/f, yes/no, on/off and 1/0. Every value converts to text through its
+    ///   text form, so `42` becomes `'42'` and back again.
+    /// - A date or timestamp converts to either timestamp type, and a
+    ///   timestamp to its DATE or TIME part.
+    /// - Arrays convert element by element, and JSON scalars as their text.
+    /// - Other pairs go through text too, and are rejected when the target
+    ///   can't read the value's text form, such as DATE to INT. BLOB only
+    ///   converts from text.
+    pub fn cast_to(self, data_type: &DataType) -> Result<Value, String> {
+        let Some(source_type) = self.data_type() else {
+            return Ok(Value::Null);
+        };
+        let impossible = |value: &Value| {
+            format!(
+                "Cannot cast {} value '{}' to {}.",
+                source_type, value, data_type
+            )
+        };
+        match (self, data_type) {
+            (value, target) if value.as_f64().is_some() && target.is_numeric() => {
+                value.cast_number(target)
+            }
+            (Value::Boolean(value), target) if target.is_integer() => {
+                Value::from_i64(target, value as i64)
+            }
+            (value, DataType::Boolean) if value.as_i64().is_some() => {
+                Ok(Value::Boolean(value.as_i64().unwrap() != 0))
+            }
+            (value, DataType::Date) if value.as_datetime().is_some() => {
+                Ok(Value::Date(value.as_datetime().unwrap().date()))
+            }
+            (value @ (Value::Timestamp(_) | Value::TimestampTz(_)), DataType::Time) => {
+                Ok(Value::Time(value.as_datetime().unwrap().time()))
+            }
+            (Value::Array(values), DataType::Array(element)) => values
+                .into_iter()
+                .map(|value| value.cast_to(element))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Array),
+            (Value::Varchar(text) | Value::Char(text), DataType::Boolean) => {
+                parse_boolean(text.trim())
+                    .map(Value::Boolean)
+                    .ok_or_else(|| format!("Expected boolean, got '{}'.", text))
+            }
+            (Value::Varchar(text), target) => Value::from_str(target, &text),
+            (Value::Char(text), target) => Value::from_str(target, text.trim_end_matches(' ')),
+            (value, DataType::Char(_) | DataType::Varchar(_) | DataType::Text) => {
+                Value::from_str(data_type, &value.to_string())
+            }
+            (value, DataType::Blob) => Err(impossible(&value)),
+            // A JSON string converts as the text inside its quotes
+            (Value::Json(json), target) if *target != DataType::Json => {
+                Value::from_str(target, &json.to_text()).map_err(|_| impossible(&Value::Json(json)))
+            }
+            (value, target) => value
+                .clone()
+                .coerce_to(target)
+                .or_else(|_| Value::from_str(target, &value.to_string()))
+                .map_err(|_| impossible(&value)),
+        }
+    }
+
+    /// Converts a number to the numeric type `data_type`, rounding half away
+    /// from zero when a fraction is dropped.
+    fn cast_number(self, data_type: &DataType) -> Result<Value, String> {
+        let out_of_range = || format!("Value {} is out of range for {}.", self, data_type);
+        match &self {
+            value if value.as_i64().is_some() => {
+                Value::from_i64(data_type, value.as_i64().unwrap())
+            }
+            Value::Decimal(value) if data_type.is_integer() => value
+                .to_i64()
+                .ok_or_else(out_of_range)
+                .and_then(|whole| Value::from_i64(data_type, whole)),
+            Value::Decimal(value)
+                if matches!(data_type, DataType::Decimal(..) | DataType::Numeric) =>
+            {
+                Value::from_decimal(data_type, *value)
+            }
+            Value::Decimal(value) => Value::from_f64(data_type, value.to_f64()),
+            // FLOAT or DOUBLE; the shortest text form keeps 0.1 from becoming 0.100000001
+            value => match data_type {
+                DataType::Float | DataType::Double => {
+                    Value::from_f64(data_type, value.as_f64().unwrap())
+                }
+                DataType::Decimal(..) | DataType::Numeric => Decimal::parse(&value.to_string())
+                    .ok_or_else(out_of_range)
+                    .and_then(|number| Value::from_decimal(data_type, number)),
+                _ => {
+                    let whole = value.as_f64().unwrap().round();
+                    if !(i64::MIN as f64..i64::MAX as f64).contains(&whole) {
+                        return Err(out_of_range());
+                    }
+                    Value::from_i64(data_type, whole as i64)
+                }
+            },
+        }
+    }
+
+    pub fn from_str(data_type: &DataType, value: &str) -> Result<Value, String> {
+        match data_type {
+            DataType::SmallInt | DataType::Int | DataType::BigInt => value
+                .parse::<i64>()
+                .map_err(|_| format!("Expected integer, got '{}'.", value))
+                .and_then(|number| Value::from_i64(data_type, number)),
+            DataType::Decimal(..) | DataType::Numeric => Decimal::parse(value)
+                .ok_or_else(|| format!("Expected decimal, got '{}'.", value))
+                .and_then(|number| Value::from_decimal(data_type, number)),
+            // Trailing spaces beyond the width are dropped rather than rejected
+            DataType::Char(len) => {
+                let text = value.trim_end_matches(' ');
+                let count = text.chars().count();
+                if count > *len {
+                    Err(format!("Value exceeds maximum length of {}.", len))
                 } else {
-                    Ok(Value::Char(value.chars().next().unwrap()))
+                    Ok(Value::Char(format!("{}{}", text, " ".repeat(len - count))))
                 }
             }
             DataType::Boolean => match value.to_lowercase().as_str() {
-                "true" | "1" => Ok(Value::Boolean(true)),
-                "false" | "0" => Ok(Value::Boolean(false)),
+                "true" => Ok(Value::Boolean(true)),
+                "false" => Ok(Value::Boolean(false)),
                 _ => Err(format!("Expected boolean, got '{}'.", value)),
             },
             DataType::Float => value
                 .parse::<f32>()
                 .map(Value::Float)
                 .map_err(|_| format!(" Expected float, got '{}'.", value)),
+            DataType::Double => value
+                .parse::<f64>()
+                .map(Value::Double)
+                .map_err(|_| format!("Expected double, got '{}'.", value)),
             DataType::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d")
                 .map(Value::Date)
                 .map_err(|_| format!("Expected date (YYYY-MM-DD), got '{}'.", value)),
+            // TIMESTAMP keeps the written time whatever the offset says
+            DataType::Timestamp => parse_timestamp(value)
+                .map(|(datetime, _)| Value::Timestamp(datetime))
+                .ok_or_else(|| format!("Expected timestamp (ISO 8601), got '{}'.", value)),
+            DataType::TimestampTz => parse_timestamp(value)
+                .map(|(datetime, offset)| {
+                    let offset = offset.map_or(0, |offset| offset.local_minus_utc());
+                    Value::TimestampTz((datetime - TimeDelta::seconds(offset as i64)).and_utc())
+                })
+                .ok_or_else(|| format!("Expected timestamp (ISO 8601), got '{}'.", value)),
+            DataType::Time => parse_time(value)
+                .map(Value::Time)
+                .ok_or_else(|| format!("Expected time (HH:MM:SS), got '{}'.", value)),
+            DataType::Interval => Interval::parse(value)
+                .map(Value::Interval)
+                .ok_or_else(|| format!("Expected interval, got '{}'.", value)),
+            DataType::Json => Json::parse(value).map(Value::Json),
+            DataType::Array(element) => parse_array(value, element).map(Value::Array),
+            DataType::Uuid => Uuid::parse(value)
+                .map(Value::Uuid)
+                .ok_or_else(|| format!("Expected UUID, got '{}'.", value)),
+            DataType::Enum(enum_type) => enum_type
+                .labels
+                .iter()
+                .position(|label| label == value)
+                .map(|index| Value::Enum(enum_type.clone(), index as u16))
+                .ok_or_else(|| format!("Invalid value '{}' for enum {}.", value, enum_type.name)),
             DataType::Varchar(len) => {
-                if value.len() > *len {
+                if value.chars().count() > *len {
                     Err(format!("Value exceeds maximum length of {}.", len))
                 } else {
                     Ok(Value::Varchar(value.to_string()))
                 }
-            } // Add other data types as needed
+            }
+            DataType::Text => Ok(Value::Varchar(value.to_string())),
+            // `\x` starts hex digits, as in the text form; other text is taken as is
+            DataType::Blob => match value.strip_prefix("\\x") {
+                Some(digits) => from_hex(digits)
+                    .map(Value::Blob)
+                    .ok_or_else(|| format!("Expected hex digits after \\x, got '{}'.", value)),
+                None => Ok(Value::Blob(value.as_bytes().to_vec())),
+            }, // Add other data types as needed
+        }
+    }
+}
+
+impl Collation {
+    pub fn parse(name: &str) -> Result<Collation, String> {
+        match name.to_uppercase().as_str() {
+            "BINARY" | "C" => Ok(Collation::Binary),
+            "NOCASE" => Ok(Collation::NoCase),
+            "UNICODE" => Ok(Collation::Unicode),
+            _ => Err(format!("Unknown collation '{}'.", name)),
+        }
+    }
+
+    /// The form of `text` that compares byte by byte under this collation.
+    pub fn key<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self {
+            Collation::Binary => Cow::Borrowed(text),
+            Collation::NoCase => Cow::Owned(text.to_lowercase()),
+            Collation::Unicode => Cow::Owned(text.nfc().collect()),
+        }
+    }
+}
+
+impl Display for Collation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Collation::Binary => write!(f, "BINARY"),
+            Collation::NoCase => write!(f, "NOCASE"),
+            Collation::Unicode => write!(f, "UNICODE"),
+        }
+    }
+}
+
+/// The spellings of TRUE and FALSE that CAST accepts, in any case.
+fn parse_boolean(text: &str) -> Option<bool> {
+    match text.to_lowercase().as_str() {
+        "true" | "t" | "yes" | "y" | "on" | "1" => Some(true),
+        "false" | "f" | "no" | "n" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(digits: &str) -> Option<Vec<u8>> {
+    // from_str_radix would also take a sign
+    if !digits.len().is_multiple_of(2) || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infer_picks_the_narrowest_type() {
+        assert_eq!(DataType::infer(&["1", "-2"]), DataType::Int);
+        assert_eq!(DataType::infer(&["1", "3000000000"]), DataType::BigInt);
+        assert_eq!(DataType::infer(&["1", "2.5"]), DataType::Double);
+        assert_eq!(DataType::infer(&["2024-01-02"]), DataType::Date);
+        assert_eq!(
+            DataType::infer(&["2024-01-02", "2024-01-02 03:04:05"]),
+            DataType::Timestamp
+        );
+        assert_eq!(DataType::infer(&["true", "FALSE"]), DataType::Boolean);
+        // Lengths count characters, not bytes
+        assert_eq!(DataType::infer(&["1", "héllo"]), DataType::Varchar(5));
+        assert_eq!(DataType::infer(&["", "2024-01-02"]), DataType::Date);
+        assert_eq!(DataType::infer(&[""]), DataType::Varchar(1));
+    }
+
+    #[test]
+    fn blob_text_takes_only_hex_digits() {
+        assert_eq!(
+            Value::from_str(&DataType::Blob, "\\x0aFf"),
+            Ok(Value::Blob(vec![0x0a, 0xff]))
+        );
+        // A sign is not a hex digit, though from_str_radix would take one
+        for text in ["\\x+f", "\\x-1", "\\x0+0f", "\\x f", "\\xf"] {
+            assert!(Value::from_str(&DataType::Blob, text).is_err(), "{}", text);
         }
     }
 }
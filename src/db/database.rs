@@ -1,26 +1,111 @@
-use super::data::{Column, Row};
-use super::statement::{CreateTable, Insert, Select};
-use super::table::Table;
+use super::csv::{write_record, CsvReader, CsvRecord, ImportSummary};
+use super::data::{
+    Collation, Column, Constraint, ConstraintKind, DataType, EnumType, ForeignKey, Row, Value,
+};
+use super::expression::Expression;
+use super::function::{call_state_function, call_table_function, is_state_function};
+use super::query::{execute_select, project, ResultSet, WriteResult};
+use super::sequence::Sequence;
+use super::statement::{
+    AlterAction, AlterTable, ConflictAction, CreateSequence, CreateTable, CreateTableAs,
+    CreateType, FromItem, Insert, InsertSource, InsertValue, OnConflict, Select, SelectItem,
+};
+use super::table::{RowWrite, Table};
+use crate::parser::quote_identifier;
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct Database {
     pub tables: HashMap<String, Table>,
+    pub sequences: HashMap<String, Sequence>,
+    pub types: HashMap<String, Arc<EnumType>>,
+    last_insert_id: Option<i32>,
 }
 
 impl Database {
     pub fn new() -> Self {
         Database {
             tables: HashMap::new(),
+            sequences: HashMap::new(),
+            types: HashMap::new(),
+            last_insert_id: None,
         }
     }
 
-    pub fn create_table(&mut self, create_table: &CreateTable) {
+    pub fn create_sequence(&mut self, create_sequence: &CreateSequence) -> Result<(), String> {
+        let sequence = &create_sequence.sequence;
+        if self.sequences.contains_key(&sequence.name) {
+            return Err(format!("Sequence '{}' already exists.", sequence.name));
+        }
+        self.sequences
+            .insert(sequence.name.clone(), sequence.clone());
+        Ok(())
+    }
+
+    pub fn create_type(&mut self, create_type: &CreateType) -> Result<(), String> {
+        let name = &create_type.enum_type.name;
+        if self.types.contains_key(name) {
+            return Err(format!("Type '{}' already exists.", name));
+        }
+        self.types
+            .insert(name.clone(), create_type.enum_type.clone());
+        Ok(())
+    }
+
+    /// Swaps an enum type named by a statement for the declared type.
+    fn resolve_type(&self, data_type: &DataType) -> Result<DataType, String> {
+        match data_type {
+            DataType::Enum(named) => self
+                .types
+                .get(&named.name)
+                .map(|enum_type| DataType::Enum(enum_type.clone()))
+                .ok_or_else(|| format!("Type '{}' does not exist.", named.name)),
+            DataType::Array(element) => Ok(DataType::Array(Box::new(self.resolve_type(element)?))),
+            data_type => Ok(data_type.clone()),
+        }
+    }
+
+    /// Resolves a column's type and the casts in its DEFAULT.
+    fn resolve_column(&self, column: &Column) -> Result<Column, String> {
+        let mut default = column.default.clone();
+        if let Some(default) = &mut default {
+            default.resolve_types(&|data_type| self.resolve_type(data_type))?;
+        }
+        Ok(Column {
+            data_type: self.resolve_type(&column.data_type)?,
+            default,
+            ..column.clone()
+        })
+    }
+
+    /// Resolves the casts in CHECK constraints.
+    fn resolve_constraints(&self, constraints: &[Constraint]) -> Result<Vec<Constraint>, String> {
+        let mut constraints = constraints.to_vec();
+        for constraint in &mut constraints {
+            if let ConstraintKind::Check(expression) = &mut constraint.kind {
+                expression.resolve_types(&|data_type| self.resolve_type(data_type))?;
+            }
+        }
+        Ok(constraints)
+    }
+
+    pub fn create_table(&mut self, create_table: &CreateTable) -> Result<(), String> {
+        let columns = create_table
+            .columns
+            .iter()
+            .map(|column| self.resolve_column(column))
+            .collect::<Result<Vec<_>, _>>()?;
         let table = Table::new(
             create_table.table_name.clone(),
-            create_table.columns.clone(),
-        );
+            columns,
+            self.resolve_constraints(&create_table.constraints)?,
+        )?;
+        self.check_foreign_key_schema(&table.name, &table.columns, &table.constraints)?;
         self.tables.insert(table.name.clone(), table);
+        Ok(())
     }
 
     /*
@@ -33,26 +118,3266 @@ impl Database {
     }
     */
 
-    pub fn insert_into_table(&mut self, insert: &Insert) -> Result<(), String> {
-        if let Some(table) = self.tables.get_mut(&insert.table_name) {
-            table.insert(&insert.values)
-        } else {
-            Err(format!("Table '{}' does not exist.", insert.table_name))
+    /// Creates a table shaped like the query's result and fills it with the rows.
+    pub fn create_table_as(&mut self, create_table_as: &CreateTableAs) -> Result<(), String> {
+        if self.tables.contains_key(&create_table_as.table_name) {
+            return Err(format!(
+                "Table '{}' already exists.",
+                create_table_as.table_name
+            ));
         }
+        let result = self.select_from_table(&create_table_as.query)?;
+
+        let mut table = Table::new(create_table_as.table_name.clone(), result.columns, vec![])?;
+        let rows = result
+            .rows
+            .into_iter()
+            .map(|row| row.values.into_iter().map(Some).collect())
+            .collect();
+        table.insert_values(None, rows, None)?;
+        self.tables.insert(table.name.clone(), table);
+        Ok(())
     }
 
-    pub fn select_from_table(&self, select: &Select) -> Result<Vec<Row>, String> {
-        if let Some(table) = self.tables.get(&select.table_name) {
-            table.select()
-        } else {
-            Err(format!("Table '{}' does not exist.", select.table_name))
+    pub fn alter_table(&mut self, alter_table: &AlterTable) -> Result<(), String> {
+        // Enum types are looked up before the table is borrowed
+        let resolved = match &alter_table.action {
+            AlterAction::AlterColumnType { data_type, .. } => Some(self.resolve_type(data_type)?),
+            _ => None,
+        };
+        let added = match &alter_table.action {
+            AlterAction::AddColumn {
+                column,
+                constraints,
+            } => Some((
+                self.resolve_column(column)?,
+                self.resolve_constraints(constraints)?,
+            )),
+            _ => None,
+        };
+        let added_constraint = match &alter_table.action {
+            AlterAction::AddConstraint(constraint) => self
+                .resolve_constraints(std::slice::from_ref(constraint))?
+                .pop(),
+            _ => None,
+        };
+        let table = self
+            .tables
+            .get_mut(&alter_table.table_name)
+            .ok_or_else(|| format!("Table '{}' does not exist.", alter_table.table_name))?;
+
+        match &alter_table.action {
+            AlterAction::AddColumn { .. } => {
+                let (column, constraints) = added.unwrap();
+                let references = constraints
+                    .iter()
+                    .any(|constraint| matches!(constraint.kind, ConstraintKind::ForeignKey(_)));
+                if !references {
+                    return table.add_column(column, constraints);
+                }
+
+                let columns = [table.columns.clone(), vec![column.clone()]].concat();
+                let all = [table.constraints.clone(), constraints.clone()].concat();
+                let name = table.name.clone();
+                self.check_foreign_key_schema(&name, &columns, &all)?;
+
+                // Existing rows take the new column's default, which must be referenceable
+                let before = self.tables[&name].clone();
+                let table = self.tables.get_mut(&name).unwrap();
+                table.add_column(column, constraints)?;
+                if let Err(e) = self.check_foreign_keys(&self.tables[&name], &[]) {
+                    self.tables.insert(name, before);
+                    return Err(e);
+                }
+                Ok(())
+            }
+            AlterAction::AddConstraint(_) => {
+                let constraint = added_constraint.unwrap();
+                if !matches!(constraint.kind, ConstraintKind::ForeignKey(_)) {
+                    return table.add_constraint(constraint);
+                }
+
+                let columns = table.columns.clone();
+                let all = [table.constraints.clone(), vec![constraint.clone()]].concat();
+                let name = table.name.clone();
+                self.check_foreign_key_schema(&name, &columns, &all)?;
+
+                let before = self.tables[&name].clone();
+                let table = self.tables.get_mut(&name).unwrap();
+                table.add_constraint(constraint)?;
+                if let Err(e) = self.check_foreign_keys(&self.tables[&name], &[]) {
+                    self.tables.insert(name, before);
+                    return Err(e);
+                }
+                Ok(())
+            }
+            AlterAction::DropColumn(name) => table.drop_column(name),
+            AlterAction::RenameColumn { from, to } => {
+                table.rename_column(from, to)?;
+                for foreign_key in self.foreign_keys_to(&alter_table.table_name) {
+                    for column in foreign_key.referenced.iter_mut().filter(|c| *c == from) {
+                        *column = to.clone();
+                    }
+                }
+                Ok(())
+            }
+            AlterAction::AlterColumnType { column, .. } => {
+                let table_name = alter_table.table_name.clone();
+                let used = self.tables.values().any(|table| {
+                    foreign_keys(&table.constraints).any(|foreign_key| {
+                        (table.name == table_name && foreign_key.columns.contains(column))
+                            || (foreign_key.table == table_name
+                                && foreign_key.referenced.contains(column))
+                    })
+                });
+                if used {
+                    return Err(format!(
+                        "Cannot change the type of '{}', it is part of a foreign key.",
+                        column
+                    ));
+                }
+                let table = self.tables.get_mut(&table_name).unwrap();
+                table.alter_column_type(column, resolved.unwrap())
+            }
+            AlterAction::RenameTable(name) => {
+                if self.tables.contains_key(name) {
+                    return Err(format!("Table '{}' already exists.", name));
+                }
+                let mut table = self.tables.remove(&alter_table.table_name).unwrap();
+                table.name = name.clone();
+                self.tables.insert(name.clone(), table);
+                for foreign_key in self.foreign_keys_to(&alter_table.table_name) {
+                    foreign_key.table = name.clone();
+                }
+                Ok(())
+            }
         }
     }
 
-    pub fn get_table_columns(&self, table_name: &str) -> Result<Vec<Column>, String> {
+    /// Every foreign key, in any table, that references `table_name`.
+    fn foreign_keys_to(&mut self, table_name: &str) -> Vec<&mut ForeignKey> {
         self.tables
+            .values_mut()
+            .flat_map(|table| table.constraints.iter_mut())
+            .filter_map(|constraint| match &mut constraint.kind {
+                ConstraintKind::ForeignKey(foreign_key) if foreign_key.table == table_name => {
+                    Some(foreign_key)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Checks that each foreign key of a table about to have this schema
+    /// references UNIQUE columns of comparable types in an existing table.
+    fn check_foreign_key_schema(
+        &self,
+        table_name: &str,
+        columns: &[Column],
+        constraints: &[Constraint],
+    ) -> Result<(), String> {
+        for constraint in constraints {
+            let ConstraintKind::ForeignKey(foreign_key) = &constraint.kind else {
+                continue;
+            };
+            let (parent_columns, parent_constraints) = if foreign_key.table == table_name {
+                (columns, constraints)
+            } else {
+                let parent = self
+                    .tables
+                    .get(&foreign_key.table)
+                    .ok_or_else(|| format!("Table '{}' does not exist.", foreign_key.table))?;
+                (parent.columns.as_slice(), parent.constraints.as_slice())
+            };
+
+            for (name, referenced) in foreign_key.columns.iter().zip(&foreign_key.referenced) {
+                let column = columns.iter().find(|c| &c.name == name).unwrap();
+                let parent_column = parent_columns
+                    .iter()
+                    .find(|c| &c.name == referenced)
+                    .ok_or_else(|| {
+                        format!(
+                            "Column '{}' not found in table '{}'.",
+                            referenced, foreign_key.table
+                        )
+                    })?;
+                if !comparable(&column.data_type, &parent_column.data_type) {
+                    return Err(format!(
+                        "Foreign key '{}' cannot match {} column '{}' with {} column '{}'.",
+                        constraint.name,
+                        column.data_type,
+                        name,
+                        parent_column.data_type,
+                        referenced
+                    ));
+                }
+            }
+
+            let mut referenced = foreign_key.referenced.clone();
+            referenced.sort();
+            let unique = parent_constraints.iter().any(|c| match &c.kind {
+                ConstraintKind::Unique(columns) => {
+                    let mut columns = columns.clone();
+                    columns.sort();
+                    columns == referenced
+                }
+                _ => false,
+            });
+            if !unique {
+                return Err(format!(
+                    "There is no unique constraint on {} ({}) for foreign key '{}' to reference.",
+                    foreign_key.table,
+                    foreign_key.referenced.join(", "),
+                    constraint.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks foreign keys as they would stand after `writes` to `table`: the
+    /// rows written must reference existing rows, and rows elsewhere that
+    /// reference `table` must still find theirs after an update.
+    fn check_foreign_keys(&self, table: &Table, writes: &[RowWrite]) -> Result<(), String> {
+        let after = table.rows_after(writes);
+        let written = match writes.is_empty() {
+            // No writes means checking every row, as after ALTER TABLE
+            true => after.clone(),
+            false => writes
+                .iter()
+                .map(|write| match write {
+                    RowWrite::Insert(values) | RowWrite::Update(_, values) => values.as_slice(),
+                })
+                .collect(),
+        };
+
+        for constraint in &table.constraints {
+            let ConstraintKind::ForeignKey(foreign_key) = &constraint.kind else {
+                continue;
+            };
+            let (parent, parent_rows) = match foreign_key.table == table.name {
+                true => (table, after.clone()),
+                false => {
+                    let parent = &self.tables[&foreign_key.table];
+                    (parent, parent.rows_after(&[]))
+                }
+            };
+            if let Some(key) =
+                missing_reference(table, foreign_key, &written, parent, &parent_rows)?
+            {
+                return Err(format!(
+                    "Key {} is not present in table '{}', violating foreign key '{}'.",
+                    key, foreign_key.table, constraint.name
+                ));
+            }
+        }
+
+        if !writes
+            .iter()
+            .any(|write| matches!(write, RowWrite::Update(..)))
+        {
+            return Ok(());
+        }
+        for child in self.tables.values() {
+            for constraint in &child.constraints {
+                let ConstraintKind::ForeignKey(foreign_key) = &constraint.kind else {
+                    continue;
+                };
+                if foreign_key.table != table.name {
+                    continue;
+                }
+                let child_rows = match child.name == table.name {
+                    true => after.clone(),
+                    false => child.rows_after(&[]),
+                };
+                if let Some(key) =
+                    missing_reference(child, foreign_key, &child_rows, table, &after)?
+                {
+                    return Err(format!(
+                        "Key {} is still referenced from table '{}' by foreign key '{}'.",
+                        key, child.name, constraint.name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Plans rows into `table_name`, checks its foreign keys and stores them.
+    /// Also returns the last identity value inserted, which later calls to
+    /// `last_insert_id()` report.
+    fn write_rows(
+        &mut self,
+        table_name: &str,
+        columns: Option<&[String]>,
+        rows: Vec<Vec<Option<Value>>>,
+        overriding: bool,
+        on_conflict: Option<&OnConflict>,
+    ) -> Result<(Vec<Row>, Option<i32>), String> {
+        let on_conflict = on_conflict.map(|on_conflict| self.bind_on_conflict(on_conflict));
+        let on_conflict = on_conflict.transpose()?;
+
+        let Database {
+            tables,
+            sequences,
+            last_insert_id,
+            ..
+        } = self;
+        let mut evaluate_default = |default: &Expression| {
+            bind_state_calls(sequences, *last_insert_id, default)?.evaluate(&[], &[])
+        };
+        let writes = tables.get_mut(table_name).unwrap().plan_insert(
+            columns,
+            rows,
+            overriding,
+            on_conflict.as_ref(),
+            &mut evaluate_default,
+        )?;
+
+        self.check_foreign_keys(&self.tables[table_name], &writes)?;
+        let inserts = writes
+            .iter()
+            .any(|write| matches!(write, RowWrite::Insert(_)));
+        let table = self.tables.get_mut(table_name).unwrap();
+        let written = table.apply_writes(writes)?;
+        let mut inserted_id = None;
+        if inserts && table.columns.iter().any(|column| column.identity.is_some()) {
+            inserted_id = table.last_insert_id();
+            self.last_insert_id = inserted_id;
+        }
+        Ok((written, inserted_id))
+    }
+
+    /// Evaluates the functions that need database state in a copy of
+    /// `expression`; see `call_state_function`. Enum types in casts are
+    /// looked up too.
+    fn bind(&mut self, expression: &Expression) -> Result<Expression, String> {
+        let mut bound = bind_state_calls(&mut self.sequences, self.last_insert_id, expression)?;
+        bound.resolve_types(&|data_type| self.resolve_type(data_type))?;
+        Ok(bound)
+    }
+
+    fn bind_on_conflict(&mut self, on_conflict: &OnConflict) -> Result<OnConflict, String> {
+        let mut bound = on_conflict.clone();
+        if let ConflictAction::Update(assignments) = &mut bound.action {
+            for assignment in assignments {
+                assignment.value = self.bind(&assignment.value)?;
+            }
+        }
+        Ok(bound)
+    }
+
+    /// Inserts every row of `insert`, or none of them if any row fails.
+    ///
+    /// Rows updated by ON CONFLICT count as affected and are returned too.
+    pub fn insert_into_table(&mut self, insert: &Insert) -> Result<WriteResult, String> {
+        if !self.tables.contains_key(&insert.table_name) {
+            return Err(format!("Table '{}' does not exist.", insert.table_name));
+        }
+
+        // `None` marks a DEFAULT placeholder
+        let rows = match &insert.source {
+            InsertSource::Values(rows) => rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|value| match value {
+                            InsertValue::Expression(expression) => {
+                                self.bind(expression)?.evaluate(&[], &[]).map(Some)
+                            }
+                            InsertValue::Default => Ok(None),
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            // The query runs to completion first, so a table can be copied into itself
+            InsertSource::Select(select) => self
+                .select_from_table(select)?
+                .rows
+                .into_iter()
+                .map(|row| row.values.into_iter().map(Some).collect())
+                .collect(),
+        };
+
+        // Rows can't be taken back once written, so RETURNING is checked first
+        if let Some(items) = &insert.returning {
+            let table = &self.tables[&insert.table_name];
+            for item in items {
+                if let SelectItem::Expression { expression, .. } = item {
+                    for name in expression.column_names() {
+                        table.column_index(&name)?;
+                    }
+                }
+            }
+        }
+
+        let (written, last_insert_id) = self.write_rows(
+            &insert.table_name,
+            insert.columns.as_deref(),
+            rows,
+            insert.overriding,
+            insert.on_conflict.as_ref(),
+        )?;
+        let affected = written.len();
+        let returning = match &insert.returning {
+            Some(items) => Some(project(
+                &self.tables[&insert.table_name].columns,
+                written,
+                items,
+            )?),
+            None => None,
+        };
+        Ok(WriteResult {
+            affected,
+            returning,
+            last_insert_id,
+        })
+    }
+
+    /// Runs a query. Calls such as `nextval` are evaluated again for each row
+    /// they are used on.
+    pub fn select_from_table(&mut self, select: &Select) -> Result<ResultSet, String> {
+        let mut select = select.clone();
+        let mut expressions = select
+            .columns
+            .iter_mut()
+            .filter_map(|item| match item {
+                SelectItem::Expression { expression, .. } => Some(expression),
+                SelectItem::Wildcard => None,
+            })
+            .chain(select.where_clause.as_mut())
+            .chain(
+                select
+                    .order_by
+                    .iter_mut()
+                    .map(|order| &mut order.expression),
+            )
+            .collect::<Vec<_>>();
+        let mut per_row = false;
+        for expression in &mut expressions {
+            expression.resolve_types(&|data_type| self.resolve_type(data_type))?;
+            per_row |= expression.calls(is_state_function);
+        }
+
+        let source = match &select.from {
+            Some(FromItem::Table(table_name)) => {
+                let table = self
+                    .tables
+                    .get(table_name)
+                    .ok_or_else(|| format!("Table '{}' does not exist.", table_name))?;
+                Some(ResultSet {
+                    columns: table.columns.clone(),
+                    rows: table.select()?,
+                })
+            }
+            Some(FromItem::Function { name, args }) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.bind(arg)?.evaluate(&[], &[]))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Some(call_table_function(name, args)?)
+            }
+            None => None,
+        };
+        let (sequences, last_insert_id) = (&mut self.sequences, self.last_insert_id);
+        execute_select(source, &select, &mut |expression| match per_row {
+            true => bind_state_calls(sequences, last_insert_id, expression).map(Some),
+            false => Ok(None),
+        })
+    }
+
+    /// Loads CSV records into `table_name`, creating the table from the data when missing.
+    ///
+    /// Rows that fail to convert are skipped and reported with their line number.
+    pub fn import_csv<R: BufRead>(
+        &mut self,
+        table_name: &str,
+        reader: R,
+        header: bool,
+    ) -> Result<ImportSummary, String> {
+        let mut records = CsvReader::new(reader);
+        let mut summary = ImportSummary::default();
+
+        let header_fields = if header {
+            match records.next() {
+                Some(record) => Some(record?.fields),
+                None => return Ok(summary),
+            }
+        } else {
+            None
+        };
+
+        if !self.tables.contains_key(table_name) {
+            // Type inference needs to see every row before the table can be created
+            let buffered = records.collect::<Result<Vec<CsvRecord>, String>>()?;
+            let width = header_fields
+                .as_ref()
+                .map(|fields| fields.len())
+                .or_else(|| buffered.first().map(|record| record.fields.len()))
+                .ok_or_else(|| "Cannot create a table from an empty CSV file.".to_string())?;
+
+            let columns = (0..width)
+                .map(|index| {
+                    let samples = buffered
+                        .iter()
+                        .filter_map(|record| record.fields.get(index).map(String::as_str))
+                        .collect::<Vec<_>>();
+                    Column {
+                        name: header_fields
+                            .as_ref()
+                            .map(|fields| fields[index].clone())
+                            .unwrap_or_else(|| format!("column{}", index + 1)),
+                        data_type: DataType::infer(&samples),
+                        default: None,
+                        not_null: false,
+                        identity: None,
+                        collation: Collation::default(),
+                    }
+                })
+                .collect();
+            self.create_table(&CreateTable {
+                table_name: table_name.to_string(),
+                columns,
+                constraints: vec![],
+            })?;
+            summary.table_created = true;
+
+            let table = self.tables.get_mut(table_name).unwrap();
+            for record in buffered {
+                match table.insert(&record.fields) {
+                    Ok(()) => summary.rows_imported += 1,
+                    Err(e) => summary.errors.push((record.line, e)),
+                }
+            }
+            return Ok(summary);
+        }
+
+        for record in records {
+            let record = record?;
+            let row = self.tables[table_name].text_row(&record.fields);
+            match self.write_rows(table_name, None, vec![row], false, None) {
+                Ok(_) => summary.rows_imported += 1,
+                Err(e) => summary.errors.push((record.line, e)),
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Writes every row of `table_name` as RFC 4180 CSV and returns the row count.
+    pub fn export_csv<W: Write>(
+        &self,
+        table_name: &str,
+        writer: &mut W,
+        header: bool,
+    ) -> Result<usize, String> {
+        let table = self
+            .tables
+            .get(table_name)
+            .ok_or_else(|| format!("Table '{}' does not exist.", table_name))?;
+
+        if header {
+            let names = table
+                .columns
+                .iter()
+                .map(|column| column.name.clone())
+                .collect::<Vec<_>>();
+            write_record(writer, &names).map_err(|e| e.to_string())?;
+        }
+
+        let rows = table.select()?;
+        for row in &rows {
+            // NULL is written as an empty field, which import reads back as NULL
+            let fields = row
+                .values
+                .iter()
+                .map(|v| match v {
+                    Value::Null => String::new(),
+                    v => v.to_string(),
+                })
+                .collect::<Vec<_>>();
+            write_record(writer, &fields).map_err(|e| e.to_string())?;
+        }
+        Ok(rows.len())
+    }
+
+    /// Writes every table as `CREATE TABLE` and `INSERT` statements, ordered by table name.
+    pub fn dump<W: Write>(&self, writer: &mut W) -> Result<(), String> {
+        // Types and sequences come first, as columns use them and column
+        // defaults may call them
+        let mut types = self.types.values().collect::<Vec<_>>();
+        types.sort_by(|a, b| a.name.cmp(&b.name));
+        for enum_type in types {
+            let create_type = CreateType {
+                enum_type: enum_type.clone(),
+            };
+            writeln!(writer, "{};", create_type).map_err(|e| e.to_string())?;
+        }
+
+        let mut sequences = self.sequences.values().collect::<Vec<_>>();
+        sequences.sort_by(|a, b| a.name.cmp(&b.name));
+        for sequence in sequences {
+            let create_sequence = CreateSequence {
+                sequence: sequence.clone(),
+            };
+            writeln!(writer, "{};", create_sequence).map_err(|e| e.to_string())?;
+        }
+
+        let mut pending = self.tables.keys().collect::<Vec<_>>();
+        pending.sort();
+
+        // Referenced tables go first so every foreign key finds its rows on
+        // reload. When tables reference each other, the first by name goes
+        // without the keys to tables not yet written, and they are added back
+        // once every row is in.
+        let mut dumped: Vec<&String> = Vec::new();
+        let mut deferred = Vec::new();
+        while !pending.is_empty() {
+            let ready = pending.iter().position(|name| {
+                foreign_keys(&self.tables[*name].constraints).all(|foreign_key| {
+                    foreign_key.table == **name || dumped.contains(&&foreign_key.table)
+                })
+            });
+            let name = pending.remove(ready.unwrap_or(0));
+            let table = &self.tables[name];
+            let (constraints, later): (Vec<_>, Vec<_>) = table
+                .constraints
+                .iter()
+                .cloned()
+                .partition(|constraint| match &constraint.kind {
+                    ConstraintKind::ForeignKey(foreign_key) => {
+                        foreign_key.table == *name || dumped.contains(&&foreign_key.table)
+                    }
+                    _ => true,
+                });
+            self.write_table(table, constraints, writer)?;
+            deferred.extend(later.into_iter().map(|constraint| AlterTable {
+                table_name: name.clone(),
+                action: AlterAction::AddConstraint(constraint),
+            }));
+            dumped.push(name);
+        }
+        for alter_table in deferred {
+            writeln!(writer, "{};", alter_table).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    pub fn dump_table<W: Write>(&self, table_name: &str, writer: &mut W) -> Result<(), String> {
+        let table = self
+            .tables
             .get(table_name)
-            .map(|table| table.columns.clone())
-            .ok_or_else(|| format!("Table '{}' does not exist.", table_name))
+            .ok_or_else(|| format!("Table '{}' does not exist.", table_name))?;
+        self.write_table(table, table.constraints.clone(), writer)
+    }
+
+    /// Writes `CREATE TABLE` with the given constraints, then the rows.
+    fn write_table<W: Write>(
+        &self,
+        table: &Table,
+        constraints: Vec<Constraint>,
+        writer: &mut W,
+    ) -> Result<(), String> {
+        let create_table = CreateTable {
+            table_name: table.name.clone(),
+            columns: table.columns.clone(),
+            constraints,
+        };
+        writeln!(writer, "{};", create_table).map_err(|e| e.to_string())?;
+
+        let rows = table
+            .select()?
+            .iter()
+            .map(|row| {
+                let values = row
+                    .values
+                    .iter()
+                    .map(|value| value.to_sql_literal())
+                    .collect::<Vec<_>>();
+                format!("({})", values.join(", "))
+            })
+            .collect::<Vec<_>>();
+        // Rows may reference rows stored after them, which one statement allows
+        let self_referencing =
+            foreign_keys(&table.constraints).any(|foreign_key| foreign_key.table == table.name);
+        let statements = match self_referencing && !rows.is_empty() {
+            true => vec![rows.join(", ")],
+            false => rows,
+        };
+        // Stored identity values are restored as they are
+        let overriding = match table
+            .columns
+            .iter()
+            .any(|column| matches!(&column.identity, Some(identity) if identity.always))
+        {
+            true => " OVERRIDING SYSTEM VALUE",
+            false => "",
+        };
+        for values in statements {
+            writeln!(
+                writer,
+                "INSERT INTO {}{} VALUES {};",
+                quote_identifier(&table.name),
+                overriding,
+                values
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+fn bind_state_calls(
+    sequences: &mut HashMap<String, Sequence>,
+    last_insert_id: Option<i32>,
+    expression: &Expression,
+) -> Result<Expression, String> {
+    let mut bound = expression.clone();
+    bound.replace_calls(&mut |name, args| {
+        call_state_function(sequences, last_insert_id, name, args)
+    })?;
+    Ok(bound)
+}
+
+/// Types whose values can be compared for a foreign key match.
+fn comparable(a: &DataType, b: &DataType) -> bool {
+    use DataType::*;
+    matches!(
+        (a, b),
+        (Char(_) | Varchar(_) | Text, Char(_) | Varchar(_) | Text)
+    ) || (a.is_numeric() && b.is_numeric())
+        || std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+fn foreign_keys(constraints: &[Constraint]) -> impl Iterator<Item = &ForeignKey> {
+    constraints
+        .iter()
+        .filter_map(|constraint| match &constraint.kind {
+            ConstraintKind::ForeignKey(foreign_key) => Some(foreign_key),
+            _ => None,
+        })
+}
+
+/// Finds the first of `rows` whose key has no match in `parent_rows`, and
+/// describes it like `(a, b) = (1, 2)`. A key with a NULL part matches nothing
+/// and needs nothing.
+fn missing_reference(
+    child: &Table,
+    foreign_key: &ForeignKey,
+    rows: &[&[Value]],
+    parent: &Table,
+    parent_rows: &[&[Value]],
+) -> Result<Option<String>, String> {
+    let key = foreign_key
+        .columns
+        .iter()
+        .map(|name| child.column_index(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let parent_key = foreign_key
+        .referenced
+        .iter()
+        .map(|name| parent.column_index(name))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for row in rows {
+        if key.iter().any(|&index| row[index] == Value::Null) {
+            continue;
+        }
+        let found = parent_rows.iter().any(|parent_row| {
+            key.iter().zip(&parent_key).all(|(&index, &parent_index)| {
+                parent_row[parent_index] != Value::Null
+                    && row[index].compare(&parent_row[parent_index]) == Ok(Ordering::Equal)
+            })
+        });
+        if !found {
+            let values = key
+                .iter()
+                .map(|&index| row[index].to_string())
+                .collect::<Vec<_>>();
+            return Ok(Some(format!(
+                "({}) = ({})",
+                foreign_key.columns.join(", "),
+                values.join(", ")
+            )));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::data::Value;
+    use crate::db::decimal::Decimal;
+    use crate::db::expression::Expression;
+    use crate::db::json::Json;
+    use crate::db::statement::Statement;
+    use crate::parser::{parse_statement, split_statements};
+
+    fn run(db: &mut Database, script: &str) {
+        for statement in split_statements(script) {
+            try_run(db, &statement).unwrap();
+        }
+    }
+
+    /// Runs one statement that changes the database, for those expected to fail.
+    fn try_run(db: &mut Database, sql: &str) -> Result<(), String> {
+        match parse_statement(sql).map_err(|e| e.message)? {
+            Statement::CreateTable(create_table) => db.create_table(&create_table),
+            Statement::Insert(insert) => db.insert_into_table(&insert).map(|_| ()),
+            Statement::CreateTableAs(create) => db.create_table_as(&create),
+            Statement::AlterTable(alter) => db.alter_table(&alter),
+            Statement::CreateSequence(create) => db.create_sequence(&create),
+            Statement::CreateType(create) => db.create_type(&create),
+            other => panic!("unexpected statement: {}", other),
+        }
+    }
+
+    fn insert(db: &mut Database, sql: &str) -> Result<WriteResult, String> {
+        match parse_statement(sql).unwrap() {
+            Statement::Insert(insert) => db.insert_into_table(&insert),
+            other => panic!("unexpected statement: {}", other),
+        }
+    }
+
+    fn query(db: &mut Database, sql: &str) -> ResultSet {
+        match parse_statement(sql).unwrap() {
+            Statement::Select(select) => db.select_from_table(&select).unwrap(),
+            other => panic!("unexpected statement: {}", other),
+        }
+    }
+
+    fn dump_to_string(db: &Database) -> String {
+        let mut out = Vec::new();
+        db.dump(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn dump_round_trips_every_value_type() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE people (id INT, initial CHAR, active BOOLEAN, score FLOAT, \
+             name VARCHAR(20), born DATE);
+             INSERT INTO people VALUES (1, 'J', TRUE, 1.5, 'O''Brien; Jr', '2000-01-02');
+             INSERT INTO people VALUES (-2, '''', FALSE, -0.1, '', DATE '1999-12-31');
+             INSERT INTO people VALUES (3, NULL, NULL, 2, NULL, NULL);
+             CREATE TABLE empty (id INT DEFAULT 1 + 1, note VARCHAR(9) DEFAULT 'n/a');
+             CREATE TABLE \"Order\" (\"select\" INT, \"Mixed Case\" VARCHAR(5));
+             INSERT INTO \"Order\" VALUES (1, E'a\\nb');",
+        );
+
+        let dump = dump_to_string(&db);
+        let mut restored = Database::new();
+        run(&mut restored, &dump);
+
+        assert_eq!(dump, dump_to_string(&restored));
+        for (name, table) in &db.tables {
+            let other = &restored.tables[name];
+            assert_eq!(table.select(), other.select());
+            let types = |t: &Table| {
+                t.columns
+                    .iter()
+                    .map(|c| (c.name.clone(), c.data_type.clone()))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(types(table), types(other));
+        }
+    }
+
+    #[test]
+    fn csv_import_export_round_trip() {
+        let csv = "id,note,born\r\n1,\"two\r\nlines\",2000-01-02\r\n2,,\r\n3,\"a \"\"b\"\", c\",1999-12-31\r\n";
+        let mut db = Database::new();
+        let summary = db.import_csv("people", csv.as_bytes(), true).unwrap();
+        assert!(summary.table_created);
+        assert_eq!(summary.rows_imported, 3);
+        let types = db.tables["people"]
+            .columns
+            .iter()
+            .map(|column| column.data_type.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            [DataType::Int, DataType::Varchar(10), DataType::Date]
+        );
+
+        let mut out = Vec::new();
+        assert_eq!(db.export_csv("people", &mut out, true), Ok(3));
+        assert_eq!(String::from_utf8(out.clone()).unwrap(), csv);
+
+        // Rows that don't fit are reported by the line they start on
+        run(
+            &mut db,
+            "CREATE TABLE short (id INT, note VARCHAR(5), born DATE)",
+        );
+        let summary = db.import_csv("short", out.as_slice(), true).unwrap();
+        assert!(!summary.table_created);
+        assert_eq!(summary.rows_imported, 1);
+        let lines = summary
+            .errors
+            .iter()
+            .map(|(line, _)| *line)
+            .collect::<Vec<_>>();
+        assert_eq!(lines, [2, 5]);
+        assert_eq!(
+            db.tables["short"].select().unwrap()[0].values,
+            [Value::Int(2), Value::Varchar(String::new()), Value::Null]
+        );
+    }
+
+    #[test]
+    fn dump_quotes_special_floats() {
+        let mut db = Database::new();
+        run(&mut db, "CREATE TABLE t (x FLOAT)");
+        db.insert_into_table(&Insert {
+            table_name: "t".to_string(),
+            columns: None,
+            source: InsertSource::Values(vec![vec![InsertValue::Expression(Expression::Literal(
+                Value::Varchar("NaN".to_string()),
+            ))]]),
+            on_conflict: None,
+            overriding: false,
+            returning: None,
+        })
+        .unwrap();
+
+        let dump = dump_to_string(&db);
+        assert!(dump.contains("VALUES ('NaN')"));
+        let mut restored = Database::new();
+        run(&mut restored, &dump);
+        assert_eq!(restored.tables["t"].select().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn insert_applies_coercion_rules() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (flag BOOLEAN, score FLOAT, id INT, name VARCHAR(5), born DATE)",
+        );
+        let insert = |db: &mut Database, values: &str| {
+            let statement = format!("INSERT INTO t VALUES ({})", values);
+            match parse_statement(&statement).unwrap() {
+                Statement::Insert(insert) => db.insert_into_table(&insert),
+                other => panic!("unexpected statement: {}", other),
+            }
+        };
+
+        assert!(insert(&mut db, "TRUE, 1, '42', 'ab', '2024-01-01'").is_ok());
+        assert!(insert(&mut db, "NULL, NULL, NULL, NULL, NULL").is_ok());
+        assert!(insert(&mut db, "'1', 1, 1, 'ab', NULL").is_err());
+        assert!(insert(&mut db, "TRUE, 1.5, 1.5, 'ab', NULL").is_err());
+        assert!(insert(&mut db, "TRUE, 1, 1, 12, NULL").is_err());
+        assert!(insert(&mut db, "TRUE, 1, 1, 'ab', 20240101").is_err());
+
+        let rows = db.tables["t"].select().unwrap();
+        assert_eq!(rows[0].values[1], Value::Float(1.0));
+        assert_eq!(rows[0].values[2], Value::Int(42));
+        assert_eq!(rows[1].values, vec![Value::Null; 5]);
+    }
+
+    #[test]
+    fn insert_column_list_fills_the_rest_with_defaults() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT, name VARCHAR(10) DEFAULT 'anon', score FLOAT);
+             INSERT INTO t (score, id) VALUES (1.5, 1)",
+        );
+        assert_eq!(
+            db.tables["t"].select().unwrap()[0].values,
+            vec![
+                Value::Int(1),
+                Value::Varchar("anon".to_string()),
+                Value::Float(1.5)
+            ]
+        );
+    }
+
+    #[test]
+    fn insert_default_keyword() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT, name VARCHAR(10) DEFAULT 'anon', score FLOAT);
+             INSERT INTO t VALUES (3, DEFAULT, DEFAULT);
+             INSERT INTO t (name, id) VALUES (DEFAULT, DEFAULT)",
+        );
+        let rows = db.tables["t"].select().unwrap();
+        let anon = Value::Varchar("anon".to_string());
+        // A column without a DEFAULT clause defaults to NULL
+        assert_eq!(
+            rows[0].values,
+            vec![Value::Int(3), anon.clone(), Value::Null]
+        );
+        assert_eq!(rows[1].values, vec![Value::Null, anon, Value::Null]);
+    }
+
+    #[test]
+    fn insert_multi_row_values() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT, name VARCHAR(10));
+             INSERT INTO t VALUES (1, 'a'), (2, 'b'), (3, NULL)",
+        );
+        let ids = db.tables["t"]
+            .select()
+            .unwrap()
+            .iter()
+            .map(|row| row.values[0].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [Value::Int(1), Value::Int(2), Value::Int(3)]);
+    }
+
+    #[test]
+    fn insert_failing_row_rolls_back_the_statement() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT, name VARCHAR(10));
+             INSERT INTO t VALUES (1, 'a')",
+        );
+        assert!(try_run(&mut db, "INSERT INTO t (id) VALUES (4), ('x')").is_err());
+        assert_eq!(db.tables["t"].select().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn insert_rejects_mismatched_column_lists() {
+        let mut db = Database::new();
+        run(&mut db, "CREATE TABLE t (id INT, name VARCHAR(10))");
+        for sql in [
+            "INSERT INTO t (id, name) VALUES (1)",
+            "INSERT INTO t (id) VALUES (1, 'a')",
+            "INSERT INTO t VALUES (1, 'a', 2)",
+            "INSERT INTO t VALUES (1, 'a'), (2)",
+            "INSERT INTO t (missing) VALUES (1)",
+        ] {
+            assert!(try_run(&mut db, sql).is_err(), "{}", sql);
+        }
+        assert!(db.tables["t"].select().unwrap().is_empty());
+    }
+
+    #[test]
+    fn insert_select_converts_types() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE src (id INT, name VARCHAR(10));
+             CREATE TABLE dst (id BIGINT, score DOUBLE, name TEXT);
+             INSERT INTO src VALUES (1, 'a'), (2, 'bb');
+             INSERT INTO dst SELECT id, id, name FROM src",
+        );
+        let rows = db.tables["dst"].select().unwrap();
+        assert_eq!(
+            rows[1].values,
+            vec![
+                Value::BigInt(2),
+                Value::Double(2.0),
+                Value::Varchar("bb".to_string())
+            ]
+        );
+
+        // Values from a query follow the same coercion rules as literals
+        assert!(try_run(&mut db, "INSERT INTO src (id) SELECT name FROM src").is_err());
+        assert_eq!(db.tables["src"].select().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn insert_select_reads_the_table_before_writing() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE src (id INT, name VARCHAR(10), score FLOAT);
+             INSERT INTO src VALUES (1, 'a', 1.5), (2, 'bb', NULL);
+             INSERT INTO src (name, id) SELECT name, id + 10 FROM src",
+        );
+        let rows = db.tables["src"].select().unwrap();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(
+            rows[2].values,
+            vec![Value::Int(11), Value::Varchar("a".to_string()), Value::Null]
+        );
+    }
+
+    #[test]
+    fn insert_select_rejects_a_column_count_mismatch() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT, name VARCHAR(10));
+             INSERT INTO t VALUES (1, 'a')",
+        );
+        assert!(try_run(&mut db, "INSERT INTO t SELECT id FROM t").is_err());
+        assert!(try_run(&mut db, "INSERT INTO t (id) SELECT id, name FROM t").is_err());
+        assert_eq!(db.tables["t"].select().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn ctas_copies_column_types_and_rows() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE src (id INT, name VARCHAR(10), score FLOAT);
+             INSERT INTO src VALUES (1, 'a', 1.5), (2, 'bb', NULL);
+             CREATE TABLE derived AS SELECT id, UPPER(name) AS shout, id * 2, score FROM src",
+        );
+        let types = db.tables["derived"]
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.data_type.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                ("id", DataType::Int),
+                ("shout", DataType::Text),
+                ("id * 2", DataType::Int),
+                ("score", DataType::Float),
+            ]
+        );
+        let rows = db.tables["derived"].select().unwrap();
+        assert_eq!(
+            rows[1].values,
+            vec![
+                Value::Int(2),
+                Value::Varchar("BB".to_string()),
+                Value::Int(4),
+                Value::Null
+            ]
+        );
+    }
+
+    #[test]
+    fn ctas_rejects_an_existing_table() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT);
+             INSERT INTO t VALUES (1)",
+        );
+        assert_eq!(
+            try_run(&mut db, "CREATE TABLE t AS SELECT id FROM t"),
+            Err("Table 't' already exists.".to_string())
+        );
+        assert_eq!(db.tables["t"].select().unwrap().len(), 1);
+    }
+
+    fn column_types(db: &Database, table: &str) -> Vec<DataType> {
+        db.tables[table]
+            .columns
+            .iter()
+            .map(|column| column.data_type.clone())
+            .collect()
+    }
+
+    #[test]
+    fn ctas_empty_result_keeps_types() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (a INT, b VARCHAR(3), d DATE);
+             INSERT INTO t VALUES (1, 'x', '2024-01-31');
+             CREATE TABLE d AS SELECT a * 2 AS dbl, b || 'y' AS s, d + 1 AS next,
+                 CAST(a AS BIGINT) AS big, a > 0 AS positive FROM t WHERE a > 5;
+             CREATE TABLE sums AS SELECT SUM(a), AVG(a) FROM t WHERE a > 5",
+        );
+        assert_eq!(
+            column_types(&db, "d"),
+            vec![
+                DataType::Int,
+                DataType::Text,
+                DataType::Date,
+                DataType::BigInt,
+                DataType::Boolean
+            ]
+        );
+        assert_eq!(
+            column_types(&db, "sums"),
+            vec![DataType::BigInt, DataType::Decimal(38, 6)]
+        );
+        run(
+            &mut db,
+            "INSERT INTO d VALUES (4, 'a longer string', '2024-02-01', 5, TRUE)",
+        );
+        assert_eq!(db.tables["d"].select().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn ctas_text_is_not_sized_to_rows() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (b VARCHAR(3));
+             INSERT INTO t VALUES ('x'), ('xy');
+             CREATE TABLE e AS SELECT b || 'y' AS s, UPPER(b) AS u, 'lit' AS l FROM t;
+             INSERT INTO e VALUES ('much longer than before', 'also longer', 'and this')",
+        );
+        assert_eq!(
+            column_types(&db, "e"),
+            vec![DataType::Text, DataType::Text, DataType::Text]
+        );
+        assert_eq!(db.tables["e"].select().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn ctas_arithmetic_widens_decimals() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (a DECIMAL(4, 2), b DECIMAL(3, 1), i INT);
+             INSERT INTO t VALUES (99.99, 99.9, 7);
+             CREATE TABLE r AS SELECT a + b, a * b, a / b, i + a, i * 2 FROM t",
+        );
+        assert_eq!(
+            column_types(&db, "r"),
+            vec![
+                DataType::Decimal(5, 2),
+                DataType::Decimal(7, 3),
+                DataType::Decimal(11, 8),
+                DataType::Decimal(13, 2),
+                DataType::Int
+            ]
+        );
+        let rows = db.tables["r"].select().unwrap();
+        assert_eq!(rows[0].values[0].to_string(), "199.89");
+        assert_eq!(rows[0].values[1].to_string(), "9989.001");
+    }
+
+    fn stock(db: &Database) -> Vec<(String, Value)> {
+        db.tables["stock"]
+            .select()
+            .unwrap()
+            .iter()
+            .map(|row| (row.values[0].to_string(), row.values[1].clone()))
+            .collect()
+    }
+
+    #[test]
+    fn on_conflict_do_update_sees_excluded_values() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE stock (sku VARCHAR(5) UNIQUE, qty INT);
+             INSERT INTO stock VALUES ('a', 1), ('b', 2)",
+        );
+        let result = insert(
+            &mut db,
+            "INSERT INTO stock VALUES ('a', 5), ('c', 1) \
+             ON CONFLICT (sku) DO UPDATE SET qty = stock.qty + excluded.qty",
+        );
+        // Both the updated row and the inserted one count as affected
+        assert_eq!(result.map(|r| r.affected), Ok(2));
+        assert_eq!(
+            stock(&db),
+            vec![
+                ("a".to_string(), Value::Int(6)),
+                ("b".to_string(), Value::Int(2)),
+                ("c".to_string(), Value::Int(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn on_conflict_do_nothing_skips_conflicting_rows() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE stock (sku VARCHAR(5) UNIQUE, qty INT);
+             INSERT INTO stock VALUES ('b', 2)",
+        );
+        // The second 'd' conflicts with the first, inserted by the same statement
+        let result = insert(
+            &mut db,
+            "INSERT INTO stock VALUES ('b', 9), ('d', 4), ('d', 5) ON CONFLICT (sku) DO NOTHING",
+        );
+        assert_eq!(result.map(|r| r.affected), Ok(1));
+        assert_eq!(
+            stock(&db),
+            vec![
+                ("b".to_string(), Value::Int(2)),
+                ("d".to_string(), Value::Int(4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn on_conflict_do_update_rejects_updating_a_row_twice() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE stock (sku VARCHAR(5) UNIQUE, qty INT);
+             INSERT INTO stock VALUES ('a', 1)",
+        );
+        assert!(insert(
+            &mut db,
+            "INSERT INTO stock VALUES ('a', 1), ('a', 2) ON CONFLICT (sku) DO UPDATE SET qty = 0",
+        )
+        .is_err());
+        assert_eq!(stock(&db), vec![("a".to_string(), Value::Int(1))]);
+    }
+
+    #[test]
+    fn on_conflict_needs_a_unique_target() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE stock (sku VARCHAR(5) UNIQUE, qty INT);
+             INSERT INTO stock VALUES ('a', 1)",
+        );
+        assert_eq!(
+            insert(
+                &mut db,
+                "INSERT INTO stock VALUES ('a', 2) ON CONFLICT (qty) DO NOTHING"
+            )
+            .map(|r| r.affected),
+            Err("There is no unique constraint on (qty) to match ON CONFLICT.".to_string())
+        );
+        assert!(insert(
+            &mut db,
+            "INSERT INTO stock VALUES ('a', 2) ON CONFLICT (sku) DO UPDATE SET missing = 1"
+        )
+        .is_err());
+        assert_eq!(stock(&db), vec![("a".to_string(), Value::Int(1))]);
+    }
+
+    #[test]
+    fn returning_star_and_expressions() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT, name VARCHAR(5) DEFAULT 'x')",
+        );
+        let result = insert(
+            &mut db,
+            "INSERT INTO t (id) VALUES (1), (2) RETURNING *, id * 10 AS tens",
+        )
+        .unwrap();
+        assert_eq!(result.affected, 2);
+        let returning = result.returning.unwrap();
+        let names = returning
+            .columns
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["id", "name", "tens"]);
+        // Defaults are filled in, as the rows were stored
+        assert_eq!(
+            returning.rows[1].values,
+            vec![
+                Value::Int(2),
+                Value::Varchar("x".to_string()),
+                Value::Int(20)
+            ]
+        );
+    }
+
+    #[test]
+    fn returning_includes_rows_updated_on_conflict() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT UNIQUE, name VARCHAR(5) DEFAULT 'x');
+             INSERT INTO t VALUES (1, 'a')",
+        );
+        let result = insert(
+            &mut db,
+            "INSERT INTO t (id) VALUES (1), (2) ON CONFLICT (id) DO UPDATE SET name = 'b' \
+             RETURNING name",
+        )
+        .unwrap();
+        let names = result
+            .returning
+            .unwrap()
+            .rows
+            .into_iter()
+            .map(|r| r.values[0].to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["b", "x"]);
+
+        // Rows skipped by DO NOTHING aren't returned
+        let result = insert(
+            &mut db,
+            "INSERT INTO t VALUES (2, 'c') ON CONFLICT (id) DO NOTHING RETURNING id",
+        )
+        .unwrap();
+        assert_eq!(result.affected, 0);
+        assert!(result.returning.unwrap().rows.is_empty());
+    }
+
+    #[test]
+    fn returning_without_the_clause_is_none() {
+        let mut db = Database::new();
+        run(&mut db, "CREATE TABLE t (id INT)");
+        let result = insert(&mut db, "INSERT INTO t VALUES (1)").unwrap();
+        assert!(result.returning.is_none());
+    }
+
+    #[test]
+    fn returning_an_unknown_column_writes_nothing() {
+        let mut db = Database::new();
+        run(&mut db, "CREATE TABLE t (id INT)");
+        assert_eq!(
+            insert(&mut db, "INSERT INTO t VALUES (1) RETURNING missing").map(|r| r.affected),
+            Err("Column 'missing' not found in table schema.".to_string())
+        );
+        assert!(db.tables["t"].select().unwrap().is_empty());
+    }
+
+    fn column_names(db: &Database, table: &str) -> Vec<String> {
+        db.tables[table]
+            .columns
+            .iter()
+            .map(|column| column.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn alter_table_add_column_fills_its_default() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT);
+             INSERT INTO t VALUES (1), (2);
+             ALTER TABLE t ADD COLUMN active BOOLEAN DEFAULT TRUE;
+             ALTER TABLE t ADD note TEXT",
+        );
+        assert_eq!(column_names(&db, "t"), ["id", "active", "note"]);
+        let rows = db.tables["t"].select().unwrap();
+        assert_eq!(
+            rows[1].values,
+            vec![Value::Int(2), Value::Boolean(true), Value::Null]
+        );
+        // Existing rows would break NOT NULL without a default
+        assert!(try_run(&mut db, "ALTER TABLE t ADD COLUMN n INT NOT NULL").is_err());
+        assert!(try_run(&mut db, "ALTER TABLE t ADD COLUMN id INT").is_err());
+        assert_eq!(column_names(&db, "t"), ["id", "active", "note"]);
+    }
+
+    #[test]
+    fn alter_table_drop_column() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT, code VARCHAR(3));
+             INSERT INTO t VALUES (1, '10');
+             ALTER TABLE t DROP id",
+        );
+        assert_eq!(column_names(&db, "t"), ["code"]);
+        assert_eq!(
+            db.tables["t"].select().unwrap()[0].values,
+            vec![Value::Varchar("10".to_string())]
+        );
+        assert_eq!(
+            try_run(&mut db, "ALTER TABLE t DROP missing"),
+            Err("Column 'missing' not found in table schema.".to_string())
+        );
+        assert_eq!(
+            try_run(&mut db, "ALTER TABLE t DROP code"),
+            Err("Cannot drop 'code', the only column of 't'.".to_string())
+        );
+    }
+
+    #[test]
+    fn alter_table_rename_column_and_table() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT, code VARCHAR(3));
+             CREATE TABLE other (id INT);
+             INSERT INTO t VALUES (1, '10');
+             ALTER TABLE t RENAME COLUMN code TO amount;
+             ALTER TABLE t RENAME TO totals",
+        );
+        assert!(!db.tables.contains_key("t"));
+        assert_eq!(column_names(&db, "totals"), ["id", "amount"]);
+        assert_eq!(db.tables["totals"].select().unwrap().len(), 1);
+
+        assert_eq!(
+            try_run(&mut db, "ALTER TABLE totals RENAME COLUMN amount TO id"),
+            Err("Column 'id' already exists.".to_string())
+        );
+        assert_eq!(
+            try_run(&mut db, "ALTER TABLE totals RENAME TO other"),
+            Err("Table 'other' already exists.".to_string())
+        );
+        assert_eq!(
+            try_run(&mut db, "ALTER TABLE t RENAME TO v"),
+            Err("Table 't' does not exist.".to_string())
+        );
+    }
+
+    #[test]
+    fn alter_column_type_converts_every_row() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (amount VARCHAR(3));
+             INSERT INTO t VALUES ('10'), ('20');
+             ALTER TABLE t ALTER COLUMN amount TYPE INT",
+        );
+        assert_eq!(column_types(&db, "t"), [DataType::Int]);
+        let rows = db.tables["t"].select().unwrap();
+        assert_eq!(rows[1].values, vec![Value::Int(20)]);
+    }
+
+    #[test]
+    fn alter_column_type_failure_leaves_the_table_untouched() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (amount INT);
+             INSERT INTO t VALUES (10), (300)",
+        );
+        // One value that doesn't fit leaves every row and the schema untouched
+        assert!(try_run(&mut db, "ALTER TABLE t ALTER amount TYPE VARCHAR(2)").is_err());
+        assert_eq!(column_types(&db, "t"), [DataType::Int]);
+        assert_eq!(
+            db.tables["t"].select().unwrap()[0].values[0],
+            Value::Int(10)
+        );
+    }
+
+    #[test]
+    fn unique_rejects_duplicates_but_not_nulls() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT UNIQUE, qty INT);
+             INSERT INTO t VALUES (1, 5), (NULL, 1), (NULL, 2)",
+        );
+        // The duplicate may also come from the same statement
+        assert_eq!(
+            try_run(&mut db, "INSERT INTO t VALUES (3, 1), (1, 1)"),
+            Err("Duplicate key (id) = (1) violates unique constraint 't_id_key'.".to_string())
+        );
+        assert_eq!(
+            try_run(&mut db, "INSERT INTO t VALUES (4, 1), (4, 2)"),
+            Err("Duplicate key (id) = (4) violates unique constraint 't_id_key'.".to_string())
+        );
+        assert_eq!(db.tables["t"].select().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn named_unique_spans_several_columns() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (a INT, b INT, CONSTRAINT pair UNIQUE (a, b));
+             INSERT INTO t VALUES (1, 1), (1, 2)",
+        );
+        assert_eq!(
+            try_run(&mut db, "INSERT INTO t VALUES (1, 1)"),
+            Err("Duplicate key (a, b) = (1, 1) violates unique constraint 'pair'.".to_string())
+        );
+        assert_eq!(
+            try_run(
+                &mut db,
+                "CREATE TABLE u (a INT, CONSTRAINT c UNIQUE (a), CONSTRAINT c CHECK (a > 0))"
+            ),
+            Err("Constraint 'c' already exists.".to_string())
+        );
+    }
+
+    #[test]
+    fn not_null_rejects_missing_values() {
+        let mut db = Database::new();
+        run(&mut db, "CREATE TABLE t (id INT NOT NULL, qty INT)");
+        for sql in [
+            "INSERT INTO t (qty) VALUES (1)",
+            "INSERT INTO t VALUES (NULL, 1)",
+            "INSERT INTO t VALUES (DEFAULT, 1)",
+        ] {
+            assert_eq!(
+                try_run(&mut db, sql),
+                Err("Null value in column 'id' violates NOT NULL constraint.".to_string()),
+                "{}",
+                sql
+            );
+        }
+        // Existing rows would be left without a value
+        run(&mut db, "INSERT INTO t VALUES (1, 1)");
+        assert!(try_run(&mut db, "ALTER TABLE t ADD COLUMN note VARCHAR(5) NOT NULL").is_err());
+        assert_eq!(db.tables["t"].select().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn check_rejects_false_but_not_unknown() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT UNIQUE, qty INT DEFAULT 1 CHECK (qty > 0));
+             INSERT INTO t VALUES (1, 5), (2, NULL);
+             INSERT INTO t (id) VALUES (3)",
+        );
+        assert_eq!(
+            try_run(&mut db, "INSERT INTO t VALUES (4, 0)"),
+            Err("Row violates check constraint 't_qty_check'.".to_string())
+        );
+        // Rows changed by ON CONFLICT are checked too
+        assert_eq!(
+            try_run(
+                &mut db,
+                "INSERT INTO t VALUES (1, 1) ON CONFLICT (id) DO UPDATE SET qty = -1"
+            ),
+            Err("Row violates check constraint 't_qty_check'.".to_string())
+        );
+        let rows = db.tables["t"].select().unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2].values, vec![Value::Int(3), Value::Int(1)]);
+        assert_eq!(
+            try_run(&mut db, "CREATE TABLE u (a INT CHECK (missing > 0))"),
+            Err("Constraint 'u_a_check' refers to unknown column 'missing'.".to_string())
+        );
+    }
+
+    #[test]
+    fn constrained_columns_cannot_be_dropped() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT, qty INT CHECK (qty > 0), note TEXT)",
+        );
+        assert!(try_run(&mut db, "ALTER TABLE t DROP COLUMN qty").is_err());
+        assert_eq!(column_names(&db, "t"), ["id", "qty", "note"]);
+    }
+
+    #[test]
+    fn renames_reach_into_constraints() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT NOT NULL UNIQUE, qty INT CHECK (qty > 0));
+             ALTER TABLE t RENAME COLUMN qty TO amount",
+        );
+        assert!(try_run(&mut db, "INSERT INTO t VALUES (3, -1)").is_err());
+        // The schema survives a dump
+        let dump = dump_to_string(&db);
+        assert!(dump.contains("CONSTRAINT t_qty_check CHECK (amount > 0)"));
+        let mut copy = Database::new();
+        run(&mut copy, &dump);
+        assert_eq!(dump_to_string(&copy), dump);
+    }
+
+    const TREE: &str = "CREATE TABLE tree (id INT UNIQUE, up INT REFERENCES tree (id));
+             CREATE TABLE leaf (
+                id INT,
+                tree_id INT,
+                CONSTRAINT to_tree FOREIGN KEY (tree_id) REFERENCES tree (id) ON DELETE RESTRICT
+             );
+             INSERT INTO tree VALUES (2, 1), (1, NULL);
+             INSERT INTO leaf VALUES (1, 2), (2, NULL)";
+
+    #[test]
+    fn foreign_key_rejects_a_missing_parent() {
+        let mut db = Database::new();
+        // A row may reference one inserted later in the same statement,
+        // and NULL references nothing
+        run(&mut db, TREE);
+        assert_eq!(
+            try_run(&mut db, "INSERT INTO leaf VALUES (3, 1), (4, 7)"),
+            Err("Key (tree_id) = (7) is not present in table 'tree', violating foreign key 'to_tree'.".to_string())
+        );
+        assert_eq!(db.tables["leaf"].select().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn foreign_key_blocks_changing_a_referenced_key() {
+        let mut db = Database::new();
+        run(&mut db, TREE);
+        assert_eq!(
+            try_run(
+                &mut db,
+                "INSERT INTO tree VALUES (2, NULL) ON CONFLICT (id) DO UPDATE SET id = 3"
+            ),
+            Err("Key (tree_id) = (2) is still referenced from table 'leaf' by foreign key 'to_tree'.".to_string())
+        );
+        // A key nothing references may change
+        run(
+            &mut db,
+            "INSERT INTO tree VALUES (5, NULL);
+             INSERT INTO tree VALUES (5, NULL) ON CONFLICT (id) DO UPDATE SET id = 6",
+        );
+        assert_eq!(
+            db.tables["tree"].select().unwrap()[2].values[0],
+            Value::Int(6)
+        );
+    }
+
+    #[test]
+    fn foreign_key_must_reference_a_unique_key() {
+        let mut db = Database::new();
+        run(&mut db, TREE);
+        assert_eq!(
+            try_run(&mut db, "CREATE TABLE bad (x INT REFERENCES leaf (id))"),
+            Err("There is no unique constraint on leaf (id) for foreign key 'bad_x_fkey' to reference.".to_string())
+        );
+        assert!(try_run(&mut db, "CREATE TABLE bad (x INT REFERENCES missing (id))").is_err());
+        assert_eq!(
+            try_run(
+                &mut db,
+                "CREATE TABLE bad (x INT REFERENCES tree (missing))"
+            ),
+            Err("Column 'missing' not found in table 'tree'.".to_string())
+        );
+        assert_eq!(
+            try_run(
+                &mut db,
+                "CREATE TABLE bad (a INT, b INT, FOREIGN KEY (a, b) REFERENCES tree (id))"
+            ),
+            Err("Foreign key has 2 columns but references 1".to_string())
+        );
+        assert!(!db.tables.contains_key("bad"));
+    }
+
+    #[test]
+    fn foreign_key_rejects_actions_it_cannot_enforce() {
+        // There is no DELETE to carry out other actions, nor transactions to defer to
+        assert_eq!(
+            parse_statement("CREATE TABLE bad (x INT REFERENCES tree (id) ON DELETE CASCADE)")
+                .map_err(|e| e.message)
+                .err(),
+            Some("ON DELETE CASCADE is not supported, only RESTRICT or NO ACTION".to_string())
+        );
+        assert!(parse_statement(
+            "CREATE TABLE bad (x INT REFERENCES tree (id) ON DELETE SET NULL)"
+        )
+        .is_err());
+        assert!(parse_statement(
+            "CREATE TABLE bad (x INT REFERENCES tree (id) ON DELETE NO ACTION NOT NULL)"
+        )
+        .is_ok());
+        assert!(
+            parse_statement("CREATE TABLE bad (x INT REFERENCES tree (id) DEFERRABLE)").is_err()
+        );
+    }
+
+    #[test]
+    fn foreign_key_on_an_added_column_checks_its_default() {
+        let mut db = Database::new();
+        run(&mut db, TREE);
+        assert!(try_run(
+            &mut db,
+            "ALTER TABLE leaf ADD COLUMN other INT DEFAULT 5 REFERENCES tree (id)"
+        )
+        .is_err());
+        assert_eq!(db.tables["leaf"].columns.len(), 2);
+        run(
+            &mut db,
+            "ALTER TABLE leaf ADD COLUMN other INT DEFAULT 1 REFERENCES tree (id)",
+        );
+        assert_eq!(db.tables["leaf"].columns.len(), 3);
+    }
+
+    #[test]
+    fn foreign_key_follows_renames() {
+        let mut db = Database::new();
+        run(&mut db, TREE);
+        // Renaming the referenced table or column carries over to the keys
+        run(
+            &mut db,
+            "ALTER TABLE tree RENAME COLUMN id TO node;
+             ALTER TABLE tree RENAME TO nodes;
+             INSERT INTO leaf VALUES (3, 1)",
+        );
+        assert!(try_run(&mut db, "INSERT INTO leaf VALUES (4, 7)").is_err());
+        let dump = dump_to_string(&db);
+        assert!(dump.contains("FOREIGN KEY (tree_id) REFERENCES nodes (node)\n"));
+        assert!(dump.find("CREATE TABLE nodes") < dump.find("CREATE TABLE leaf"));
+        let mut copy = Database::new();
+        run(&mut copy, &dump);
+        assert_eq!(dump_to_string(&copy), dump);
+    }
+
+    #[test]
+    fn dump_defers_cyclic_foreign_keys() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE a (id INT UNIQUE);
+             CREATE TABLE b (id INT UNIQUE, aid INT REFERENCES a (id));
+             ALTER TABLE a ADD COLUMN bid INT REFERENCES b (id);
+             INSERT INTO a VALUES (1, NULL);
+             INSERT INTO b VALUES (10, 1);
+             INSERT INTO a VALUES (1, 10) ON CONFLICT (id) DO UPDATE SET bid = 10;
+             CREATE TABLE c (bid INT REFERENCES b (id));
+             INSERT INTO c VALUES (10)",
+        );
+
+        // Rows that reference each other can only be linked once both exist
+        let dump = dump_to_string(&db);
+        assert!(dump.ends_with(
+            "ALTER TABLE a ADD CONSTRAINT a_bid_fkey FOREIGN KEY (bid) REFERENCES b (id);\n"
+        ));
+        assert!(dump.find("INSERT INTO a") < dump.find("CREATE TABLE b"));
+        assert!(dump.find("INSERT INTO b") < dump.find("CREATE TABLE c"));
+        let mut copy = Database::new();
+        run(&mut copy, &dump);
+        assert_eq!(dump_to_string(&copy), dump);
+        assert_eq!(copy.tables["a"].select(), db.tables["a"].select());
+    }
+
+    #[test]
+    fn added_constraints_hold_for_existing_rows() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE a (id INT UNIQUE);
+             CREATE TABLE c (bid INT);
+             INSERT INTO a VALUES (1);
+             INSERT INTO c VALUES (10), (10)",
+        );
+        assert!(try_run(&mut db, "ALTER TABLE c ADD CHECK (bid > 10)").is_err());
+        assert!(try_run(
+            &mut db,
+            "ALTER TABLE c ADD FOREIGN KEY (bid) REFERENCES a (id)"
+        )
+        .is_err());
+        assert!(try_run(&mut db, "ALTER TABLE c ADD UNIQUE (bid)").is_err());
+        assert!(db.tables["c"].constraints.is_empty());
+
+        run(
+            &mut db,
+            "ALTER TABLE c ADD CHECK (bid >= 10);
+             INSERT INTO a VALUES (10);
+             ALTER TABLE c ADD FOREIGN KEY (bid) REFERENCES a (id)",
+        );
+        assert_eq!(db.tables["c"].constraints.len(), 2);
+        assert!(try_run(&mut db, "INSERT INTO c VALUES (11)").is_err());
+    }
+
+    fn first_row(db: &mut Database, sql: &str) -> Result<Vec<Value>, String> {
+        match parse_statement(sql).unwrap() {
+            Statement::Select(select) => db
+                .select_from_table(&select)
+                .map(|result| result.rows[0].values.clone()),
+            other => panic!("unexpected statement: {}", other),
+        }
+    }
+
+    #[test]
+    fn identity_always_rejects_explicit_values() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT GENERATED ALWAYS AS IDENTITY, name VARCHAR(5));
+             INSERT INTO t (name) VALUES ('a'), ('b')",
+        );
+        assert!(try_run(&mut db, "INSERT INTO t VALUES (9, 'c')").is_err());
+        let result = insert(&mut db, "INSERT INTO t VALUES (DEFAULT, 'c')").unwrap();
+        assert_eq!(result.last_insert_id, Some(3));
+        // OVERRIDING SYSTEM VALUE lets one in, as a dump needs
+        let result = insert(
+            &mut db,
+            "INSERT INTO t OVERRIDING SYSTEM VALUE VALUES (50, 'd')",
+        )
+        .unwrap();
+        assert_eq!(result.last_insert_id, Some(50));
+        assert_eq!(
+            try_run(
+                &mut db,
+                "CREATE TABLE v (id VARCHAR(3) GENERATED ALWAYS AS IDENTITY)"
+            ),
+            Err("Identity column 'id' must be INT, not VARCHAR(3).".to_string())
+        );
+    }
+
+    #[test]
+    fn identity_by_default_keeps_its_counter() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE u (id INT AUTOINCREMENT, name VARCHAR(5))",
+        );
+        // Explicit values go in without moving the counter
+        insert(&mut db, "INSERT INTO u VALUES (50, 'x')").unwrap();
+        let result = insert(&mut db, "INSERT INTO u (name) VALUES ('y')").unwrap();
+        assert_eq!(result.last_insert_id, Some(1));
+    }
+
+    #[test]
+    fn identity_values_are_used_up_by_failed_inserts() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT GENERATED ALWAYS AS IDENTITY, n INT CHECK (n > 0))",
+        );
+        assert!(try_run(&mut db, "INSERT INTO t (n) VALUES (1), (0)").is_err());
+        let result = insert(&mut db, "INSERT INTO t (n) VALUES (2)").unwrap();
+        assert_eq!(result.last_insert_id, Some(3));
+    }
+
+    #[test]
+    fn sequence_as_a_column_default() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE SEQUENCE codes START WITH 10 INCREMENT BY 5;
+             CREATE TABLE t (id INT GENERATED ALWAYS AS IDENTITY, code INT DEFAULT nextval('codes'));
+             INSERT INTO t (id) VALUES (DEFAULT), (DEFAULT)",
+        );
+        let rows = db.tables["t"].select().unwrap();
+        assert_eq!(rows[1].values, [Value::Int(2), Value::Int(15)]);
+        assert_eq!(
+            first_row(&mut db, "SELECT last_insert_id(), currval('codes')"),
+            Ok(vec![Value::Int(2), Value::Int(15)])
+        );
+    }
+
+    #[test]
+    fn setval_and_sequence_errors() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE SEQUENCE codes START WITH 10 INCREMENT BY 5",
+        );
+        assert_eq!(
+            first_row(&mut db, "SELECT currval('codes')"),
+            Err("Sequence 'codes' has not handed out a value yet in this session.".to_string())
+        );
+        assert_eq!(
+            first_row(&mut db, "SELECT setval('codes', 100), nextval('codes')"),
+            Ok(vec![Value::Int(100), Value::Int(105)])
+        );
+        assert_eq!(
+            first_row(&mut db, "SELECT nextval('missing')"),
+            Err("Sequence 'missing' does not exist.".to_string())
+        );
+        assert_eq!(
+            try_run(&mut db, "CREATE SEQUENCE codes"),
+            Err("Sequence 'codes' already exists.".to_string())
+        );
+    }
+
+    #[test]
+    fn counters_survive_a_dump() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE SEQUENCE codes START WITH 10 INCREMENT BY 5;
+             CREATE TABLE t (
+                id INT GENERATED ALWAYS AS IDENTITY,
+                code INT DEFAULT nextval('codes'),
+                name VARCHAR(5)
+             );
+             INSERT INTO t (name) VALUES ('a'), ('b'), ('c')",
+        );
+        let dump = dump_to_string(&db);
+        assert!(dump.contains("CREATE SEQUENCE codes START WITH 25 INCREMENT BY 5;"));
+        assert!(dump.contains("INSERT INTO t OVERRIDING SYSTEM VALUE VALUES (3, 20, 'c');"));
+        let mut copy = Database::new();
+        run(&mut copy, &dump);
+        assert_eq!(dump_to_string(&copy), dump);
+        let result = insert(&mut copy, "INSERT INTO t (name) VALUES ('d')").unwrap();
+        assert_eq!(result.last_insert_id, Some(4));
+        assert_eq!(
+            copy.tables["t"].select().unwrap()[3].values[1],
+            Value::Int(25)
+        );
+    }
+
+    #[test]
+    fn nextval_advances_for_each_row() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE SEQUENCE s;
+             CREATE TABLE t (a INT);
+             INSERT INTO t VALUES (10), (20), (30);
+             CREATE TABLE u (id INT UNIQUE, a INT)",
+        );
+        let result = query(&mut db, "SELECT nextval('s'), a FROM t");
+        assert_eq!(result.columns[0].name, "nextval('s')");
+        let ids = result
+            .rows
+            .iter()
+            .map(|row| row.values[0].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [Value::Int(1), Value::Int(2), Value::Int(3)]);
+
+        // Each copied row gets its own key, so UNIQUE holds
+        run(&mut db, "INSERT INTO u SELECT nextval('s'), a FROM t");
+        let ids = db.tables["u"]
+            .select()
+            .unwrap()
+            .into_iter()
+            .map(|row| row.values[0].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [Value::Int(4), Value::Int(5), Value::Int(6)]);
+    }
+
+    const EXTREMES: &str =
+        "CREATE TABLE t (s SMALLINT, i INT, b BIGINT, r REAL, d DOUBLE PRECISION);
+         INSERT INTO t VALUES (-32768, 2147483647, 9223372036854775807, 0.5, 0.1)";
+
+    fn select_from_t(db: &mut Database, expression: &str) -> Result<Value, String> {
+        first_row(db, &format!("SELECT {} FROM t", expression)).map(|values| values[0].clone())
+    }
+
+    #[test]
+    fn integer_columns_hold_their_range() {
+        let mut db = Database::new();
+        run(&mut db, EXTREMES);
+        run(
+            &mut db,
+            "INSERT INTO t VALUES (32767, -2147483648, -9223372036854775808, NULL, NULL)",
+        );
+        for values in [
+            "32768, 1, 1, 1, 1",
+            "-32769, 1, 1, 1, 1",
+            "1, 2147483648, 1, 1, 1",
+        ] {
+            let sql = format!("INSERT INTO t VALUES ({})", values);
+            assert!(try_run(&mut db, &sql).is_err(), "{}", sql);
+        }
+        assert_eq!(db.tables["t"].select().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn arithmetic_widens_to_the_larger_type() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE small (s SMALLINT, i INT, b BIGINT, r REAL, d DOUBLE);
+             INSERT INTO small VALUES (1, 1, 1, 0.5, 0.1);
+             CREATE TABLE sums AS SELECT s + s, s + i, i + b, b + r, r + r, r + d FROM small",
+        );
+        assert_eq!(
+            column_types(&db, "sums"),
+            vec![
+                DataType::SmallInt,
+                DataType::Int,
+                DataType::BigInt,
+                DataType::Double,
+                DataType::Float,
+                DataType::Double,
+            ]
+        );
+    }
+
+    #[test]
+    fn integer_overflow_is_an_error() {
+        let mut db = Database::new();
+        run(&mut db, EXTREMES);
+        // Arithmetic checks the widened type, not just the final result
+        for expression in ["-s", "s + s", "i + 1", "b + 1", "i + 1 + b - b"] {
+            assert_eq!(
+                select_from_t(&mut db, expression),
+                Err("Integer overflow.".to_string()),
+                "{}",
+                expression
+            );
+        }
+        assert_eq!(select_from_t(&mut db, "s + 1"), Ok(Value::Int(-32767)));
+    }
+
+    #[test]
+    fn integer_division_truncates() {
+        let mut db = Database::new();
+        assert_eq!(
+            first_row(&mut db, "SELECT 7 / 2, -7 / 2, -7 % 2"),
+            Ok(vec![Value::Int(3), Value::Int(-3), Value::Int(-1)])
+        );
+        assert_eq!(
+            first_row(&mut db, "SELECT 7 / 0"),
+            Err("Division by zero.".to_string())
+        );
+        assert_eq!(
+            first_row(&mut db, "SELECT 7 % 0"),
+            Err("Division by zero.".to_string())
+        );
+    }
+
+    #[test]
+    fn integers_mixed_with_floats() {
+        let mut db = Database::new();
+        run(&mut db, EXTREMES);
+        assert_eq!(
+            select_from_t(&mut db, "i - 1 + d"),
+            Ok(Value::Double(2147483646.1))
+        );
+        assert_eq!(select_from_t(&mut db, "r * 2"), Ok(Value::Float(1.0)));
+    }
+
+    #[test]
+    fn extreme_numbers_survive_a_dump() {
+        let mut db = Database::new();
+        run(&mut db, EXTREMES);
+        let dump = dump_to_string(&db);
+        assert!(dump.contains("(-32768, 2147483647, 9223372036854775807, 0.5, 0.1)"));
+        let mut copy = Database::new();
+        run(&mut copy, &dump);
+        assert_eq!(copy.tables["t"].select(), db.tables["t"].select());
+    }
+
+    #[test]
+    fn numeric_keeps_each_value_scale() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (n NUMERIC, d DECIMAL);
+             INSERT INTO t VALUES (2.5, 1.25), (7, '0.001'), (-0.10, 3)",
+        );
+        assert_eq!(
+            column_types(&db, "t"),
+            [DataType::Numeric, DataType::Numeric]
+        );
+        let rows = db.tables["t"].select().unwrap();
+        let shown = rows
+            .iter()
+            .map(|row| format!("{} {}", row.values[0], row.values[1]))
+            .collect::<Vec<_>>();
+        assert_eq!(shown, ["2.5 1.25", "7 0.001", "-0.10 3"]);
+
+        let result = query(&mut db, "SELECT n + d, n * d FROM t");
+        assert_eq!(result.columns[0].data_type, DataType::Numeric);
+        assert_eq!(result.rows[0].values[0].to_string(), "3.75");
+        assert_eq!(result.rows[0].values[1].to_string(), "3.125");
+
+        let dump = dump_to_string(&db);
+        assert!(dump.contains("n NUMERIC,\n  d NUMERIC\n"));
+        let mut copy = Database::new();
+        run(&mut copy, &dump);
+        assert_eq!(copy.tables["t"].select(), db.tables["t"].select());
+    }
+
+    #[test]
+    fn decimal_precision_is_capped_at_38_digits() {
+        let mut db = Database::new();
+        let most = "9".repeat(38);
+        run(
+            &mut db,
+            &format!(
+                "CREATE TABLE t (n NUMERIC, d DECIMAL(38, 38));
+                 INSERT INTO t VALUES ('{}', '0.{}')",
+                most, most
+            ),
+        );
+        let rows = db.tables["t"].select().unwrap();
+        assert_eq!(rows[0].values[0].to_string(), most);
+        assert_eq!(rows[0].values[1].to_string(), format!("0.{}", most));
+
+        let statement = |sql: &str| parse_statement(sql).map_err(|e| e.message);
+        // One digit more doesn't fit, whether written or computed
+        for sql in [
+            format!("INSERT INTO t (n) VALUES ('1{}')", most),
+            format!("INSERT INTO t (d) VALUES ('0.1{}')", most),
+        ] {
+            let Statement::Insert(insert) = statement(&sql).unwrap() else {
+                panic!("unexpected statement: {}", sql);
+            };
+            assert!(db.insert_into_table(&insert).is_err(), "{}", sql);
+        }
+        let Statement::Select(select) = statement("SELECT n + 1 FROM t").unwrap() else {
+            panic!("unexpected statement");
+        };
+        assert_eq!(
+            db.select_from_table(&select).unwrap_err(),
+            "Numeric overflow."
+        );
+        assert!(statement("CREATE TABLE big (n DECIMAL(39, 0))").is_err());
+    }
+
+    const PRICES: &str =
+        "CREATE TABLE prices (amount DECIMAL(6, 2) UNIQUE, rate NUMERIC(4, 3), n NUMERIC);
+         INSERT INTO prices VALUES (1.005, 0.1, 2.5), (-1234.5, 1, 7), ('0.10', '0.0005', 1)";
+
+    fn shown_rows(db: &Database, table: &str) -> Vec<String> {
+        db.tables[table]
+            .select()
+            .unwrap()
+            .iter()
+            .map(|row| {
+                row.values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn decimal_rounds_to_the_column_scale() {
+        let mut db = Database::new();
+        run(&mut db, PRICES);
+        // Halves round away from zero, and the scale is kept when shown
+        assert_eq!(
+            shown_rows(&db, "prices"),
+            vec!["1.01 0.100 2.5", "-1234.50 1.000 7", "0.10 0.001 1"]
+        );
+    }
+
+    #[test]
+    fn decimal_rejects_too_many_whole_digits() {
+        let mut db = Database::new();
+        run(&mut db, PRICES);
+        assert!(try_run(&mut db, "INSERT INTO prices VALUES (10000, NULL, NULL)").is_err());
+        assert!(try_run(&mut db, "INSERT INTO prices VALUES (NULL, 10, NULL)").is_err());
+        // Too many digits for DECIMAL makes the literal a DOUBLE, which never becomes one
+        assert!(try_run(
+            &mut db,
+            "INSERT INTO prices VALUES (0.000000000000000000000000000000000000001, NULL, NULL)"
+        )
+        .is_err());
+        assert!(try_run(&mut db, "INSERT INTO prices VALUES (9999.99, 9.999, NULL)").is_ok());
+    }
+
+    #[test]
+    fn decimal_equality_ignores_the_scale() {
+        let mut db = Database::new();
+        run(&mut db, PRICES);
+        // 0.1 is the 0.10 already stored, so UNIQUE rejects it
+        assert!(try_run(&mut db, "INSERT INTO prices VALUES (0.1, NULL, NULL)").is_err());
+        assert!(try_run(&mut db, "INSERT INTO prices VALUES (NULL, 0.5, NULL)").is_ok());
+    }
+
+    #[test]
+    fn decimal_arithmetic_is_exact() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            &format!(
+                "{};
+                 CREATE TABLE totals AS SELECT amount * rate, amount + n FROM prices",
+                PRICES
+            ),
+        );
+        assert_eq!(
+            column_types(&db, "totals"),
+            vec![DataType::Decimal(10, 5), DataType::Numeric]
+        );
+        let rows = db.tables["totals"].select().unwrap();
+        assert_eq!(rows[1].values[0].to_string(), "-1234.50000");
+        assert_eq!(rows[1].values[1].to_string(), "-1227.50");
+        assert_eq!(
+            first_row(&mut db, "SELECT 0.1 + 0.2 = 0.3, 1.0 / 3"),
+            Ok(vec![
+                Value::Boolean(true),
+                Value::Decimal(Decimal::parse("0.3333333").unwrap())
+            ])
+        );
+    }
+
+    #[test]
+    fn text_compares_with_decimal_exactly() {
+        let mut db = Database::new();
+        run(&mut db, PRICES);
+        // Text is read at its own scale, not rounded to the column's
+        let matching = |db: &mut Database, condition: &str| {
+            query(
+                db,
+                &format!("SELECT amount FROM prices WHERE {}", condition),
+            )
+            .rows
+            .len()
+        };
+        assert_eq!(matching(&mut db, "amount = '0.10'"), 1);
+        assert_eq!(matching(&mut db, "amount = '0.104'"), 0);
+        assert_eq!(
+            matching(&mut db, "'0.104' > amount AND amount > '0.099'"),
+            1
+        );
+        assert_eq!(
+            first_row(&mut db, "SELECT '1.24' = 1.2, 1.2 < '1.24'"),
+            Ok(vec![Value::Boolean(false), Value::Boolean(true)])
+        );
+    }
+
+    #[test]
+    fn decimal_survives_a_dump() {
+        let mut db = Database::new();
+        run(&mut db, PRICES);
+        let dump = dump_to_string(&db);
+        assert!(dump.contains("amount DECIMAL(6, 2)"));
+        assert!(dump.contains("(-1234.50, 1.000, 7)"));
+        let mut copy = Database::new();
+        run(&mut copy, &dump);
+        assert_eq!(dump_to_string(&copy), dump);
+    }
+
+    const SALES: &str =
+        "CREATE TABLE sales (amount DECIMAL(6, 2), qty INT, weight DOUBLE, note TEXT);
+         INSERT INTO sales VALUES (0.10, 2147483647, 0.5, 'a'), (0.20, 2147483647, NULL, 'b'),
+            (NULL, 1, 1.0, NULL)";
+
+    fn shown_first_row(db: &mut Database, sql: &str) -> Result<Vec<String>, String> {
+        first_row(db, sql).map(|values| values.iter().map(|value| value.to_string()).collect())
+    }
+
+    #[test]
+    fn sum_and_avg_skip_nulls() {
+        let mut db = Database::new();
+        run(&mut db, SALES);
+        // DECIMALs stay exact and INT sums don't overflow at INT's range
+        let result = query(
+            &mut db,
+            "SELECT SUM(amount), AVG(amount), SUM(qty), AVG(qty), AVG(weight) AS w FROM sales",
+        );
+        let names = result
+            .columns
+            .iter()
+            .map(|column| column.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            ["SUM(amount)", "AVG(amount)", "SUM(qty)", "AVG(qty)", "w"]
+        );
+        let values = result.rows[0]
+            .values
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            [
+                "0.30",
+                "0.15000000",
+                "4294967295",
+                "1431655765.000000",
+                "0.75"
+            ]
+        );
+    }
+
+    #[test]
+    fn aggregates_over_no_values_are_null() {
+        let mut db = Database::new();
+        run(&mut db, SALES);
+        assert_eq!(
+            shown_first_row(
+                &mut db,
+                "SELECT SUM(qty), AVG(qty) FROM sales WHERE qty < 0"
+            ),
+            Ok(vec!["NULL".to_string(), "NULL".to_string()])
+        );
+        assert_eq!(
+            shown_first_row(
+                &mut db,
+                "SELECT SUM(amount) FROM sales WHERE amount IS NULL"
+            ),
+            Ok(vec!["NULL".to_string()])
+        );
+    }
+
+    #[test]
+    fn aggregates_inside_expressions() {
+        let mut db = Database::new();
+        run(&mut db, SALES);
+        assert_eq!(
+            shown_first_row(
+                &mut db,
+                "SELECT ROUND(AVG(amount), 1) * 2, SUM(amount) FROM sales WHERE qty > 1000"
+            ),
+            Ok(vec!["0.4".to_string(), "0.30".to_string()])
+        );
+    }
+
+    #[test]
+    fn aggregate_misuse_is_rejected() {
+        let mut db = Database::new();
+        run(&mut db, SALES);
+        for sql in [
+            "SELECT SUM(note) FROM sales",
+            "SELECT note, SUM(qty) FROM sales",
+            "SELECT *, SUM(qty) FROM sales",
+            "SELECT SUM(qty, amount) FROM sales",
+            "SELECT * FROM sales WHERE AVG(qty) > 1",
+        ] {
+            assert!(first_row(&mut db, sql).is_err(), "{}", sql);
+        }
+    }
+
+    const EVENTS: &str = "CREATE TABLE ev (at TIMESTAMP, utc TIMESTAMP WITH TIME ZONE, t TIME, d DATE, span INTERVAL);
+         INSERT INTO ev VALUES ('2024-01-31T10:15:30.5', '2024-01-31 10:15:30+02:00', '23:30',
+            '2024-02-29', '1 year 2 mons 3 days 04:05:06');
+         INSERT INTO ev VALUES (DATE '2024-03-01', '2024-03-01T00:00:00Z', '00:00:01',
+            '2023-12-31', 'P1DT2H')";
+
+    fn shown_from_ev(db: &mut Database, expressions: &str) -> Result<Vec<String>, String> {
+        shown_first_row(db, &format!("SELECT {} FROM ev", expressions))
+    }
+
+    #[test]
+    fn timestamptz_is_stored_in_utc() {
+        let mut db = Database::new();
+        run(&mut db, EVENTS);
+        assert_eq!(
+            shown_from_ev(&mut db, "utc, at"),
+            Ok(vec![
+                "2024-01-31 08:15:30+00:00".to_string(),
+                "2024-01-31 10:15:30.500".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn invalid_dates_and_times_are_rejected() {
+        let mut db = Database::new();
+        run(&mut db, EVENTS);
+        for sql in [
+            "INSERT INTO ev (d) VALUES ('2023-02-29')",
+            "INSERT INTO ev (t) VALUES ('25:00')",
+            "INSERT INTO ev (span) VALUES ('1 fortnight')",
+            "INSERT INTO ev (at) VALUES ('yesterday')",
+        ] {
+            assert!(try_run(&mut db, sql).is_err(), "{}", sql);
+        }
+        assert_eq!(db.tables["ev"].select().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn date_arithmetic() {
+        let mut db = Database::new();
+        run(&mut db, EVENTS);
+        // A month from January 31st is the last day of February
+        assert_eq!(
+            shown_from_ev(
+                &mut db,
+                "at + INTERVAL '1 month', d + 1, d - DATE '2024-01-01', DATE '2024-01-01' - 1"
+            ),
+            Ok(vec![
+                "2024-02-29 10:15:30.500".to_string(),
+                "2024-03-01".to_string(),
+                "59".to_string(),
+                "2023-12-31".to_string(),
+            ])
+        );
+        assert!(shown_from_ev(&mut db, "d + 1.5").is_err());
+    }
+
+    #[test]
+    fn interval_arithmetic() {
+        let mut db = Database::new();
+        run(&mut db, EVENTS);
+        // A time wraps around midnight
+        assert_eq!(
+            shown_from_ev(
+                &mut db,
+                "at - TIMESTAMP '2024-01-01', t + span, utc - at, span * 2"
+            ),
+            Ok(vec![
+                "30 days 10:15:30.5".to_string(),
+                "03:35:06".to_string(),
+                "-02:00:00.5".to_string(),
+                "2 years 4 mons 6 days 08:10:12".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn extract_fields() {
+        let mut db = Database::new();
+        run(&mut db, EVENTS);
+        assert_eq!(
+            shown_from_ev(
+                &mut db,
+                "EXTRACT(year FROM d), EXTRACT(second FROM at), EXTRACT(epoch FROM utc), \
+                 EXTRACT(hour FROM span)"
+            ),
+            Ok(vec![
+                "2024".to_string(),
+                "30.500000".to_string(),
+                "1706688930.000000".to_string(),
+                "4".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn date_trunc_and_strftime() {
+        let mut db = Database::new();
+        run(&mut db, EVENTS);
+        assert_eq!(
+            shown_from_ev(
+                &mut db,
+                "date_trunc('quarter', at), date_trunc('week', d), strftime('%d/%m %H:%M', t)"
+            ),
+            Ok(vec![
+                "2024-01-01 00:00:00".to_string(),
+                "2024-02-26 00:00:00".to_string(),
+                "01/01 23:30".to_string(),
+            ])
+        );
+        // A plain TIMESTAMP has no zone to format
+        assert!(shown_from_ev(&mut db, "strftime('%z', at)").is_err());
+        assert!(shown_from_ev(&mut db, "date_trunc('fortnight', at)").is_err());
+    }
+
+    #[test]
+    fn date_and_time_comparisons() {
+        let mut db = Database::new();
+        run(&mut db, EVENTS);
+        // Dates compare with timestamps, and intervals by their length
+        let matching = |db: &mut Database, condition: &str| {
+            query(db, &format!("SELECT d FROM ev WHERE {}", condition))
+                .rows
+                .len()
+        };
+        assert_eq!(matching(&mut db, "d < at AND at >= '2024-03-01'"), 1);
+        assert_eq!(matching(&mut db, "span = INTERVAL '26 hours'"), 1);
+        assert_eq!(matching(&mut db, "now() > utc"), 2);
+    }
+
+    #[test]
+    fn date_and_time_survive_a_dump() {
+        let mut db = Database::new();
+        run(&mut db, EVENTS);
+        let dump = dump_to_string(&db);
+        assert!(dump.contains("utc TIMESTAMPTZ,"));
+        assert!(dump.contains("TIMESTAMP '2024-03-01 00:00:00', TIMESTAMPTZ"));
+        let mut copy = Database::new();
+        run(&mut copy, &dump);
+        assert_eq!(copy.tables["ev"].select(), db.tables["ev"].select());
+    }
+
+    const FILES: &str = "CREATE TABLE files (name TEXT, data BYTEA, kind BLOB);
+         INSERT INTO files VALUES ('a', X'00ff10', '\\x4142');
+         INSERT INTO files VALUES ('short', '', 'hi')";
+
+    #[test]
+    fn text_holds_values_larger_than_a_page() {
+        let mut db = Database::new();
+        let long = "x".repeat(3 << 20);
+        run(
+            &mut db,
+            &format!(
+                "CREATE TABLE t (name TEXT, n INT);
+                 INSERT INTO t VALUES ('{}', 1), ('{}', 2), ('short', 3)",
+                long, long
+            ),
+        );
+        let rows = db.tables["t"].select().unwrap();
+        assert_eq!(rows[1].values, vec![Value::Varchar(long), Value::Int(2)]);
+        assert_eq!(
+            first_row(&mut db, "SELECT LENGTH(name) FROM t"),
+            Ok(vec![Value::Int(3 << 20)])
+        );
+    }
+
+    #[test]
+    fn blob_takes_hex_or_plain_text() {
+        let mut db = Database::new();
+        run(&mut db, FILES);
+        let rows = db.tables["files"].select().unwrap();
+        assert_eq!(rows[0].values[1], Value::Blob(vec![0, 0xff, 0x10]));
+        assert_eq!(rows[0].values[2], Value::Blob(b"AB".to_vec()));
+        assert_eq!(rows[1].values[1], Value::Blob(Vec::new()));
+        assert_eq!(rows[1].values[2], Value::Blob(b"hi".to_vec()));
+        assert_eq!(rows[0].values[1].to_string(), "\\x00ff10");
+    }
+
+    #[test]
+    fn blob_rejects_bad_hex() {
+        let mut db = Database::new();
+        run(&mut db, FILES);
+        for value in ["'\\x+f'", "'\\xzz'", "'\\x0'"] {
+            let sql = format!("INSERT INTO files (data) VALUES ({})", value);
+            assert!(try_run(&mut db, &sql).is_err(), "{}", sql);
+        }
+        assert!(parse_statement("INSERT INTO files (data) VALUES (X'0')").is_err());
+        assert!(parse_statement("INSERT INTO files (data) VALUES (X'0g')").is_err());
+    }
+
+    #[test]
+    fn blob_length_concat_and_comparison() {
+        let mut db = Database::new();
+        run(&mut db, FILES);
+        assert_eq!(
+            first_row(
+                &mut db,
+                "SELECT LENGTH(data), data || kind FROM files WHERE kind = X'4142'"
+            ),
+            Ok(vec![
+                Value::Int(3),
+                Value::Blob(vec![0, 0xff, 0x10, b'A', b'B']),
+            ])
+        );
+    }
+
+    #[test]
+    fn text_and_blob_survive_a_dump() {
+        let mut db = Database::new();
+        run(&mut db, FILES);
+        let dump = dump_to_string(&db);
+        assert!(dump.contains("name TEXT,\n  data BLOB,"));
+        assert!(dump.contains("X'00ff10', X'4142'"));
+        let mut copy = Database::new();
+        run(&mut copy, &dump);
+        assert_eq!(copy.tables["files"].select(), db.tables["files"].select());
+    }
+
+    const EVENT_LOG: &str = r#"CREATE TABLE events (id INT, payload JSONB);
+         INSERT INTO events VALUES (1, '{"user": {"name": "ann", "tags": ["a", "b"]}, "n": 2.50}');
+         INSERT INTO events VALUES (2, JSON '[1, {"x": null}]')"#;
+
+    #[test]
+    fn json_rejects_invalid_documents() {
+        let mut db = Database::new();
+        run(&mut db, EVENT_LOG);
+        let error = try_run(&mut db, "INSERT INTO events VALUES (3, '{\"a\": }')").unwrap_err();
+        assert!(error.contains("Invalid JSON"), "{}", error);
+        assert_eq!(db.tables["events"].select().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn json_operators_on_objects() {
+        let mut db = Database::new();
+        run(&mut db, EVENT_LOG);
+        let result = query(
+            &mut db,
+            "SELECT payload -> 'user' ->> 'name', payload #>> '{user,tags,1}', \
+             payload -> 'missing', payload -> 0 FROM events WHERE id = 1",
+        );
+        assert_eq!(
+            result.rows[0].values,
+            vec![
+                Value::Varchar("ann".to_string()),
+                Value::Varchar("b".to_string()),
+                Value::Null,
+                Value::Null,
+            ]
+        );
+        // Text holding JSON is read as JSON
+        assert_eq!(
+            first_row(&mut db, r#"SELECT '{"a": 1}' ->> 'a'"#),
+            Ok(vec![Value::Varchar("1".to_string())])
+        );
+    }
+
+    #[test]
+    fn json_operators_on_arrays() {
+        let mut db = Database::new();
+        run(&mut db, EVENT_LOG);
+        // JSON null stays JSON with ->, but is NULL as text
+        let result = query(
+            &mut db,
+            "SELECT payload -> -1, payload #> '{1,x}', payload -> 1 ->> 'x', payload -> 'a' \
+             FROM events WHERE id = 2",
+        );
+        assert_eq!(
+            result.rows[0].values,
+            vec![
+                Value::Json(Json::parse(r#"{"x": null}"#).unwrap()),
+                Value::Json(Json::Null),
+                Value::Null,
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn json_extract_and_array_length() {
+        let mut db = Database::new();
+        run(&mut db, EVENT_LOG);
+        let result = query(
+            &mut db,
+            "SELECT json_extract(payload, '$.n'), json_array_length(payload, '$.user.tags'), \
+             json_array_length(payload) FROM events WHERE id = 1",
+        );
+        assert_eq!(
+            result.rows[0].values,
+            vec![
+                Value::Decimal(Decimal::parse("2.50").unwrap()),
+                Value::Int(2),
+                Value::Int(0),
+            ]
+        );
+        // Several paths give an array of what each one found
+        assert_eq!(
+            first_row(&mut db, "SELECT json_extract('[1,2]', '$[1]', '$[0]')"),
+            Ok(vec![Value::Json(Json::parse("[2, 1]").unwrap())])
+        );
+    }
+
+    #[test]
+    fn json_each_yields_members_and_elements() {
+        let mut db = Database::new();
+        let result = query(
+            &mut db,
+            r#"SELECT key, value, type FROM json_each('{"a": 1, "b": [true]}') WHERE type <> 'number'"#,
+        );
+        assert_eq!(
+            result.rows[0].values,
+            vec![
+                Value::Varchar("b".to_string()),
+                Value::Json(Json::parse("[true]").unwrap()),
+                Value::Varchar("array".to_string()),
+            ]
+        );
+        let result = query(
+            &mut db,
+            "SELECT key FROM json_each('[10, 20, 30]') ORDER BY key DESC",
+        );
+        assert_eq!(result.rows.len(), 3);
+        assert_eq!(result.rows[0].values, vec![Value::Int(2)]);
+        let result = query(
+            &mut db,
+            r#"SELECT key FROM json_each('{"a": {"b": [1, 2]}}', '$.a.b')"#,
+        );
+        assert_eq!(result.rows.len(), 2);
+    }
+
+    #[test]
+    fn json_each_of_a_scalar_is_one_row() {
+        let mut db = Database::new();
+        let result = query(&mut db, "SELECT key, value, type FROM json_each('5')");
+        assert_eq!(result.rows.len(), 1);
+        assert_eq!(
+            result.rows[0].values,
+            vec![
+                Value::Null,
+                Value::Json(Json::parse("5").unwrap()),
+                Value::Varchar("number".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn json_survives_a_dump() {
+        let mut db = Database::new();
+        run(&mut db, EVENT_LOG);
+        let dump = dump_to_string(&db);
+        assert!(dump.contains("payload JSON\n"));
+        assert!(dump.contains(r#"(2, JSON '[1, {"x": null}]')"#));
+        let mut copy = Database::new();
+        run(&mut copy, &dump);
+        assert_eq!(copy.tables["events"].select(), db.tables["events"].select());
+    }
+
+    const PEOPLE: &str = "CREATE TYPE mood AS ENUM ('sad', 'ok', 'happy');
+         CREATE TABLE people (id UUID, feeling mood);
+         INSERT INTO people VALUES ('A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11', 'happy');
+         INSERT INTO people VALUES ('{a0eebc999c0b4ef8bb6d6bb9bd380a12}', 'sad');
+         INSERT INTO people VALUES (gen_random_uuid(), 'ok')";
+
+    #[test]
+    fn uuid_accepts_any_case_and_braces() {
+        let mut db = Database::new();
+        run(&mut db, PEOPLE);
+        let rows = db.tables["people"].select().unwrap();
+        assert_eq!(
+            rows[0].values[0].to_string(),
+            "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11"
+        );
+        assert_eq!(
+            rows[1].values[0].to_string(),
+            "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a12"
+        );
+        // Text is read as a UUID when compared with one
+        let result = query(
+            &mut db,
+            "SELECT feeling FROM people WHERE id = 'a0eebc999c0b4ef8bb6d6bb9bd380a11'",
+        );
+        assert_eq!(result.rows.len(), 1);
+    }
+
+    #[test]
+    fn uuid_rejects_bad_text() {
+        let mut db = Database::new();
+        run(&mut db, PEOPLE);
+        for sql in [
+            "INSERT INTO people VALUES ('not-a-uuid', 'ok')",
+            "INSERT INTO people VALUES ('+0eebc999c0b4ef8bb6d6bb9bd380a11', 'ok')",
+            "INSERT INTO people VALUES ('a0eebc999c0b4ef8bb6d6bb9bd380a1', 'ok')",
+        ] {
+            let error = try_run(&mut db, sql).unwrap_err();
+            assert!(error.contains("Expected UUID"), "{}", error);
+        }
+        assert_eq!(db.tables["people"].select().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn gen_random_uuid_is_version_4() {
+        let mut db = Database::new();
+        run(&mut db, PEOPLE);
+        let generated = db.tables["people"].select().unwrap()[2].values[0].to_string();
+        assert_eq!(&generated[14..15], "4");
+        assert!(matches!(&generated[19..20], "8" | "9" | "a" | "b"));
+        assert_eq!(
+            first_row(&mut db, "SELECT gen_random_uuid() = gen_random_uuid()"),
+            Ok(vec![Value::Boolean(false)])
+        );
+    }
+
+    #[test]
+    fn enum_rejects_unknown_labels() {
+        let mut db = Database::new();
+        run(&mut db, PEOPLE);
+        let error = try_run(&mut db, "INSERT INTO people VALUES (NULL, 'meh')").unwrap_err();
+        assert!(error.contains("Invalid value 'meh'"), "{}", error);
+        // Labels are case sensitive
+        let error = try_run(&mut db, "INSERT INTO people VALUES (NULL, 'Happy')").unwrap_err();
+        assert!(error.contains("Invalid value 'Happy'"), "{}", error);
+    }
+
+    #[test]
+    fn enum_type_errors() {
+        let mut db = Database::new();
+        run(&mut db, PEOPLE);
+        for (sql, expected) in [
+            (
+                "CREATE TABLE t (feeling feelings)",
+                "Type 'feelings' does not exist.",
+            ),
+            (
+                "CREATE TYPE mood AS ENUM ('x')",
+                "Type 'mood' already exists.",
+            ),
+            (
+                "CREATE TYPE twice AS ENUM ('a', 'a')",
+                "Duplicate enum label 'a'",
+            ),
+        ] {
+            let error = try_run(&mut db, sql).unwrap_err();
+            assert!(error.contains(expected), "{}", error);
+        }
+    }
+
+    #[test]
+    fn enum_sorts_in_declaration_order() {
+        let mut db = Database::new();
+        run(&mut db, PEOPLE);
+        let result = query(
+            &mut db,
+            "SELECT feeling FROM people WHERE feeling > 'sad' ORDER BY feeling DESC",
+        );
+        let feelings = result
+            .rows
+            .iter()
+            .map(|row| row.values[0].to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(feelings, ["happy", "ok"]);
+    }
+
+    #[test]
+    fn uuid_and_enum_survive_a_dump() {
+        let mut db = Database::new();
+        run(&mut db, PEOPLE);
+        let dump = dump_to_string(&db);
+        assert!(dump.starts_with("CREATE TYPE mood AS ENUM ('sad', 'ok', 'happy');\n"));
+        assert!(dump.contains("(UUID 'a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11', 'happy')"));
+        let mut copy = Database::new();
+        run(&mut copy, &dump);
+        assert_eq!(copy.tables["people"].select(), db.tables["people"].select());
+    }
+
+    const POSTS: &str = r#"CREATE TABLE posts (id INT, tags VARCHAR(5)[], grid INT[][]);
+         INSERT INTO posts VALUES (1, ARRAY['a', 'b c'], '{{1,2},{3,4}}');
+         INSERT INTO posts VALUES (2, '{x,"y,z",NULL}', ARRAY[ARRAY[5]]);
+         INSERT INTO posts VALUES (3, '{}', NULL)"#;
+
+    #[test]
+    fn array_text_is_parsed_and_shown_quoted() {
+        let mut db = Database::new();
+        run(&mut db, POSTS);
+        let rows = db.tables["posts"].select().unwrap();
+        assert_eq!(
+            rows[1].values[1],
+            Value::Array(vec![
+                Value::Varchar("x".to_string()),
+                Value::Varchar("y,z".to_string()),
+                Value::Null,
+            ])
+        );
+        assert_eq!(rows[1].values[1].to_string(), r#"{x,"y,z",NULL}"#);
+        assert_eq!(rows[0].values[2].to_string(), "{{1,2},{3,4}}");
+        assert_eq!(rows[2].values[1], Value::Array(vec![]));
+    }
+
+    #[test]
+    fn array_rejects_bad_elements() {
+        let mut db = Database::new();
+        run(&mut db, POSTS);
+        for sql in [
+            "INSERT INTO posts VALUES (4, ARRAY['toolong'], NULL)",
+            "INSERT INTO posts VALUES (4, '{a,b', NULL)",
+            "INSERT INTO posts VALUES (4, NULL, ARRAY[ARRAY['a']])",
+        ] {
+            assert!(try_run(&mut db, sql).is_err(), "{}", sql);
+        }
+        assert_eq!(db.tables["posts"].select().unwrap().len(), 3);
+        let error = first_row(&mut db, "SELECT ARRAY[1, 'a']").unwrap_err();
+        assert!(
+            error.contains("ARRAY elements must share a type"),
+            "{}",
+            error
+        );
+        // Mixed numbers widen to a common type
+        assert_eq!(
+            shown_first_row(&mut db, "SELECT ARRAY[1, 2.5]"),
+            Ok(vec!["{1.0,2.5}".to_string()])
+        );
+    }
+
+    #[test]
+    fn array_subscripts_and_lengths() {
+        let mut db = Database::new();
+        run(&mut db, POSTS);
+        let result = query(
+            &mut db,
+            "SELECT tags[2], tags[9], grid[2][1], array_length(grid, 1), array_length(tags, 1) \
+             FROM posts WHERE id = 1",
+        );
+        assert_eq!(
+            result.rows[0].values,
+            vec![
+                Value::Varchar("b c".to_string()),
+                Value::Null,
+                Value::Int(3),
+                Value::Int(2),
+                Value::Int(2),
+            ]
+        );
+        // Subscripts start at 1, and lengths of missing dimensions are NULL
+        assert_eq!(
+            first_row(
+                &mut db,
+                "SELECT ARRAY[1, 2][0], ARRAY[1, 2][-1], array_length(ARRAY[1], 2), \
+                 array_length(ARRAY[ARRAY[1, 2]], 2), array_length('{}'::INT[], 1)"
+            ),
+            Ok(vec![
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                Value::Int(2),
+                Value::Null,
+            ])
+        );
+        let error = first_row(&mut db, "SELECT array_length(5, 1)").unwrap_err();
+        assert!(error.contains("expects an array"), "{}", error);
+    }
+
+    #[test]
+    fn any_and_all_compare_with_each_element() {
+        let mut db = Database::new();
+        run(&mut db, POSTS);
+        let result = query(&mut db, "SELECT id FROM posts WHERE 'b c' = ANY(tags)");
+        assert_eq!(result.rows.len(), 1);
+        assert_eq!(result.rows[0].values, vec![Value::Int(1)]);
+        // 'x' <> ALL is FALSE for post 2, and TRUE for post 3's empty array
+        let result = query(&mut db, "SELECT id FROM posts WHERE 'x' <> ALL(tags)");
+        let ids = result
+            .rows
+            .iter()
+            .map(|row| row.values[0].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [Value::Int(1), Value::Int(3)]);
+        // A NULL element leaves an unmatched ANY unknown
+        assert_eq!(
+            first_row(
+                &mut db,
+                "SELECT 2 = ANY(ARRAY[1, NULL]), 2 = ANY('{1,2}'), NULL = ANY(ARRAY[1]), \
+                 1 = ANY('{}'::INT[]), 1 = ALL('{}'::INT[])"
+            ),
+            Ok(vec![
+                Value::Null,
+                Value::Boolean(true),
+                Value::Null,
+                Value::Boolean(false),
+                Value::Boolean(true),
+            ])
+        );
+    }
+
+    #[test]
+    fn unnest_yields_each_element() {
+        let mut db = Database::new();
+        let result = query(
+            &mut db,
+            "SELECT * FROM unnest(ARRAY[3, 1, 2]) ORDER BY unnest",
+        );
+        let elements = result
+            .rows
+            .iter()
+            .map(|row| row.values[0].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(elements, [Value::Int(1), Value::Int(2), Value::Int(3)]);
+        let result = query(&mut db, "SELECT * FROM unnest(ARRAY[1, NULL])");
+        assert_eq!(result.rows[1].values, vec![Value::Null]);
+        let result = query(&mut db, "SELECT * FROM unnest('{}'::INT[])");
+        assert!(result.rows.is_empty());
+    }
+
+    #[test]
+    fn arrays_survive_a_dump() {
+        let mut db = Database::new();
+        run(&mut db, POSTS);
+        let dump = dump_to_string(&db);
+        assert!(dump.contains("tags VARCHAR(5)[],\n  grid INT[][]\n"));
+        assert!(dump.contains("(3, '{}', NULL)"));
+        let mut copy = Database::new();
+        run(&mut copy, &dump);
+        assert_eq!(copy.tables["posts"].select(), db.tables["posts"].select());
+    }
+
+    #[test]
+    fn array_agg_collects_values() {
+        let mut db = Database::new();
+        run(
+            &mut db,
+            "CREATE TABLE t (id INT, n INT);
+             INSERT INTO t VALUES (1, 10), (2, NULL), (3, 30)",
+        );
+        let result = query(&mut db, "SELECT ARRAY_AGG(n) FROM t");
+        assert_eq!(
+            result.columns[0].data_type,
+            DataType::Array(Box::new(DataType::Int))
+        );
+        assert_eq!(result.rows[0].values[0].to_string(), "{10,NULL,30}");
+
+        // No rows gives NULL rather than an empty array, still typed as INT[]
+        let result = query(&mut db, "SELECT ARRAY_AGG(n) FROM t WHERE id > 5");
+        assert_eq!(result.rows[0].values[0], Value::Null);
+        run(
+            &mut db,
+            "CREATE TABLE agg AS SELECT ARRAY_AGG(n) AS ns FROM t WHERE id > 5",
+        );
+        assert_eq!(
+            db.tables["agg"].columns[0].data_type,
+            DataType::Array(Box::new(DataType::Int))
+        );
+    }
+
+    const WORDS: &str = "CREATE TABLE words (code CHAR(4), name VARCHAR(5) COLLATE NOCASE UNIQUE, \
+         word TEXT COLLATE UNICODE);
+         INSERT INTO words VALUES ('ab', 'héllo', 'cafe\u{301}');
+         INSERT INTO words VALUES ('abcd  ', 'Apple', 'cafe');
+         INSERT INTO words VALUES ('b', 'bob', 'x')";
+
+    fn shown_column(db: &mut Database, sql: &str) -> Vec<String> {
+        query(db, sql)
+            .rows
+            .into_iter()
+            .map(|row| row.values[0].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn char_pads_to_its_length() {
+        let mut db = Database::new();
+        run(&mut db, WORDS);
+        let rows = db.tables["words"].select().unwrap();
+        assert_eq!(rows[0].values[0], Value::Char("ab  ".to_string()));
+        // Trailing spaces past the length are dropped rather than rejected
+        assert_eq!(rows[1].values[0], Value::Char("abcd".to_string()));
+        // Padding isn't counted or compared
+        assert_eq!(
+            shown_column(&mut db, "SELECT LENGTH(code) FROM words"),
+            ["2", "4", "1"]
+        );
+        assert_eq!(
+            shown_column(&mut db, "SELECT name FROM words WHERE code = 'ab'"),
+            ["héllo"]
+        );
+        assert_eq!(
+            shown_first_row(&mut db, "SELECT ''::CHAR(2) || '|', LENGTH(''::CHAR(2))"),
+            Ok(vec!["  |".to_string(), "0".to_string()])
+        );
+    }
+
+    #[test]
+    fn character_lengths_are_enforced() {
+        let mut db = Database::new();
+        run(&mut db, WORDS);
+        for sql in [
+            "INSERT INTO words VALUES ('abcde', 'a', 'a')",
+            // Lengths count characters, not bytes
+            "INSERT INTO words VALUES ('a', 'héllos', 'a')",
+        ] {
+            let error = try_run(&mut db, sql).unwrap_err();
+            assert!(error.contains("exceeds maximum length"), "{}", error);
+        }
+        assert!(try_run(&mut db, "INSERT INTO words VALUES ('abc', 'héllö', 'a')").is_ok());
+        for sql in ["SELECT 'abc'::CHAR(2)", "SELECT 'ab'::VARCHAR(1)"] {
+            let error = first_row(&mut db, sql).unwrap_err();
+            assert!(error.contains("exceeds maximum length"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn nocase_unique_treats_case_as_equal() {
+        let mut db = Database::new();
+        run(&mut db, WORDS);
+        assert!(try_run(&mut db, "INSERT INTO words VALUES ('a', 'HÉLLO', 'a')").is_err());
+        assert!(try_run(&mut db, "INSERT INTO words VALUES ('a', 'BOB', 'a')").is_err());
+        assert_eq!(db.tables["words"].select().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn collations_apply_to_comparisons() {
+        let mut db = Database::new();
+        run(&mut db, WORDS);
+        assert_eq!(
+            shown_column(&mut db, "SELECT name FROM words WHERE name = 'APPLE'"),
+            ["Apple"]
+        );
+        assert!(shown_column(
+            &mut db,
+            "SELECT name FROM words WHERE name COLLATE BINARY = 'APPLE'"
+        )
+        .is_empty());
+        assert_eq!(
+            shown_column(&mut db, "SELECT name FROM words WHERE name LIKE 'B%'"),
+            ["bob"]
+        );
+        // UNICODE compares the normalized forms, so a combining accent matches
+        assert_eq!(
+            shown_column(&mut db, "SELECT name FROM words WHERE word = 'caf\u{e9}'"),
+            ["héllo"]
+        );
+        assert_eq!(
+            shown_first_row(
+                &mut db,
+                "SELECT 'a' = 'A' COLLATE NOCASE, 'a' < 'B' COLLATE NOCASE, 'a' < 'B'"
+            ),
+            Ok(vec![
+                "true".to_string(),
+                "true".to_string(),
+                "false".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn collations_apply_to_ordering() {
+        let mut db = Database::new();
+        run(&mut db, WORDS);
+        assert_eq!(
+            shown_column(
+                &mut db,
+                "SELECT code FROM words ORDER BY code COLLATE NOCASE DESC"
+            ),
+            ["b   ", "abcd", "ab  "]
+        );
+        run(
+            &mut db,
+            "CREATE TABLE mixed (s VARCHAR(9));
+             INSERT INTO mixed VALUES ('b'), ('B'), ('a')",
+        );
+        assert_eq!(
+            shown_column(&mut db, "SELECT s FROM mixed ORDER BY s"),
+            ["B", "a", "b"]
+        );
+        assert_eq!(
+            shown_column(&mut db, "SELECT s FROM mixed ORDER BY s COLLATE NOCASE, s"),
+            ["a", "B", "b"]
+        );
+    }
+
+    #[test]
+    fn unknown_collations_are_rejected() {
+        let mut db = Database::new();
+        let error = try_run(&mut db, "CREATE TABLE bad (s TEXT COLLATE BOGUS)").unwrap_err();
+        assert_eq!(error, "Unknown collation 'BOGUS'.");
+        let error = parse_statement("SELECT 'a' COLLATE BOGUS").unwrap_err();
+        assert_eq!(error.message, "Unknown collation 'BOGUS'.");
+    }
+
+    #[test]
+    fn character_columns_survive_a_dump() {
+        let mut db = Database::new();
+        run(&mut db, WORDS);
+        let dump = dump_to_string(&db);
+        assert!(dump.contains("code CHAR(4),\n  name VARCHAR(5) COLLATE NOCASE,\n"));
+        let mut copy = Database::new();
+        run(&mut copy, &dump);
+        assert_eq!(copy.tables["words"].select(), db.tables["words"].select());
+    }
+
+    const MOODS: &str = "CREATE TYPE mood AS ENUM ('sad', 'happy');
+         CREATE TABLE moods (m mood CHECK (m <> 'sad'::mood))";
+
+    fn cast(db: &mut Database, sql: &str) -> Result<String, String> {
+        first_row(db, &format!("SELECT {}", sql)).map(|values| values[0].to_string())
+    }
+
+    #[test]
+    fn casts_convert_values() {
+        let mut db = Database::new();
+        run(&mut db, MOODS);
+        for (sql, expected) in [
+            ("CAST(-2.5 AS INT)", "-3"),
+            ("'3.7'::DOUBLE::BIGINT", "4"),
+            ("CAST(7 AS DECIMAL(4, 1))", "7.0"),
+            ("0.1::FLOAT::DECIMAL(3, 2)", "0.10"),
+            ("2::BOOLEAN", "true"),
+            ("FALSE::SMALLINT", "0"),
+            ("' Off '::BOOLEAN", "false"),
+            ("42::VARCHAR(2)", "42"),
+            ("'ab'::CHAR(3) || '|'", "ab |"),
+            ("'ab '::CHAR(3)::VARCHAR(3) || '|'", "ab|"),
+            ("TRUE::TEXT", "true"),
+            ("'2024-02-29'::DATE", "2024-02-29"),
+            ("TIMESTAMP '2024-02-29 23:59:59'::DATE", "2024-02-29"),
+            ("TIMESTAMPTZ '2024-02-29 23:00:00-02'::DATE", "2024-03-01"),
+            (
+                "DATE '2024-02-29'::TIMESTAMPTZ",
+                "2024-02-29 00:00:00+00:00",
+            ),
+            ("TIMESTAMP '2024-02-29 12:30:00'::TIME", "12:30:00"),
+            ("'1 day'::INTERVAL", "1 day"),
+            ("X'6869'::TEXT", "\\x6869"),
+            ("'hi'::BLOB", "\\x6869"),
+            ("JSON '\"2024-01-02\"'::DATE", "2024-01-02"),
+            ("JSON '[1]'::TEXT", "[1]"),
+            ("5::JSON", "5"),
+            (
+                "'A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11'::UUID",
+                "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11",
+            ),
+            ("'happy'::mood", "happy"),
+            ("ARRAY[1.5, 2]::INT[]", "{2,2}"),
+            ("'{1,NULL}'::INT[]", "{1,NULL}"),
+            ("ARRAY[1, 2]::TEXT", "{1,2}"),
+            ("NULL::DATE", "NULL"),
+            ("TRY_CAST('x' AS INT)", "NULL"),
+            ("TRY_CAST(DATE '2024-01-01' AS INT)", "NULL"),
+            ("TRY_CAST('7' AS INT)", "7"),
+        ] {
+            assert_eq!(cast(&mut db, sql), Ok(expected.to_string()), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn casts_report_failures() {
+        let mut db = Database::new();
+        run(&mut db, MOODS);
+        for (sql, error) in [
+            (
+                "DATE '2024-01-01'::INT",
+                "Cannot cast DATE value '2024-01-01' to INT.",
+            ),
+            ("1::BLOB", "Cannot cast INT value '1' to BLOB."),
+            (
+                "1.5::BOOLEAN",
+                "Cannot cast DECIMAL(2, 1) value '1.5' to BOOLEAN.",
+            ),
+            (
+                "TIME '10:00:00'::DATE",
+                "Cannot cast TIME value '10:00:00' to DATE.",
+            ),
+            (
+                "70000::SMALLINT",
+                "Value 70000 is out of range for SMALLINT.",
+            ),
+            ("'abc'::VARCHAR(2)", "Value exceeds maximum length of 2."),
+            ("'maybe'::BOOLEAN", "Expected boolean, got 'maybe'."),
+            ("'glad'::mood", "Invalid value 'glad' for enum mood."),
+            ("'x'::nosuch", "Type 'nosuch' does not exist."),
+            ("TRY_CAST(1 / 0 AS INT)", "Division by zero."),
+        ] {
+            assert_eq!(cast(&mut db, sql), Err(error.to_string()), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn casts_in_check_constraints_name_enum_types() {
+        let mut db = Database::new();
+        run(&mut db, MOODS);
+        assert!(try_run(&mut db, "INSERT INTO moods VALUES ('happy')").is_ok());
+        assert!(try_run(&mut db, "INSERT INTO moods VALUES ('sad')").is_err());
+        assert_eq!(db.tables["moods"].select().unwrap().len(), 1);
     }
 }
@@ -0,0 +1,381 @@
+use chrono::{
+    Datelike, Days, FixedOffset, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike,
+};
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const MICROS_PER_DAY: i64 = 86_400 * MICROS_PER_SECOND;
+
+/// A span of time as months, days and microseconds, kept apart because a
+/// month or a day isn't a fixed number of seconds: one month after January
+/// 31st is February's last day, and one day after 02:00 is 02:00.
+///
+/// Comparisons count a month as 30 days and a day as 24 hours, so
+/// `1 day` equals `24:00:00`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub micros: i64,
+}
+
+impl Interval {
+    /// Reads either `[-]n unit ...` with an optional `[-]HH:MM[:SS[.f]]`, as
+    /// in `1 year 2 mons 3 days 04:05:06`, or an ISO-8601 duration such as
+    /// `P1Y2M3DT4H5M6S`.
+    pub fn parse(text: &str) -> Option<Interval> {
+        let text = text.trim();
+        match text.strip_prefix(['P', 'p']) {
+            Some(iso) => Self::parse_iso(iso),
+            None => Self::parse_verbose(text),
+        }
+    }
+
+    fn parse_verbose(text: &str) -> Option<Interval> {
+        let mut interval = Interval::default();
+        let mut words = text.split_whitespace().peekable();
+        words.peek()?;
+        while let Some(word) = words.next() {
+            if word.contains(':') {
+                interval.micros = interval.micros.checked_add(parse_clock(word)?)?;
+                continue;
+            }
+            let unit = words.next()?.to_lowercase();
+            interval = interval.checked_add(Self::from_unit(word, unit.trim_end_matches('s'))?)?;
+        }
+        Some(interval)
+    }
+
+    fn parse_iso(text: &str) -> Option<Interval> {
+        let (date, time) = text.split_once(['T', 't']).unwrap_or((text, ""));
+        let mut interval = Interval::default();
+        for (part, units) in [(date, "YMWD"), (time, "HMS")] {
+            let mut rest = part;
+            while !rest.is_empty() {
+                let end = rest.find(|ch: char| ch.is_ascii_alphabetic())?;
+                let designator = rest[end..].chars().next()?.to_ascii_uppercase();
+                if !units.contains(designator) {
+                    return None;
+                }
+                let unit = match (units, designator) {
+                    ("YMWD", 'Y') => "year",
+                    ("YMWD", 'M') => "month",
+                    (_, 'W') => "week",
+                    (_, 'D') => "day",
+                    (_, 'H') => "hour",
+                    (_, 'M') => "minute",
+                    _ => "second",
+                };
+                interval = interval.checked_add(Self::from_unit(&rest[..end], unit)?)?;
+                rest = &rest[end + 1..];
+            }
+        }
+        Some(interval)
+    }
+
+    /// `number` of `unit`, which is singular and lowercase. Only seconds
+    /// take a fraction.
+    fn from_unit(number: &str, unit: &str) -> Option<Interval> {
+        let mut interval = Interval::default();
+        if matches!(unit, "second" | "sec") {
+            let seconds = number.parse::<f64>().ok()?;
+            interval.micros = (seconds * MICROS_PER_SECOND as f64).round() as i64;
+            return seconds.is_finite().then_some(interval);
+        }
+        let count = number.parse::<i32>().ok()?;
+        match unit {
+            "year" => interval.months = count.checked_mul(12)?,
+            "month" | "mon" => interval.months = count,
+            "week" => interval.days = count.checked_mul(7)?,
+            "day" => interval.days = count,
+            "hour" => interval.micros = count as i64 * 3600 * MICROS_PER_SECOND,
+            "minute" | "min" => interval.micros = count as i64 * 60 * MICROS_PER_SECOND,
+            _ => return None,
+        }
+        Some(interval)
+    }
+
+    /// The time between two instants, in whole days and the rest.
+    pub fn between(later: NaiveDateTime, earlier: NaiveDateTime) -> Option<Interval> {
+        let micros = (later - earlier).num_microseconds()?;
+        Some(Interval {
+            months: 0,
+            days: i32::try_from(micros / MICROS_PER_DAY).ok()?,
+            micros: micros % MICROS_PER_DAY,
+        })
+    }
+
+    pub fn checked_add(self, other: Interval) -> Option<Interval> {
+        Some(Interval {
+            months: self.months.checked_add(other.months)?,
+            days: self.days.checked_add(other.days)?,
+            micros: self.micros.checked_add(other.micros)?,
+        })
+    }
+
+    pub fn checked_neg(self) -> Option<Interval> {
+        Some(Interval {
+            months: self.months.checked_neg()?,
+            days: self.days.checked_neg()?,
+            micros: self.micros.checked_neg()?,
+        })
+    }
+
+    pub fn checked_mul(self, factor: i64) -> Option<Interval> {
+        let factor32 = i32::try_from(factor).ok()?;
+        Some(Interval {
+            months: self.months.checked_mul(factor32)?,
+            days: self.days.checked_mul(factor32)?,
+            micros: self.micros.checked_mul(factor)?,
+        })
+    }
+
+    /// Adds months first, then days, then the time, the way a calendar would.
+    pub fn add_to(self, datetime: NaiveDateTime) -> Option<NaiveDateTime> {
+        let months = Months::new(self.months.unsigned_abs());
+        let datetime = match self.months < 0 {
+            true => datetime.checked_sub_months(months)?,
+            false => datetime.checked_add_months(months)?,
+        };
+        let days = Days::new(self.days.unsigned_abs() as u64);
+        let datetime = match self.days < 0 {
+            true => datetime.checked_sub_days(days)?,
+            false => datetime.checked_add_days(days)?,
+        };
+        datetime.checked_add_signed(TimeDelta::microseconds(self.micros))
+    }
+
+    /// Moves a time of day around the clock; whole days make no difference.
+    pub fn add_to_time(self, time: NaiveTime) -> NaiveTime {
+        let micros = self.micros % MICROS_PER_DAY;
+        time.overflowing_add_signed(TimeDelta::microseconds(micros))
+            .0
+    }
+
+    /// The length in microseconds, with 30-day months.
+    pub fn total_micros(&self) -> i128 {
+        (self.months as i128 * 30 + self.days as i128) * MICROS_PER_DAY as i128
+            + self.micros as i128
+    }
+}
+
+impl Ord for Interval {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.total_micros().cmp(&other.total_micros())
+    }
+}
+
+impl PartialOrd for Interval {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Interval {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Interval {}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        let mut push = |count: i64, unit: &str| match count {
+            0 => {}
+            1 => parts.push(format!("1 {}", unit)),
+            _ => parts.push(format!("{} {}s", count, unit)),
+        };
+        push(self.months as i64 / 12, "year");
+        push(self.months as i64 % 12, "mon");
+        push(self.days as i64, "day");
+        if self.micros != 0 || parts.is_empty() {
+            let sign = if self.micros < 0 { "-" } else { "" };
+            parts.push(format!(
+                "{}{}",
+                sign,
+                format_clock(self.micros.unsigned_abs())
+            ));
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// `HH:MM:SS` with as many fractional digits as needed; hours may pass 24.
+fn format_clock(micros: u64) -> String {
+    let seconds = micros / MICROS_PER_SECOND as u64;
+    let clock = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    match micros % MICROS_PER_SECOND as u64 {
+        0 => clock,
+        fraction => format!(
+            "{}.{}",
+            clock,
+            format!("{:06}", fraction).trim_end_matches('0')
+        ),
+    }
+}
+
+/// Reads `[-]HH:MM[:SS[.f]]` as microseconds.
+fn parse_clock(text: &str) -> Option<i64> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let mut fields = text.splitn(3, ':');
+    let hours = fields.next()?.parse::<i64>().ok()?;
+    let minutes = fields.next()?.parse::<i64>().ok()?;
+    let seconds = fields.next().unwrap_or("0").parse::<f64>().ok()?;
+    if minutes >= 60 || !(0.0..60.0).contains(&seconds) {
+        return None;
+    }
+    let micros = hours
+        .checked_mul(3600 * MICROS_PER_SECOND)?
+        .checked_add(minutes * 60 * MICROS_PER_SECOND)?
+        .checked_add((seconds * MICROS_PER_SECOND as f64).round() as i64)?;
+    Some(if negative { -micros } else { micros })
+}
+
+/// Reads an ISO-8601 date and time, with `T` or a space between them, and
+/// an optional UTC offset such as `Z`, `+02`, `+0200` or `+02:00`. A date
+/// alone means midnight.
+pub fn parse_timestamp(text: &str) -> Option<(NaiveDateTime, Option<FixedOffset>)> {
+    let text = text.trim();
+    let (text, offset) = split_offset(text)?;
+    let datetime = [
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d")
+            .ok()
+            .map(|date| date.and_time(NaiveTime::MIN))
+    })?;
+    Some((datetime, offset))
+}
+
+/// Splits a trailing UTC offset off a timestamp; the date's own dashes are
+/// in its first ten characters.
+fn split_offset(text: &str) -> Option<(&str, Option<FixedOffset>)> {
+    if let Some(rest) = text.strip_suffix(['Z', 'z']) {
+        return Some((rest, FixedOffset::east_opt(0)));
+    }
+    let Some(start) = text.rfind(['+', '-']).filter(|start| *start > 10) else {
+        return Some((text, None));
+    };
+    let digits = text[start + 1..].replace(':', "");
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i32>().ok()?,
+            digits[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+    let seconds = (hours * 3600 + minutes * 60) * if &text[start..=start] == "-" { -1 } else { 1 };
+    Some((
+        text[..start].trim_end(),
+        Some(FixedOffset::east_opt(seconds)?),
+    ))
+}
+
+pub fn parse_time(text: &str) -> Option<NaiveTime> {
+    ["%H:%M:%S%.f", "%H:%M"]
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(text.trim(), format).ok())
+}
+
+/// Cuts a timestamp down to the start of its year, quarter, month, week
+/// (starting Monday), day, hour, minute or second.
+pub fn truncate(datetime: NaiveDateTime, field: &str) -> Result<NaiveDateTime, String> {
+    let (date, time) = (datetime.date(), datetime.time());
+    let (year, month) = (date.year(), date.month());
+    let start = |date: Option<NaiveDate>, hour, minute, second| {
+        date.and_then(|date| date.and_hms_opt(hour, minute, second))
+    };
+    let truncated = match field.to_lowercase().as_str() {
+        "year" => start(NaiveDate::from_ymd_opt(year, 1, 1), 0, 0, 0),
+        "quarter" => start(
+            NaiveDate::from_ymd_opt(year, (month - 1) / 3 * 3 + 1, 1),
+            0,
+            0,
+            0,
+        ),
+        "month" => start(NaiveDate::from_ymd_opt(year, month, 1), 0, 0, 0),
+        "week" => {
+            let monday = Days::new(date.weekday().num_days_from_monday() as u64);
+            start(date.checked_sub_days(monday), 0, 0, 0)
+        }
+        "day" => start(Some(date), 0, 0, 0),
+        "hour" => start(Some(date), time.hour(), 0, 0),
+        "minute" => start(Some(date), time.hour(), time.minute(), 0),
+        "second" => start(Some(date), time.hour(), time.minute(), time.second()),
+        _ => return Err(format!("Unknown date_trunc field '{}'.", field)),
+    };
+    truncated.ok_or_else(|| "Timestamp out of range.".to_string())
+}
+
+/// What EXTRACT returns for a field: whole numbers, except seconds and epoch
+/// which keep their microseconds.
+pub enum Field {
+    Whole(i64),
+    Micros(i128),
+}
+
+/// Reads a field of a timestamp; `epoch` counts seconds since 1970-01-01
+/// 00:00, and `dow` numbers Sunday 0.
+pub fn extract(datetime: NaiveDateTime, field: &str) -> Result<Field, String> {
+    let date = datetime.date();
+    Ok(match field.to_lowercase().as_str() {
+        "year" => Field::Whole(date.year() as i64),
+        "quarter" => Field::Whole((date.month0() / 3 + 1) as i64),
+        "month" => Field::Whole(date.month() as i64),
+        "week" => Field::Whole(date.iso_week().week() as i64),
+        "day" => Field::Whole(date.day() as i64),
+        "dow" => Field::Whole(date.weekday().num_days_from_sunday() as i64),
+        "doy" => Field::Whole(date.ordinal() as i64),
+        "epoch" => Field::Micros(datetime.and_utc().timestamp_micros() as i128),
+        _ => return extract_time(datetime.time(), field),
+    })
+}
+
+/// Reads the hour, minute or second of a time of day.
+pub fn extract_time(time: NaiveTime, field: &str) -> Result<Field, String> {
+    let micros = time.nanosecond() as i128 / 1000;
+    Ok(match field.to_lowercase().as_str() {
+        "hour" => Field::Whole(time.hour() as i64),
+        "minute" => Field::Whole(time.minute() as i64),
+        "second" => Field::Micros(time.second() as i128 * MICROS_PER_SECOND as i128 + micros),
+        "epoch" => Field::Micros(
+            time.num_seconds_from_midnight() as i128 * MICROS_PER_SECOND as i128 + micros,
+        ),
+        _ => return Err(format!("Unknown extract field '{}'.", field)),
+    })
+}
+
+/// Reads a field of an interval, splitting months into years and the
+/// time into hours, minutes and seconds.
+pub fn extract_interval(interval: Interval, field: &str) -> Result<Field, String> {
+    let micros = interval.micros as i128;
+    let per_second = MICROS_PER_SECOND as i128;
+    Ok(match field.to_lowercase().as_str() {
+        "year" => Field::Whole(interval.months as i64 / 12),
+        "month" => Field::Whole(interval.months as i64 % 12),
+        "day" => Field::Whole(interval.days as i64),
+        "hour" => Field::Whole((micros / (3600 * per_second)) as i64),
+        "minute" => Field::Whole((micros / (60 * per_second) % 60) as i64),
+        "second" => Field::Micros(micros % (60 * per_second)),
+        "epoch" => Field::Micros(interval.total_micros()),
+        _ => return Err(format!("Unknown extract field '{}'.", field)),
+    })
+}
@@ -0,0 +1,234 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+/// The most digits a DECIMAL holds; every such number fits in an i128.
+/// There is no wider type to fall back on, so a result with more whole
+/// digits than this is an overflow error.
+pub const MAX_PRECISION: u32 = 38;
+
+/// Extra fractional digits a division keeps beyond its operands' scales.
+pub const DIVISION_SCALE: u32 = 6;
+
+/// An exact base-10 number, `digits` × 10^-`scale`.
+///
+/// The scale is kept as written, so 1.50 shows as `1.50`, but it doesn't take
+/// part in comparisons: 1.5 and 1.50 are equal.
+#[derive(Debug, Clone, Copy)]
+pub struct Decimal {
+    digits: i128,
+    scale: u32,
+}
+
+fn pow10(exponent: u32) -> Option<i128> {
+    10i128.checked_pow(exponent)
+}
+
+/// Divides, rounding half away from zero.
+fn div_round(dividend: i128, divisor: i128) -> Option<i128> {
+    let quotient = dividend.checked_div(divisor)?;
+    let remainder = (dividend % divisor).unsigned_abs();
+    if remainder >= divisor.unsigned_abs() - remainder {
+        quotient.checked_add(if (dividend < 0) == (divisor < 0) {
+            1
+        } else {
+            -1
+        })
+    } else {
+        Some(quotient)
+    }
+}
+
+impl Decimal {
+    /// `digits` × 10^-`scale`; fails when that has more than `MAX_PRECISION`
+    /// digits.
+    pub fn new(digits: i128, scale: u32) -> Option<Self> {
+        let decimal = Self { digits, scale };
+        (decimal.precision() <= MAX_PRECISION).then_some(decimal)
+    }
+
+    pub fn from_i64(value: i64) -> Self {
+        Self {
+            digits: value as i128,
+            scale: 0,
+        }
+    }
+
+    /// Reads `[+-]digits[.digits]`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (negative, unsigned) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return None;
+        }
+        let mut digits: i128 = 0;
+        for ch in whole.chars().chain(fraction.chars()) {
+            let digit = ch.to_digit(10)?;
+            digits = digits.checked_mul(10)?.checked_add(digit as i128)?;
+        }
+        let scale = u32::try_from(fraction.len()).ok()?;
+        Self::new(if negative { -digits } else { digits }, scale)
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// The number of digits, counting every digit after the point.
+    pub fn precision(&self) -> u32 {
+        let mut count = 1;
+        let mut rest = self.digits.unsigned_abs() / 10;
+        while rest > 0 {
+            count += 1;
+            rest /= 10;
+        }
+        count.max(self.scale)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits == 0
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.to_string().parse().unwrap()
+    }
+
+    /// The nearest whole number, rounding half away from zero, when it fits.
+    pub fn to_i64(self) -> Option<i64> {
+        i64::try_from(self.rescale(0)?.digits).ok()
+    }
+
+    /// The same value with `scale` fractional digits, rounding half away
+    /// from zero when digits are dropped.
+    pub fn rescale(self, scale: u32) -> Option<Self> {
+        let digits = match scale.checked_sub(self.scale) {
+            Some(added) => self.digits.checked_mul(pow10(added)?)?,
+            None => div_round(self.digits, pow10(self.scale - scale)?)?,
+        };
+        Self::new(digits, scale)
+    }
+
+    /// Rounds to `places` digits after the point; negative places round to
+    /// tens, hundreds and so on.
+    pub fn round(self, places: i32) -> Option<Self> {
+        match u32::try_from(places) {
+            Ok(places) if places >= self.scale => Some(self),
+            Ok(places) => self.rescale(places),
+            Err(_) => {
+                let unit = pow10(places.unsigned_abs())?;
+                let digits = match pow10(self.scale).and_then(|f| f.checked_mul(unit)) {
+                    Some(divisor) => div_round(self.digits, divisor)?.checked_mul(unit)?,
+                    None => 0,
+                };
+                Self::new(digits, 0)
+            }
+        }
+    }
+
+    pub fn neg(self) -> Self {
+        Self {
+            digits: -self.digits,
+            scale: self.scale,
+        }
+    }
+
+    pub fn abs(self) -> Self {
+        Self {
+            digits: self.digits.abs(),
+            scale: self.scale,
+        }
+    }
+
+    /// Both digit counts brought to the larger of the two scales.
+    fn aligned(self, other: Self) -> Option<(i128, i128, u32)> {
+        let scale = self.scale.max(other.scale);
+        Some((
+            self.rescale(scale)?.digits,
+            other.rescale(scale)?.digits,
+            scale,
+        ))
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let (l, r, scale) = self.aligned(other)?;
+        Self::new(l.checked_add(r)?, scale)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        let (l, r, scale) = self.aligned(other)?;
+        Self::new(l.checked_sub(r)?, scale)
+    }
+
+    /// The scales add up, like in written multiplication, as far as
+    /// `MAX_PRECISION` allows.
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let mut digits = self.digits.checked_mul(other.digits)?;
+        let mut scale = self.scale + other.scale;
+        if scale > MAX_PRECISION {
+            digits = div_round(digits, pow10(scale - MAX_PRECISION)?)?;
+            scale = MAX_PRECISION;
+        }
+        Self::new(digits, scale)
+    }
+
+    /// Keeps `DIVISION_SCALE` more digits than the operands, rounding the last.
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        let scale = (self.scale.max(other.scale) + DIVISION_SCALE).min(MAX_PRECISION);
+        let shift = pow10(scale + other.scale - self.scale)?;
+        Self::new(
+            div_round(self.digits.checked_mul(shift)?, other.digits)?,
+            scale,
+        )
+    }
+
+    pub fn checked_rem(self, other: Self) -> Option<Self> {
+        let (l, r, scale) = self.aligned(other)?;
+        Self::new(l.checked_rem(r)?, scale)
+    }
+
+    /// The whole part and the fractional digits scaled to `scale`.
+    fn split(&self, scale: u32) -> (i128, i128) {
+        let unit = pow10(self.scale).unwrap();
+        (
+            self.digits / unit,
+            self.digits % unit * pow10(scale - self.scale).unwrap(),
+        )
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Comparing whole and fractional parts apart never overflows
+        let scale = self.scale.max(other.scale);
+        self.split(scale).cmp(&other.split(scale))
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.digits < 0 { "-" } else { "" };
+        let magnitude = self.digits.unsigned_abs().to_string();
+        if self.scale == 0 {
+            return write!(f, "{}{}", sign, magnitude);
+        }
+        let padded = format!("{:0>1$}", magnitude, self.scale as usize + 1);
+        let (whole, fraction) = padded.split_at(padded.len() - self.scale as usize);
+        write!(f, "{}{}.{}", sign, whole, fraction)
+    }
+}
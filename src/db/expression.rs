@@ -0,0 +1,1134 @@
+use chrono::TimeDelta;
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+use super::array::parse_array;
+use super::data::{Collation, Column, DataType, Value};
+use super::datetime::Interval;
+use super::decimal::{DIVISION_SCALE, MAX_PRECISION};
+use super::function::{call_function, function_type};
+use super::json::{parse_text_path, Json, PathStep};
+use crate::parser::quote_identifier;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOperator {
+    Minus,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Concat,
+    /// `->`, the member or element of a JSON document as JSON.
+    JsonGet,
+    /// `->>`, the same as text.
+    JsonGetText,
+    /// `#>`, the value at a path such as `'{a,0}'` as JSON.
+    JsonPath,
+    /// `#>>`, the same as text.
+    JsonPathText,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Literal(Value),
+    Column(String),
+    /// `table.column`, resolved against a column named "table.column".
+    QualifiedColumn {
+        table: String,
+        column: String,
+    },
+    Unary {
+        operator: UnaryOperator,
+        operand: Box<Expression>,
+    },
+    Binary {
+        left: Box<Expression>,
+        operator: BinaryOperator,
+        right: Box<Expression>,
+    },
+    Between {
+        expression: Box<Expression>,
+        low: Box<Expression>,
+        high: Box<Expression>,
+        negated: bool,
+    },
+    InList {
+        expression: Box<Expression>,
+        list: Vec<Expression>,
+        negated: bool,
+    },
+    Like {
+        expression: Box<Expression>,
+        pattern: Box<Expression>,
+        negated: bool,
+    },
+    IsNull {
+        expression: Box<Expression>,
+        negated: bool,
+    },
+    Function {
+        name: String,
+        args: Vec<Expression>,
+    },
+    /// `ARRAY[a, b]`, whose elements are converted to the type they all fit.
+    Array(Vec<Expression>),
+    /// `array[index]`, counting from 1; an index out of range gives NULL.
+    Subscript {
+        expression: Box<Expression>,
+        index: Box<Expression>,
+    },
+    /// `x = ANY(array)` or `x < ALL(array)`, comparing with every element.
+    Quantified {
+        expression: Box<Expression>,
+        operator: BinaryOperator,
+        array: Box<Expression>,
+        all: bool,
+    },
+    /// `text COLLATE NOCASE`, which compares under the given collation
+    /// whatever the operand's column says.
+    Collate {
+        expression: Box<Expression>,
+        collation: Collation,
+    },
+    /// `CAST(x AS type)` or `x::type`, converting as `Value::cast_to` does.
+    /// TRY_CAST gives NULL when the value can't be converted.
+    Cast {
+        expression: Box<Expression>,
+        data_type: DataType,
+        try_cast: bool,
+    },
+}
+
+impl BinaryOperator {
+    /// Binding power used both by the parser and to parenthesize on display.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinaryOperator::Or => 1,
+            BinaryOperator::And => 2,
+            BinaryOperator::Equal
+            | BinaryOperator::NotEqual
+            | BinaryOperator::Less
+            | BinaryOperator::LessOrEqual
+            | BinaryOperator::Greater
+            | BinaryOperator::GreaterOrEqual => 4,
+            BinaryOperator::Concat
+            | BinaryOperator::JsonGet
+            | BinaryOperator::JsonGetText
+            | BinaryOperator::JsonPath
+            | BinaryOperator::JsonPathText => 6,
+            BinaryOperator::Add | BinaryOperator::Subtract => 7,
+            BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo => 8,
+        }
+    }
+}
+
+impl Expression {
+    fn precedence(&self) -> u8 {
+        match self {
+            Expression::Binary { operator, .. } => operator.precedence(),
+            Expression::Unary {
+                operator: UnaryOperator::Not,
+                ..
+            } => 3,
+            Expression::Quantified { .. } => 4,
+            Expression::Between { .. }
+            | Expression::InList { .. }
+            | Expression::Like { .. }
+            | Expression::IsNull { .. } => 5,
+            Expression::Unary {
+                operator: UnaryOperator::Minus,
+                ..
+            } => 9,
+            Expression::Literal(_)
+            | Expression::Column(_)
+            | Expression::QualifiedColumn { .. }
+            | Expression::Function { .. }
+            | Expression::Array(_)
+            | Expression::Subscript { .. }
+            | Expression::Collate { .. }
+            | Expression::Cast { .. } => 10,
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            Expression::Literal(_) | Expression::Column(_) | Expression::QualifiedColumn { .. } => {
+                Vec::new()
+            }
+            Expression::Unary { operand, .. } => vec![operand],
+            Expression::Binary { left, right, .. } => vec![left, right],
+            Expression::Between {
+                expression,
+                low,
+                high,
+                ..
+            } => vec![expression, low, high],
+            Expression::InList {
+                expression, list, ..
+            } => std::iter::once(&mut **expression)
+                .chain(list.iter_mut())
+                .collect(),
+            Expression::Like {
+                expression,
+                pattern,
+                ..
+            } => vec![expression, pattern],
+            Expression::IsNull { expression, .. } => vec![expression],
+            Expression::Function { args, .. } | Expression::Array(args) => {
+                args.iter_mut().collect()
+            }
+            Expression::Subscript { expression, index } => vec![expression, index],
+            Expression::Quantified {
+                expression, array, ..
+            } => vec![expression, array],
+            Expression::Collate { expression, .. } | Expression::Cast { expression, .. } => {
+                vec![expression]
+            }
+        }
+    }
+
+    /// The collation this expression's text compares with: its own COLLATE,
+    /// or that of the column it reads.
+    pub fn collation(&self, columns: &[Column]) -> Option<Collation> {
+        self.explicit_collation()
+            .or_else(|| self.column_collation(columns))
+    }
+
+    fn explicit_collation(&self) -> Option<Collation> {
+        match self {
+            Expression::Collate { collation, .. } => Some(*collation),
+            _ => None,
+        }
+    }
+
+    fn column_collation(&self, columns: &[Column]) -> Option<Collation> {
+        match self {
+            // An element of an array column compares like the column
+            Expression::Subscript { expression, .. } => expression.column_collation(columns),
+            _ => self.source_column(columns).map(|column| column.collation),
+        }
+    }
+
+    /// The column a plain column reference reads.
+    fn source_column<'a>(&self, columns: &'a [Column]) -> Option<&'a Column> {
+        let name = match self {
+            Expression::Column(name) => name.clone(),
+            Expression::QualifiedColumn { table, column } => format!("{}.{}", table, column),
+            _ => return None,
+        };
+        columns.iter().find(|column| column.name == name)
+    }
+
+    /// The type this expression gives over rows of `columns`, worked out from
+    /// the expression alone so it holds for any rows, or none. `None` when
+    /// only the values could tell, as for NULL.
+    pub fn result_type(&self, columns: &[Column]) -> Option<DataType> {
+        match self {
+            // A text literal isn't sized to itself, as other rows may be longer
+            Expression::Literal(Value::Varchar(_)) => Some(DataType::Text),
+            Expression::Literal(value) => value.data_type(),
+            Expression::Column(_) | Expression::QualifiedColumn { .. } => self
+                .source_column(columns)
+                .map(|column| column.data_type.clone()),
+            Expression::Unary {
+                operator: UnaryOperator::Minus,
+                operand,
+            } => operand.result_type(columns),
+            Expression::Binary {
+                left,
+                operator,
+                right,
+            } => match operator {
+                BinaryOperator::Concat => {
+                    match (left.result_type(columns), right.result_type(columns)) {
+                        (Some(DataType::Blob), Some(DataType::Blob)) => Some(DataType::Blob),
+                        _ => Some(DataType::Text),
+                    }
+                }
+                BinaryOperator::JsonGet | BinaryOperator::JsonPath => Some(DataType::Json),
+                BinaryOperator::JsonGetText | BinaryOperator::JsonPathText => Some(DataType::Text),
+                BinaryOperator::Add
+                | BinaryOperator::Subtract
+                | BinaryOperator::Multiply
+                | BinaryOperator::Divide
+                | BinaryOperator::Modulo => arithmetic_type(
+                    *operator,
+                    &left.result_type(columns)?,
+                    &right.result_type(columns)?,
+                ),
+                _ => Some(DataType::Boolean),
+            },
+            Expression::Unary { .. }
+            | Expression::Between { .. }
+            | Expression::InList { .. }
+            | Expression::Like { .. }
+            | Expression::IsNull { .. }
+            | Expression::Quantified { .. } => Some(DataType::Boolean),
+            Expression::Function { name, args } => {
+                let arg_types = args
+                    .iter()
+                    .map(|arg| arg.result_type(columns))
+                    .collect::<Vec<_>>();
+                function_type(name, &arg_types)
+            }
+            Expression::Array(elements) => elements
+                .iter()
+                .filter_map(|element| element.result_type(columns))
+                .reduce(|current, next| match (current, next) {
+                    (current, next) if current.is_numeric() && next.is_numeric() => {
+                        current.common_numeric(&next)
+                    }
+                    (current, _) => current,
+                })
+                .map(|element_type| DataType::Array(Box::new(element_type))),
+            Expression::Subscript { expression, .. } => match expression.result_type(columns)? {
+                DataType::Array(element_type) => Some(*element_type),
+                _ => None,
+            },
+            Expression::Collate { expression, .. } => expression.result_type(columns),
+            Expression::Cast { data_type, .. } => Some(data_type.clone()),
+        }
+    }
+
+    /// Names of the unqualified columns the expression reads.
+    pub fn column_names(&self) -> Vec<String> {
+        // Walks a copy so one traversal serves both readers and `rename_column`
+        let mut names = Vec::new();
+        self.clone()
+            .walk_columns(&mut |name| names.push(name.clone()));
+        names
+    }
+
+    /// Whether the expression calls a function that `test` picks out.
+    pub fn calls(&self, test: impl Fn(&str) -> bool) -> bool {
+        let mut found = false;
+        let _ = self.clone().replace_calls(&mut |name, _| {
+            found |= test(name);
+            Ok(None)
+        });
+        found
+    }
+
+    pub fn rename_column(&mut self, from: &str, to: &str) {
+        self.walk_columns(&mut |name| {
+            if name == from {
+                *name = to.to_string();
+            }
+        });
+    }
+
+    fn walk_columns(&mut self, visit: &mut impl FnMut(&mut String)) {
+        match self {
+            Expression::Column(name) => visit(name),
+            _ => {
+                for child in self.children_mut() {
+                    child.walk_columns(visit);
+                }
+            }
+        }
+    }
+
+    /// Replaces each function call that `replace` answers with its result,
+    /// innermost first. Functions needing database state are bound this way
+    /// before evaluation.
+    pub fn replace_calls(
+        &mut self,
+        replace: &mut impl FnMut(&str, &[Expression]) -> Result<Option<Value>, String>,
+    ) -> Result<(), String> {
+        for child in self.children_mut() {
+            child.replace_calls(replace)?;
+        }
+        if let Expression::Function { name, args } = self {
+            if let Some(value) = replace(name, args)? {
+                *self = Expression::Literal(value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the type of every CAST with what `resolve` makes of it, so
+    /// enum types named by a statement become the declared types.
+    pub fn resolve_types(
+        &mut self,
+        resolve: &impl Fn(&DataType) -> Result<DataType, String>,
+    ) -> Result<(), String> {
+        for child in self.children_mut() {
+            child.resolve_types(resolve)?;
+        }
+        if let Expression::Cast { data_type, .. } = self {
+            *data_type = resolve(data_type)?;
+        }
+        Ok(())
+    }
+
+    /// Evaluates the expression against one row described by `columns`.
+    ///
+    /// Pass empty slices to evaluate constant expressions such as INSERT values.
+    /// NULL follows SQL's three-valued logic: it propagates through operators and
+    /// predicates, and AND/OR only yield NULL when the other operand can't decide.
+    pub fn evaluate(&self, columns: &[Column], values: &[Value]) -> Result<Value, String> {
+        match self {
+            Expression::Literal(value) => Ok(value.clone()),
+            Expression::Column(name) => columns
+                .iter()
+                .position(|column| &column.name == name)
+                .and_then(|index| values.get(index))
+                .cloned()
+                .ok_or_else(|| format!("Column '{}' not found.", name)),
+            Expression::QualifiedColumn { table, column } => {
+                let name = format!("{}.{}", table, column);
+                columns
+                    .iter()
+                    .position(|c| c.name == name)
+                    .and_then(|index| values.get(index))
+                    .cloned()
+                    .ok_or_else(|| format!("Column '{}' not found.", name))
+            }
+            Expression::Unary { operator, operand } => {
+                let value = operand.evaluate(columns, values)?;
+                match (operator, value) {
+                    (_, Value::Null) => Ok(Value::Null),
+                    (UnaryOperator::Not, Value::Boolean(b)) => Ok(Value::Boolean(!b)),
+                    (UnaryOperator::Minus, Value::Float(f)) => Ok(Value::Float(-f)),
+                    (UnaryOperator::Minus, Value::Double(f)) => Ok(Value::Double(-f)),
+                    (UnaryOperator::Minus, Value::Decimal(d)) => Ok(Value::Decimal(d.neg())),
+                    (UnaryOperator::Minus, Value::Interval(i)) => i
+                        .checked_neg()
+                        .map(Value::Interval)
+                        .ok_or_else(|| "Interval out of range.".to_string()),
+                    (UnaryOperator::Minus, value) if value.as_i64().is_some() => value
+                        .as_i64()
+                        .unwrap()
+                        .checked_neg()
+                        .and_then(|negated| {
+                            Value::from_i64(&value.data_type().unwrap(), negated).ok()
+                        })
+                        .ok_or_else(|| "Integer overflow.".to_string()),
+                    (UnaryOperator::Not, value) => {
+                        Err(format!("NOT expects a boolean, got '{}'.", value))
+                    }
+                    (UnaryOperator::Minus, value) => {
+                        Err(format!("Cannot negate non-numeric value '{}'.", value))
+                    }
+                }
+            }
+            Expression::Binary {
+                left: left_expression,
+                operator,
+                right: right_expression,
+            } => {
+                let left = left_expression.evaluate(columns, values)?;
+                // AND/OR short-circuit on the left operand
+                match (operator, &left) {
+                    (BinaryOperator::And, Value::Boolean(false)) => {
+                        return Ok(Value::Boolean(false))
+                    }
+                    (BinaryOperator::Or, Value::Boolean(true)) => return Ok(Value::Boolean(true)),
+                    _ => {}
+                }
+                let collation = shared_collation(&[left_expression, right_expression], columns);
+                let right = right_expression.evaluate(columns, values)?;
+                evaluate_binary(*operator, left, right, collation)
+            }
+            Expression::Between {
+                expression,
+                low,
+                high,
+                negated,
+            } => {
+                let collation = shared_collation(&[expression, low, high], columns);
+                let value = expression.evaluate(columns, values)?;
+                let low = low.evaluate(columns, values)?;
+                let high = high.evaluate(columns, values)?;
+                if [&value, &low, &high].contains(&&Value::Null) {
+                    return Ok(Value::Null);
+                }
+                let inside = value.compare_with(&low, collation)? != Ordering::Less
+                    && value.compare_with(&high, collation)? != Ordering::Greater;
+                Ok(Value::Boolean(inside != *negated))
+            }
+            Expression::InList {
+                expression,
+                list,
+                negated,
+            } => {
+                let operands = std::iter::once(&**expression)
+                    .chain(list)
+                    .collect::<Vec<_>>();
+                let collation = shared_collation(&operands, columns);
+                let value = expression.evaluate(columns, values)?;
+                if value == Value::Null {
+                    return Ok(Value::Null);
+                }
+                let mut saw_null = false;
+                for item in list {
+                    match item.evaluate(columns, values)? {
+                        Value::Null => saw_null = true,
+                        item => {
+                            if value.compare_with(&item, collation)? == Ordering::Equal {
+                                return Ok(Value::Boolean(!*negated));
+                            }
+                        }
+                    }
+                }
+                // "x IN (1, NULL)" can't rule out a match when x isn't 1
+                match saw_null {
+                    true => Ok(Value::Null),
+                    false => Ok(Value::Boolean(*negated)),
+                }
+            }
+            Expression::Like {
+                expression,
+                pattern,
+                negated,
+            } => {
+                let collation = shared_collation(&[expression, pattern], columns);
+                let value = expression.evaluate(columns, values)?;
+                let pattern = pattern.evaluate(columns, values)?;
+                if value == Value::Null || pattern == Value::Null {
+                    return Ok(Value::Null);
+                }
+                let (value, pattern) = (value.to_string(), pattern.to_string());
+                let chars = collation.key(&value).chars().collect::<Vec<_>>();
+                let pattern = collation.key(&pattern).chars().collect::<Vec<_>>();
+                Ok(Value::Boolean(like(&chars, &pattern) != *negated))
+            }
+            Expression::IsNull {
+                expression,
+                negated,
+            } => {
+                let value = expression.evaluate(columns, values)?;
+                Ok(Value::Boolean((value == Value::Null) != *negated))
+            }
+            Expression::Function { name, args } => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.evaluate(columns, values))
+                    .collect::<Result<Vec<_>, _>>()?;
+                call_function(name, args)
+            }
+            Expression::Array(elements) => {
+                let values = elements
+                    .iter()
+                    .map(|element| element.evaluate(columns, values))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let element_type = DataType::of_values(values.iter());
+                values
+                    .into_iter()
+                    .map(|value| value.coerce_to(&element_type))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Value::Array)
+                    .map_err(|e| format!("ARRAY elements must share a type: {}", e))
+            }
+            Expression::Subscript { expression, index } => {
+                let value = expression.evaluate(columns, values)?;
+                let index = index.evaluate(columns, values)?;
+                match (value, index) {
+                    (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+                    (Value::Array(elements), index) if index.as_i64().is_some() => {
+                        let position = usize::try_from(index.as_i64().unwrap() - 1).ok();
+                        Ok(position
+                            .and_then(|position| elements.get(position))
+                            .cloned()
+                            .unwrap_or(Value::Null))
+                    }
+                    (Value::Array(_), index) => {
+                        Err(format!("Array index must be an integer, got '{}'.", index))
+                    }
+                    (value, _) => Err(format!("Cannot subscript non-array value '{}'.", value)),
+                }
+            }
+            Expression::Quantified {
+                expression,
+                operator,
+                array,
+                all,
+            } => {
+                let collation = shared_collation(&[expression, array], columns);
+                let value = expression.evaluate(columns, values)?;
+                let elements = match array.evaluate(columns, values)? {
+                    Value::Null => return Ok(Value::Null),
+                    Value::Array(elements) => elements,
+                    // Text such as '{1,2}' is read as an array of the left side's type
+                    Value::Varchar(text) => {
+                        parse_array(&text, &value.data_type().unwrap_or(DataType::Text))?
+                    }
+                    other => {
+                        return Err(format!(
+                            "{} expects an array, got '{}'.",
+                            if *all { "ALL" } else { "ANY" },
+                            other
+                        ))
+                    }
+                };
+                // One TRUE decides ANY and one FALSE decides ALL; failing
+                // that, a NULL comparison leaves the result unknown
+                let decisive = !*all;
+                let mut saw_null = false;
+                for element in elements {
+                    match evaluate_binary(*operator, value.clone(), element, collation)? {
+                        Value::Boolean(result) if result == decisive => {
+                            return Ok(Value::Boolean(decisive))
+                        }
+                        Value::Null => saw_null = true,
+                        _ => {}
+                    }
+                }
+                match saw_null {
+                    true => Ok(Value::Null),
+                    false => Ok(Value::Boolean(!decisive)),
+                }
+            }
+            Expression::Collate { expression, .. } => expression.evaluate(columns, values),
+            Expression::Cast {
+                expression,
+                data_type,
+                try_cast,
+            } => {
+                let value = expression.evaluate(columns, values)?;
+                match value.cast_to(data_type) {
+                    Err(_) if *try_cast => Ok(Value::Null),
+                    result => result,
+                }
+            }
+        }
+    }
+}
+
+/// The collation for comparing `operands`: an explicit COLLATE on any of
+/// them wins, then the collation of a column among them, then BINARY.
+fn shared_collation(operands: &[&Expression], columns: &[Column]) -> Collation {
+    operands
+        .iter()
+        .find_map(|operand| operand.explicit_collation())
+        .or_else(|| {
+            operands
+                .iter()
+                .find_map(|operand| operand.column_collation(columns))
+        })
+        .unwrap_or_default()
+}
+
+fn evaluate_binary(
+    operator: BinaryOperator,
+    left: Value,
+    right: Value,
+    collation: Collation,
+) -> Result<Value, String> {
+    let ordering = |expected: &[Ordering]| -> Result<Value, String> {
+        Ok(Value::Boolean(
+            expected.contains(&left.compare_with(&right, collation)?),
+        ))
+    };
+
+    match operator {
+        BinaryOperator::And | BinaryOperator::Or => evaluate_logical(operator, left, right),
+        _ if left == Value::Null || right == Value::Null => Ok(Value::Null),
+        BinaryOperator::Equal => ordering(&[Ordering::Equal]),
+        BinaryOperator::NotEqual => ordering(&[Ordering::Less, Ordering::Greater]),
+        BinaryOperator::Less => ordering(&[Ordering::Less]),
+        BinaryOperator::LessOrEqual => ordering(&[Ordering::Less, Ordering::Equal]),
+        BinaryOperator::Greater => ordering(&[Ordering::Greater]),
+        BinaryOperator::GreaterOrEqual => ordering(&[Ordering::Greater, Ordering::Equal]),
+        BinaryOperator::Concat => match (&left, &right) {
+            (Value::Blob(l), Value::Blob(r)) => Ok(Value::Blob([l.as_slice(), r].concat())),
+            _ => Ok(Value::Varchar(format!("{}{}", left, right))),
+        },
+        BinaryOperator::JsonGet
+        | BinaryOperator::JsonGetText
+        | BinaryOperator::JsonPath
+        | BinaryOperator::JsonPathText => evaluate_json(operator, left, right),
+        _ => evaluate_arithmetic(operator, left, right),
+    }
+}
+
+/// Looks up a key, index or path in a JSON document, or in text holding one.
+/// A step that doesn't exist yields NULL, as does JSON null read as text.
+fn evaluate_json(operator: BinaryOperator, left: Value, right: Value) -> Result<Value, String> {
+    let steps = match (operator, right) {
+        (BinaryOperator::JsonPath | BinaryOperator::JsonPathText, right) => {
+            parse_text_path(&right.to_string())?
+        }
+        (_, Value::Varchar(key)) => vec![PathStep::Key(key)],
+        (_, right) if right.as_i64().is_some() => vec![PathStep::Index(right.as_i64().unwrap())],
+        (_, right) => {
+            return Err(format!(
+                "Expected a key or array index after {}, got '{}'.",
+                operator, right
+            ))
+        }
+    };
+    let json = left.into_json()?;
+    let Some(found) = json.at_path(&steps) else {
+        return Ok(Value::Null);
+    };
+    Ok(match operator {
+        BinaryOperator::JsonGet | BinaryOperator::JsonPath => Value::Json(found.clone()),
+        _ if *found == Json::Null => Value::Null,
+        _ => Value::Varchar(found.to_text()),
+    })
+}
+
+/// AND and OR over TRUE, FALSE and NULL, where NULL stands for "unknown".
+fn evaluate_logical(operator: BinaryOperator, left: Value, right: Value) -> Result<Value, String> {
+    let truth = |value: &Value| match value {
+        Value::Boolean(b) => Ok(Some(*b)),
+        Value::Null => Ok(None),
+        _ => Err(format!(
+            "Expected boolean operands, got '{}' and '{}'.",
+            left, right
+        )),
+    };
+    let (l, r) = (truth(&left)?, truth(&right)?);
+
+    // The deciding value wins over NULL: FALSE for AND, TRUE for OR
+    let decisive = operator == BinaryOperator::Or;
+    let result = match (l, r) {
+        (Some(l), _) if l == decisive => Some(decisive),
+        (_, Some(r)) if r == decisive => Some(decisive),
+        (Some(_), Some(_)) => Some(!decisive),
+        _ => None,
+    };
+    Ok(result.map_or(Value::Null, Value::Boolean))
+}
+
+pub fn evaluate_arithmetic(
+    operator: BinaryOperator,
+    left: Value,
+    right: Value,
+) -> Result<Value, String> {
+    if let Some(result) = evaluate_datetime_arithmetic(operator, &left, &right) {
+        return result;
+    }
+
+    let data_type = match (left.data_type(), right.data_type()) {
+        (Some(l), Some(r)) if l.is_numeric() && r.is_numeric() => l.common_numeric(&r),
+        _ => {
+            return Err(format!(
+                "Arithmetic requires numeric operands, got '{}' and '{}'.",
+                left, right
+            ))
+        }
+    };
+
+    // Integers are computed in i64 and must then fit the result type
+    if data_type.is_integer() {
+        let (l, r) = (left.as_i64().unwrap(), right.as_i64().unwrap());
+        let result = match operator {
+            BinaryOperator::Add => l.checked_add(r),
+            BinaryOperator::Subtract => l.checked_sub(r),
+            BinaryOperator::Multiply => l.checked_mul(r),
+            BinaryOperator::Divide | BinaryOperator::Modulo if r == 0 => {
+                return Err("Division by zero.".to_string())
+            }
+            BinaryOperator::Divide => l.checked_div(r),
+            _ => l.checked_rem(r),
+        };
+        return result
+            .and_then(|result| Value::from_i64(&data_type, result).ok())
+            .ok_or_else(|| "Integer overflow.".to_string());
+    }
+
+    // DECIMAL stays exact; integer operands join in as DECIMALs
+    if let DataType::Decimal(..) | DataType::Numeric = data_type {
+        let (l, r) = (left.as_decimal().unwrap(), right.as_decimal().unwrap());
+        let result = match operator {
+            BinaryOperator::Add => l.checked_add(r),
+            BinaryOperator::Subtract => l.checked_sub(r),
+            BinaryOperator::Multiply => l.checked_mul(r),
+            BinaryOperator::Divide | BinaryOperator::Modulo if r.is_zero() => {
+                return Err("Division by zero.".to_string())
+            }
+            BinaryOperator::Divide => l.checked_div(r),
+            _ => l.checked_rem(r),
+        };
+        return result
+            .map(Value::Decimal)
+            .ok_or_else(|| "Numeric overflow.".to_string());
+    }
+
+    // FLOAT is computed in f64 too; rounding back gives the same result
+    let (l, r) = (left.as_f64().unwrap(), right.as_f64().unwrap());
+    let result = match operator {
+        BinaryOperator::Add => l + r,
+        BinaryOperator::Subtract => l - r,
+        BinaryOperator::Multiply => l * r,
+        BinaryOperator::Divide | BinaryOperator::Modulo if r == 0.0 => {
+            return Err("Division by zero.".to_string())
+        }
+        BinaryOperator::Divide => l / r,
+        _ => l % r,
+    };
+    Ok(match data_type {
+        DataType::Float => Value::Float(result as f32),
+        _ => Value::Double(result),
+    })
+}
+
+/// The type `evaluate_arithmetic` gives for operands of these types. A
+/// DECIMAL result has room for every digit the operator can produce.
+fn arithmetic_type(
+    operator: BinaryOperator,
+    left: &DataType,
+    right: &DataType,
+) -> Option<DataType> {
+    use BinaryOperator::{Add, Divide, Multiply, Subtract};
+    let is_datetime = |data_type: &DataType| {
+        matches!(
+            data_type,
+            DataType::Date | DataType::Timestamp | DataType::TimestampTz
+        )
+    };
+
+    // The same cases as evaluate_datetime_arithmetic, in the same order
+    let result = match (operator, left, right) {
+        (Add | Subtract, DataType::Date, days) | (Add, days, DataType::Date)
+            if days.is_integer() =>
+        {
+            DataType::Date
+        }
+        (Subtract, DataType::Date, DataType::Date) => DataType::Int,
+        (Add | Subtract, value, DataType::Interval) | (Add, DataType::Interval, value)
+            if is_datetime(value) =>
+        {
+            match value {
+                DataType::TimestampTz => DataType::TimestampTz,
+                _ => DataType::Timestamp,
+            }
+        }
+        (Subtract, l, r) if is_datetime(l) && is_datetime(r) => DataType::Interval,
+        (Add | Subtract, DataType::Time, DataType::Interval)
+        | (Add, DataType::Interval, DataType::Time) => DataType::Time,
+        (Subtract, DataType::Time, DataType::Time)
+        | (Add | Subtract, DataType::Interval, DataType::Interval) => DataType::Interval,
+        (Multiply, DataType::Interval, factor) | (Multiply, factor, DataType::Interval)
+            if factor.is_integer() =>
+        {
+            DataType::Interval
+        }
+        _ if left.is_numeric() && right.is_numeric() => match left.common_numeric(right) {
+            DataType::Decimal(..) => {
+                let (l_precision, l_scale) = left.decimal_digits()?;
+                let (r_precision, r_scale) = right.decimal_digits()?;
+                let (l_whole, r_whole) = (l_precision - l_scale, r_precision - r_scale);
+                let (whole, scale) = match operator {
+                    Add | Subtract => (l_whole.max(r_whole) + 1, l_scale.max(r_scale)),
+                    Multiply => (l_whole + r_whole, l_scale + r_scale),
+                    Divide => (l_whole + r_scale, l_scale.max(r_scale) + DIVISION_SCALE),
+                    _ => (l_whole.min(r_whole), l_scale.max(r_scale)),
+                };
+                let scale = scale.min(MAX_PRECISION);
+                DataType::Decimal((whole + scale).clamp(1, MAX_PRECISION), scale)
+            }
+            common => common,
+        },
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// Calendar arithmetic: intervals move timestamps and times, integers move
+/// dates by days, and subtracting two of a kind gives the gap between them.
+/// A date moved by an interval becomes a TIMESTAMP.
+///
+/// Returns `None` when the operands aren't dates, times or intervals.
+fn evaluate_datetime_arithmetic(
+    operator: BinaryOperator,
+    left: &Value,
+    right: &Value,
+) -> Option<Result<Value, String>> {
+    use BinaryOperator::{Add, Multiply, Subtract};
+    let signed = |interval: &Interval| match operator {
+        Subtract => interval.checked_neg(),
+        _ => Some(*interval),
+    };
+    let shift = |value: &Value, interval: &Interval| {
+        let moved = signed(interval)?.add_to(value.as_datetime()?)?;
+        Some(match value {
+            Value::TimestampTz(_) => Value::TimestampTz(moved.and_utc()),
+            _ => Value::Timestamp(moved),
+        })
+    };
+    let shift_date = |date: &chrono::NaiveDate, days: i64| {
+        let days = match operator {
+            Subtract => days.checked_neg()?,
+            _ => days,
+        };
+        date.checked_add_signed(TimeDelta::try_days(days)?)
+            .map(Value::Date)
+    };
+
+    let result = match (operator, left, right) {
+        (Add | Subtract, Value::Date(date), days) if days.as_i64().is_some() => {
+            shift_date(date, days.as_i64().unwrap())
+        }
+        (Add, days, Value::Date(date)) if days.as_i64().is_some() => {
+            shift_date(date, days.as_i64().unwrap())
+        }
+        (Subtract, Value::Date(l), Value::Date(r)) => {
+            i32::try_from((*l - *r).num_days()).ok().map(Value::Int)
+        }
+        (Add | Subtract, value, Value::Interval(interval)) if value.as_datetime().is_some() => {
+            shift(value, interval)
+        }
+        (Add, Value::Interval(interval), value) if value.as_datetime().is_some() => {
+            shift(value, interval)
+        }
+        (Subtract, l, r) if l.as_datetime().is_some() && r.as_datetime().is_some() => {
+            Interval::between(l.as_datetime().unwrap(), r.as_datetime().unwrap())
+                .map(Value::Interval)
+        }
+        (Add | Subtract, Value::Time(time), Value::Interval(interval)) => {
+            signed(interval).map(|interval| Value::Time(interval.add_to_time(*time)))
+        }
+        (Add, Value::Interval(interval), Value::Time(time)) => {
+            Some(Value::Time(interval.add_to_time(*time)))
+        }
+        (Subtract, Value::Time(l), Value::Time(r)) => (*l - *r).num_microseconds().map(|micros| {
+            Value::Interval(Interval {
+                micros,
+                ..Interval::default()
+            })
+        }),
+        (Add | Subtract, Value::Interval(l), Value::Interval(r)) => signed(r)
+            .and_then(|r| l.checked_add(r))
+            .map(Value::Interval),
+        (Multiply, Value::Interval(interval), factor)
+        | (Multiply, factor, Value::Interval(interval))
+            if factor.as_i64().is_some() =>
+        {
+            interval
+                .checked_mul(factor.as_i64().unwrap())
+                .map(Value::Interval)
+        }
+        _ => return None,
+    };
+    Some(result.ok_or_else(|| "Date/time value out of range.".to_string()))
+}
+
+/// SQL LIKE matching where `%` matches any run of characters and `_` exactly one.
+///
+/// Only the last `%` seen needs retrying, as it can stretch over anything an
+/// earlier one would, so this runs in O(value × pattern) time.
+fn like(value: &[char], pattern: &[char]) -> bool {
+    let (mut v, mut p) = (0, 0);
+    // Pattern index after the last '%', and the value index it was retried at
+    let mut retry = None;
+
+    while v < value.len() {
+        match pattern.get(p) {
+            Some('%') => {
+                p += 1;
+                retry = Some((p, v));
+            }
+            Some('_') => {
+                v += 1;
+                p += 1;
+            }
+            Some(ch) if *ch == value[v] => {
+                v += 1;
+                p += 1;
+            }
+            // Let the last '%' take one more character and try again
+            _ => match retry {
+                Some((after, at)) => {
+                    p = after;
+                    v = at + 1;
+                    retry = Some((after, at + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&ch| ch == '%')
+}
+
+impl Display for UnaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnaryOperator::Minus => write!(f, "-"),
+            UnaryOperator::Not => write!(f, "NOT "),
+        }
+    }
+}
+
+impl Display for BinaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            BinaryOperator::Add => "+",
+            BinaryOperator::Subtract => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::Modulo => "%",
+            BinaryOperator::Concat => "||",
+            BinaryOperator::JsonGet => "->",
+            BinaryOperator::JsonGetText => "->>",
+            BinaryOperator::JsonPath => "#>",
+            BinaryOperator::JsonPathText => "#>>",
+            BinaryOperator::Equal => "=",
+            BinaryOperator::NotEqual => "<>",
+            BinaryOperator::Less => "<",
+            BinaryOperator::LessOrEqual => "<=",
+            BinaryOperator::Greater => ">",
+            BinaryOperator::GreaterOrEqual => ">=",
+            BinaryOperator::And => "AND",
+            BinaryOperator::Or => "OR",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Children that bind more loosely than their parent need parentheses
+        let child = |f: &mut std::fmt::Formatter<'_>, e: &Expression, min: u8| {
+            if e.precedence() < min {
+                write!(f, "({})", e)
+            } else {
+                write!(f, "{}", e)
+            }
+        };
+        let not = |negated: &bool| if *negated { "NOT " } else { "" };
+
+        match self {
+            Expression::Literal(value) => write!(f, "{}", value.to_sql_literal()),
+            Expression::Column(name) => write!(f, "{}", quote_identifier(name)),
+            Expression::QualifiedColumn { table, column } => write!(
+                f,
+                "{}.{}",
+                quote_identifier(table),
+                quote_identifier(column)
+            ),
+            Expression::Unary {
+                operator: UnaryOperator::Minus,
+                operand,
+            } => {
+                // Keep "- -1" from printing as the comment "--1"
+                let text = operand.to_string();
+                match text.starts_with('-') || operand.precedence() < self.precedence() {
+                    true => write!(f, "-({})", text),
+                    false => write!(f, "-{}", text),
+                }
+            }
+            Expression::Unary { operator, operand } => {
+                write!(f, "{}", operator)?;
+                child(f, operand, self.precedence())
+            }
+            Expression::Binary {
+                left,
+                operator,
+                right,
+            } => {
+                child(f, left, operator.precedence())?;
+                write!(f, " {} ", operator)?;
+                child(f, right, operator.precedence() + 1)
+            }
+            Expression::Between {
+                expression,
+                low,
+                high,
+                negated,
+            } => {
+                child(f, expression, 6)?;
+                write!(f, " {}BETWEEN ", not(negated))?;
+                child(f, low, 6)?;
+                write!(f, " AND ")?;
+                child(f, high, 6)
+            }
+            Expression::InList {
+                expression,
+                list,
+                negated,
+            } => {
+                child(f, expression, 6)?;
+                write!(f, " {}IN (", not(negated))?;
+                for (index, item) in list.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, ")")
+            }
+            Expression::Like {
+                expression,
+                pattern,
+                negated,
+            } => {
+                child(f, expression, 6)?;
+                write!(f, " {}LIKE ", not(negated))?;
+                child(f, pattern, 6)
+            }
+            Expression::IsNull {
+                expression,
+                negated,
+            } => {
+                child(f, expression, 6)?;
+                write!(f, " IS {}NULL", not(negated))
+            }
+            Expression::Function { name, args } => {
+                write!(f, "{}(", name)?;
+                for (index, arg) in args.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+            Expression::Array(elements) => {
+                write!(f, "ARRAY[")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
+            Expression::Subscript { expression, index } => {
+                child(f, expression, 10)?;
+                write!(f, "[{}]", index)
+            }
+            Expression::Quantified {
+                expression,
+                operator,
+                array,
+                all,
+            } => {
+                child(f, expression, 4)?;
+                let quantifier = if *all { "ALL" } else { "ANY" };
+                write!(f, " {} {}({})", operator, quantifier, array)
+            }
+            Expression::Collate {
+                expression,
+                collation,
+            } => {
+                child(f, expression, 10)?;
+                write!(f, " COLLATE {}", collation)
+            }
+            Expression::Cast {
+                expression,
+                data_type,
+                try_cast,
+            } => {
+                let name = if *try_cast { "TRY_CAST" } else { "CAST" };
+                write!(f, "{}({} AS {})", name, expression, data_type)
+            }
+        }
+    }
+}
@@ -0,0 +1,550 @@
+use super::data::{Collation, Column, DataType, Row, Value};
+use super::datetime::{self, Field};
+use super::decimal::{Decimal, DIVISION_SCALE, MAX_PRECISION};
+use super::expression::{evaluate_arithmetic, BinaryOperator, Expression};
+use super::json::{parse_json_path, Json};
+use super::query::ResultSet;
+use super::sequence::Sequence;
+use super::uuid::Uuid;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{NaiveDate, Timelike, Utc};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+fn expect_args(name: &str, args: &[Value], min: usize, max: usize) -> Result<(), String> {
+    if args.len() < min || args.len() > max {
+        let expected = match min == max {
+            true => min.to_string(),
+            false => format!("{} to {}", min, max),
+        };
+        return Err(format!(
+            "Function {}() expects {} arguments, got {}.",
+            name,
+            expected,
+            args.len()
+        ));
+    }
+    Ok(())
+}
+
+fn expect_text<'a>(name: &str, value: &'a Value) -> Result<&'a str, String> {
+    match value {
+        Value::Varchar(text) => Ok(text),
+        _ => Err(format!(
+            "Function {}() expects a string, got '{}'.",
+            name, value
+        )),
+    }
+}
+
+fn expect_int(name: &str, value: &Value) -> Result<i32, String> {
+    match value.as_i64().map(i32::try_from) {
+        Some(Ok(value)) => Ok(value),
+        _ => Err(format!(
+            "Function {}() expects an integer, got '{}'.",
+            name, value
+        )),
+    }
+}
+
+/// The type a call to `name` gives for arguments of `arg_types`, aggregates
+/// included, or `None` when only the result's value could tell.
+pub fn function_type(name: &str, arg_types: &[Option<DataType>]) -> Option<DataType> {
+    let first = arg_types.first().cloned().flatten();
+    match name.to_uppercase().as_str() {
+        "UPPER" | "LOWER" | "TRIM" | "CONCAT" | "SUBSTR" | "SUBSTRING" | "STRFTIME" => {
+            Some(DataType::Text)
+        }
+        "LENGTH" | "ARRAY_LENGTH" | "JSON_ARRAY_LENGTH" => Some(DataType::Int),
+        "NEXTVAL" | "CURRVAL" | "SETVAL" | "LAST_INSERT_ID" => Some(DataType::Int),
+        "NOW" => Some(DataType::TimestampTz),
+        "GEN_RANDOM_UUID" => Some(DataType::Uuid),
+        "DATE_TRUNC" => match arg_types.get(1) {
+            Some(Some(DataType::TimestampTz)) => Some(DataType::TimestampTz),
+            _ => Some(DataType::Timestamp),
+        },
+        "ABS" => first.filter(|data_type| data_type.is_numeric()),
+        // Rounding up may carry into another whole digit
+        "ROUND" => match first? {
+            DataType::Decimal(precision, scale) => {
+                Some(DataType::Decimal((precision + 1).min(MAX_PRECISION), scale))
+            }
+            data_type => Some(data_type).filter(|data_type| data_type.is_numeric()),
+        },
+        "SUM" => match first? {
+            data_type if data_type.is_integer() => Some(DataType::BigInt),
+            DataType::Decimal(_, scale) => Some(DataType::Decimal(MAX_PRECISION, scale)),
+            data_type => Some(data_type).filter(|data_type| data_type.is_numeric()),
+        },
+        "AVG" => match first? {
+            data_type if data_type.is_integer() => {
+                Some(DataType::Decimal(MAX_PRECISION, DIVISION_SCALE))
+            }
+            DataType::Decimal(_, scale) => Some(DataType::Decimal(
+                MAX_PRECISION,
+                (scale + DIVISION_SCALE).min(MAX_PRECISION),
+            )),
+            data_type => Some(data_type).filter(|data_type| data_type.is_numeric()),
+        },
+        "ARRAY_AGG" => first.map(|data_type| DataType::Array(Box::new(data_type))),
+        _ => None,
+    }
+}
+
+/// Evaluates a scalar function call. Names are case-insensitive.
+///
+/// A NULL argument makes the result NULL, except for CONCAT which skips it.
+pub fn call_function(name: &str, args: Vec<Value>) -> Result<Value, String> {
+    let upper = name.to_uppercase();
+    match upper.as_str() {
+        "UPPER" | "LOWER" | "TRIM" | "LENGTH" | "SUBSTR" | "SUBSTRING" | "ABS" | "ROUND"
+        | "DATE_TRUNC" | "EXTRACT" | "STRFTIME" | "JSON_EXTRACT" | "JSON_ARRAY_LENGTH"
+        | "ARRAY_LENGTH"
+            if args.contains(&Value::Null) =>
+        {
+            Ok(Value::Null)
+        }
+        "UPPER" | "LOWER" | "TRIM" | "LENGTH" => {
+            expect_args(name, &args, 1, 1)?;
+            let text = args[0].to_string();
+            Ok(match upper.as_str() {
+                "UPPER" => Value::Varchar(text.to_uppercase()),
+                "LOWER" => Value::Varchar(text.to_lowercase()),
+                "TRIM" => Value::Varchar(text.trim().to_string()),
+                // A BLOB's length is its number of bytes
+                _ => Value::Int(match &args[0] {
+                    Value::Blob(bytes) => bytes.len(),
+                    // CHAR's padding isn't part of its value
+                    Value::Char(text) => text.trim_end_matches(' ').chars().count(),
+                    _ => text.chars().count(),
+                } as i32),
+            })
+        }
+        "CONCAT" => Ok(Value::Varchar(
+            args.iter()
+                .filter(|arg| **arg != Value::Null)
+                .map(|arg| arg.to_string())
+                .collect(),
+        )),
+        "SUBSTR" | "SUBSTRING" => {
+            expect_args(name, &args, 2, 3)?;
+            let text = args[0].to_string();
+            // Positions are 1-based like in standard SQL
+            let start = expect_int(name, &args[1])?.max(1) as usize - 1;
+            let length = match args.get(2) {
+                Some(length) => expect_int(name, length)?.max(0) as usize,
+                None => usize::MAX,
+            };
+            Ok(Value::Varchar(
+                text.chars().skip(start).take(length).collect(),
+            ))
+        }
+        "ABS" => {
+            expect_args(name, &args, 1, 1)?;
+            match &args[0] {
+                value if value.data_type().is_some_and(|t| t.is_integer()) => value
+                    .as_i64()
+                    .unwrap()
+                    .checked_abs()
+                    .and_then(|abs| Value::from_i64(&value.data_type().unwrap(), abs).ok())
+                    .ok_or_else(|| "Integer overflow.".to_string()),
+                Value::Float(value) => Ok(Value::Float(value.abs())),
+                Value::Double(value) => Ok(Value::Double(value.abs())),
+                Value::Decimal(value) => Ok(Value::Decimal(value.abs())),
+                other => Err(format!(
+                    "Function {}() expects a number, got '{}'.",
+                    name, other
+                )),
+            }
+        }
+        "ROUND" => {
+            expect_args(name, &args, 1, 2)?;
+            let digits = match args.get(1) {
+                Some(digits) => expect_int(name, digits)?,
+                None => 0,
+            };
+            match &args[0] {
+                value if value.data_type().is_some_and(|t| t.is_integer()) => Ok(value.clone()),
+                Value::Float(value) => {
+                    let factor = 10f32.powi(digits);
+                    Ok(Value::Float((value * factor).round() / factor))
+                }
+                Value::Double(value) => {
+                    let factor = 10f64.powi(digits);
+                    Ok(Value::Double((value * factor).round() / factor))
+                }
+                Value::Decimal(value) => value
+                    .round(digits)
+                    .map(Value::Decimal)
+                    .ok_or_else(|| "Numeric overflow.".to_string()),
+                other => Err(format!(
+                    "Function {}() expects a number, got '{}'.",
+                    name, other
+                )),
+            }
+        }
+        "NOW" => {
+            expect_args(name, &args, 0, 0)?;
+            // Timestamps keep microseconds, like the values they're compared with
+            let now = Utc::now();
+            Ok(Value::TimestampTz(
+                now.with_nanosecond(now.nanosecond() / 1000 * 1000)
+                    .unwrap_or(now),
+            ))
+        }
+        "ARRAY_LENGTH" => {
+            expect_args(name, &args, 1, 2)?;
+            let dimension = match args.get(1) {
+                Some(dimension) => expect_int(name, dimension)?,
+                None => 1,
+            };
+            if !matches!(args[0], Value::Array(_)) {
+                return Err(format!(
+                    "Function {}() expects an array, got '{}'.",
+                    name, args[0]
+                ));
+            }
+            // Inner dimensions are measured on the first element
+            let mut value = &args[0];
+            for _ in 1..dimension.max(1) {
+                value = match value {
+                    Value::Array(elements) => elements.first().unwrap_or(&Value::Null),
+                    _ => &Value::Null,
+                };
+            }
+            match value {
+                _ if dimension < 1 => Ok(Value::Null),
+                // An empty array has no dimensions, so no length either
+                Value::Array(elements) if !elements.is_empty() => {
+                    Ok(Value::Int(elements.len() as i32))
+                }
+                // Neither has a dimension the array doesn't have
+                _ => Ok(Value::Null),
+            }
+        }
+        "GEN_RANDOM_UUID" => {
+            expect_args(name, &args, 0, 0)?;
+            Ok(Value::Uuid(Uuid::new_v4()?))
+        }
+        "DATE_TRUNC" => {
+            expect_args(name, &args, 2, 2)?;
+            let field = expect_text(name, &args[0])?;
+            match &args[1] {
+                Value::TimestampTz(value) => datetime::truncate(value.naive_utc(), field)
+                    .map(|truncated| Value::TimestampTz(truncated.and_utc())),
+                value if value.as_datetime().is_some() => {
+                    datetime::truncate(value.as_datetime().unwrap(), field).map(Value::Timestamp)
+                }
+                other => Err(format!(
+                    "Function {}() expects a timestamp, got '{}'.",
+                    name, other
+                )),
+            }
+        }
+        "EXTRACT" => {
+            expect_args(name, &args, 2, 2)?;
+            let field = expect_text(name, &args[0])?;
+            let extracted = match &args[1] {
+                Value::Time(time) => datetime::extract_time(*time, field)?,
+                Value::Interval(interval) => datetime::extract_interval(*interval, field)?,
+                value if value.as_datetime().is_some() => {
+                    datetime::extract(value.as_datetime().unwrap(), field)?
+                }
+                other => {
+                    return Err(format!(
+                        "Function {}() expects a date, time or interval, got '{}'.",
+                        name, other
+                    ))
+                }
+            };
+            match extracted {
+                Field::Whole(value) => {
+                    Ok(i32::try_from(value).map_or(Value::BigInt(value), Value::Int))
+                }
+                Field::Micros(micros) => Decimal::new(micros, 6)
+                    .map(Value::Decimal)
+                    .ok_or_else(|| "Numeric overflow.".to_string()),
+            }
+        }
+        "STRFTIME" => {
+            expect_args(name, &args, 2, 2)?;
+            let format = expect_text(name, &args[0])?;
+            let items = StrftimeItems::new(format).collect::<Vec<_>>();
+            if items.contains(&Item::Error) {
+                return Err(format!("Invalid strftime format '{}'.", format));
+            }
+            // Writing fails, rather than panicking, on a field the value lacks
+            let mut out = String::new();
+            let written = match &args[1] {
+                Value::TimestampTz(value) => {
+                    write!(out, "{}", value.format_with_items(items.iter()))
+                }
+                Value::Time(time) => write!(
+                    out,
+                    "{}",
+                    NaiveDate::default()
+                        .and_time(*time)
+                        .format_with_items(items.iter())
+                ),
+                value if value.as_datetime().is_some() => write!(
+                    out,
+                    "{}",
+                    value.as_datetime().unwrap().format_with_items(items.iter())
+                ),
+                other => {
+                    return Err(format!(
+                        "Function {}() expects a date or time, got '{}'.",
+                        name, other
+                    ))
+                }
+            };
+            written
+                .map(|_| Value::Varchar(out))
+                .map_err(|_| format!("Format '{}' doesn't apply to '{}'.", format, args[1]))
+        }
+        "JSON_EXTRACT" => {
+            expect_args(name, &args, 2, usize::MAX)?;
+            let json = args[0].clone().into_json()?;
+            let mut found = Vec::new();
+            for path in &args[1..] {
+                let steps = parse_json_path(expect_text(name, path)?)?;
+                found.push(json.at_path(&steps).cloned().unwrap_or(Json::Null));
+            }
+            // Several paths give an array of what each one found
+            Ok(match found.len() {
+                1 => Value::from_json(&found[0]),
+                _ => Value::Json(Json::Array(found)),
+            })
+        }
+        "JSON_ARRAY_LENGTH" => {
+            expect_args(name, &args, 1, 2)?;
+            let json = args[0].clone().into_json()?;
+            let steps = match args.get(1) {
+                Some(path) => parse_json_path(expect_text(name, path)?)?,
+                None => Vec::new(),
+            };
+            match json.at_path(&steps) {
+                Some(Json::Array(elements)) => Ok(Value::Int(elements.len() as i32)),
+                Some(_) => Ok(Value::Int(0)),
+                None => Ok(Value::Null),
+            }
+        }
+        _ if is_aggregate(name) => Err(format!(
+            "Aggregate function {}() is only allowed in the select list.",
+            name
+        )),
+        _ => Err(format!("Unknown function '{}'.", name)),
+    }
+}
+
+/// Evaluates a table-valued function in FROM.
+///
+/// `json_each(doc[, path])` yields a row per element of an array or member
+/// of an object, with its `key` (the index or member name), its `value` as
+/// JSON and its JSON `type`. A scalar gives one row with a NULL key.
+///
+/// `unnest(array)` yields a row per element, in a column named `unnest`.
+pub fn call_table_function(name: &str, args: Vec<Value>) -> Result<ResultSet, String> {
+    match name.to_uppercase().as_str() {
+        "JSON_EACH" => json_each(name, args),
+        "UNNEST" => unnest(name, args),
+        _ => Err(format!("Unknown table function '{}'.", name)),
+    }
+}
+
+fn column(name: &str, data_type: DataType) -> Column {
+    Column {
+        name: name.to_string(),
+        data_type,
+        default: None,
+        not_null: false,
+        identity: None,
+        collation: Collation::default(),
+    }
+}
+
+fn unnest(name: &str, args: Vec<Value>) -> Result<ResultSet, String> {
+    expect_args(name, &args, 1, 1)?;
+    let elements = match args.into_iter().next().unwrap() {
+        Value::Null => Vec::new(),
+        Value::Array(elements) => elements,
+        other => {
+            return Err(format!(
+                "Function {}() expects an array, got '{}'.",
+                name, other
+            ))
+        }
+    };
+    Ok(ResultSet {
+        columns: vec![column("unnest", DataType::of_values(elements.iter()))],
+        rows: elements
+            .into_iter()
+            .map(|element| Row {
+                values: vec![element],
+            })
+            .collect(),
+    })
+}
+
+fn json_each(name: &str, args: Vec<Value>) -> Result<ResultSet, String> {
+    expect_args(name, &args, 1, 2)?;
+    // SQL NULL, like a path that isn't there, has nothing to expand
+    let json = match &args[0] {
+        Value::Null => None,
+        value => Some(value.clone().into_json()?),
+    };
+    let steps = match args.get(1) {
+        Some(path) => parse_json_path(expect_text(name, path)?)?,
+        None => Vec::new(),
+    };
+    let (key_type, entries) = match json.as_ref().and_then(|json| json.at_path(&steps)) {
+        None => (DataType::Text, Vec::new()),
+        Some(Json::Array(elements)) => (
+            DataType::Int,
+            elements
+                .iter()
+                .enumerate()
+                .map(|(index, element)| (Value::Int(index as i32), element))
+                .collect(),
+        ),
+        Some(Json::Object(members)) => (
+            DataType::Text,
+            members
+                .iter()
+                .map(|(key, value)| (Value::Varchar(key.clone()), value))
+                .collect(),
+        ),
+        Some(scalar) => (DataType::Text, vec![(Value::Null, scalar)]),
+    };
+    Ok(ResultSet {
+        columns: vec![
+            column("key", key_type),
+            column("value", DataType::Json),
+            column("type", DataType::Text),
+        ],
+        rows: entries
+            .into_iter()
+            .map(|(key, value)| Row {
+                values: vec![
+                    key,
+                    Value::Json(value.clone()),
+                    Value::Varchar(value.type_name().to_string()),
+                ],
+            })
+            .collect(),
+    })
+}
+
+/// Whether `name` is an aggregate function, computed over every row of a
+/// query rather than row by row.
+pub fn is_aggregate(name: &str) -> bool {
+    matches!(name.to_uppercase().as_str(), "SUM" | "AVG" | "ARRAY_AGG")
+}
+
+/// Computes an aggregate over its argument's value in each row. SUM and AVG
+/// skip NULLs, and with nothing else to go on the result is NULL.
+///
+/// Integers sum to a BIGINT and DECIMALs to an exact DECIMAL; AVG of either
+/// is an exact DECIMAL. Floating values give a floating result. ARRAY_AGG
+/// collects every value, NULLs included, and gives NULL for no rows.
+pub fn call_aggregate(name: &str, values: Vec<Value>) -> Result<Value, String> {
+    if name.to_uppercase() == "ARRAY_AGG" {
+        return Ok(match values.is_empty() {
+            true => Value::Null,
+            false => Value::Array(values),
+        });
+    }
+    let values = values
+        .into_iter()
+        .filter(|value| *value != Value::Null)
+        .collect::<Vec<_>>();
+    let count = values.len() as i64;
+    let mut sum = None;
+    for value in values {
+        if !value
+            .data_type()
+            .is_some_and(|data_type| data_type.is_numeric())
+        {
+            return Err(format!(
+                "Function {}() expects numbers, got '{}'.",
+                name, value
+            ));
+        }
+        // Integers add up as BIGINT so that INT columns don't overflow early
+        let value = value.as_i64().map_or(value, Value::BigInt);
+        sum = Some(match sum {
+            Some(sum) => evaluate_arithmetic(BinaryOperator::Add, sum, value)?,
+            None => value,
+        });
+    }
+    let Some(sum) = sum else {
+        return Ok(Value::Null);
+    };
+    if name.to_uppercase() == "SUM" {
+        return Ok(sum);
+    }
+    // Integer division would drop the fraction, so integers average as DECIMAL
+    let sum = match sum {
+        Value::BigInt(sum) => Value::Decimal(Decimal::from_i64(sum)),
+        sum => sum,
+    };
+    evaluate_arithmetic(BinaryOperator::Divide, sum, Value::BigInt(count))
+}
+
+/// Whether `name` reads or changes database state, so it must be bound
+/// through `call_state_function` before each evaluation.
+pub fn is_state_function(name: &str) -> bool {
+    matches!(
+        name.to_uppercase().as_str(),
+        "NEXTVAL" | "CURRVAL" | "SETVAL" | "LAST_INSERT_ID"
+    )
+}
+
+/// Evaluates the functions that read or change database state: `nextval`,
+/// `currval` and `setval` on a named sequence, and `last_insert_id()`.
+/// Returns `None` for any other function. Arguments must be constant.
+pub fn call_state_function(
+    sequences: &mut HashMap<String, Sequence>,
+    last_insert_id: Option<i32>,
+    name: &str,
+    args: &[Expression],
+) -> Result<Option<Value>, String> {
+    if !is_state_function(name) {
+        return Ok(None);
+    }
+    let upper = name.to_uppercase();
+    let args = args
+        .iter()
+        .map(|arg| arg.evaluate(&[], &[]))
+        .collect::<Result<Vec<_>, _>>()?;
+    if upper == "LAST_INSERT_ID" {
+        expect_args(name, &args, 0, 0)?;
+        return Ok(Some(last_insert_id.map_or(Value::Null, Value::Int)));
+    }
+
+    match upper.as_str() {
+        "SETVAL" => expect_args(name, &args, 2, 2)?,
+        _ => expect_args(name, &args, 1, 1)?,
+    }
+    let sequence = match &args[0] {
+        Value::Varchar(sequence) => sequences
+            .get_mut(sequence)
+            .ok_or_else(|| format!("Sequence '{}' does not exist.", sequence))?,
+        other => {
+            return Err(format!(
+                "Function {}() expects a sequence name, got '{}'.",
+                name, other
+            ))
+        }
+    };
+    let value = match upper.as_str() {
+        "NEXTVAL" => sequence.next_value()?,
+        "CURRVAL" => sequence.current_value()?,
+        _ => {
+            let value = expect_int(name, &args[1])?;
+            sequence.set_value(value);
+            value
+        }
+    };
+    Ok(Some(Value::Int(value)))
+}
@@ -0,0 +1,400 @@
+use std::fmt::Display;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+/// Deepest nesting accepted, so a hostile document can't exhaust the stack.
+const MAX_DEPTH: usize = 256;
+
+/// A parsed JSON document, the form JSON and JSONB values are stored in so
+/// that paths are looked up without reading the text again.
+///
+/// Numbers keep their written form so no digits are lost. Object members
+/// keep the order they were first written in, and a repeated key keeps its
+/// last value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// One step of a path: an object key, or an array index counting from the
+/// end when negative.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathStep {
+    Key(String),
+    Index(i64),
+}
+
+impl Json {
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = Parser {
+            text,
+            chars: text.char_indices().peekable(),
+        };
+        let json = parser.value(0)?;
+        parser.skip_whitespace();
+        match parser.chars.peek() {
+            None => Ok(json),
+            Some(&(at, _)) => Err(parser.error("unexpected text after the value", at)),
+        }
+    }
+
+    /// The member `key` of an object.
+    pub fn field(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// The element at `index` of an array; negative indexes count from the end.
+    pub fn element(&self, index: i64) -> Option<&Json> {
+        match self {
+            Json::Array(elements) => {
+                let index = match index < 0 {
+                    true => elements.len() as i64 + index,
+                    false => index,
+                };
+                usize::try_from(index)
+                    .ok()
+                    .and_then(|index| elements.get(index))
+            }
+            _ => None,
+        }
+    }
+
+    /// Follows `steps` from this value. A key step on an array is read as an
+    /// index, as `#>` paths are written as text.
+    pub fn at_path(&self, steps: &[PathStep]) -> Option<&Json> {
+        steps
+            .iter()
+            .try_fold(self, |json, step| match (step, json) {
+                (PathStep::Key(key), Json::Array(_)) => json.element(key.parse().ok()?),
+                (PathStep::Key(key), _) => json.field(key),
+                (PathStep::Index(index), _) => json.element(*index),
+            })
+    }
+
+    /// The kind of value, as `json_each` reports it.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Json::Null => "null",
+            Json::Bool(_) => "boolean",
+            Json::Number(_) => "number",
+            Json::String(_) => "string",
+            Json::Array(_) => "array",
+            Json::Object(_) => "object",
+        }
+    }
+
+    /// The text a string holds, or the JSON text of any other value.
+    pub fn to_text(&self) -> String {
+        match self {
+            Json::String(text) => text.clone(),
+            json => json.to_string(),
+        }
+    }
+}
+
+/// Reads a SQLite-style path: `$` followed by `.key`, `."quoted key"` or
+/// `[index]` steps, where `[#-1]` counts from the end.
+pub fn parse_json_path(path: &str) -> Result<Vec<PathStep>, String> {
+    let invalid = || format!("Invalid JSON path '{}'.", path);
+    let mut rest = path.strip_prefix('$').ok_or_else(invalid)?;
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        if let Some(quoted) = rest.strip_prefix(".\"") {
+            let end = quoted.find('"').ok_or_else(invalid)?;
+            steps.push(PathStep::Key(quoted[..end].to_string()));
+            rest = &quoted[end + 1..];
+        } else if let Some(key) = rest.strip_prefix('.') {
+            let end = key.find(['.', '[']).unwrap_or(key.len());
+            if end == 0 {
+                return Err(invalid());
+            }
+            steps.push(PathStep::Key(key[..end].to_string()));
+            rest = &key[end..];
+        } else if let Some(index) = rest.strip_prefix('[') {
+            let end = index.find(']').ok_or_else(invalid)?;
+            let number = index[..end].strip_prefix('#').unwrap_or(&index[..end]);
+            let number = number.parse::<i64>().map_err(|_| invalid())?;
+            if index.starts_with('#') != (number < 0) {
+                return Err(invalid());
+            }
+            steps.push(PathStep::Index(number));
+            rest = &index[end + 1..];
+        } else {
+            return Err(invalid());
+        }
+    }
+    Ok(steps)
+}
+
+/// Reads a `#>` path written as a text array, `{a,b,0}`.
+pub fn parse_text_path(path: &str) -> Result<Vec<PathStep>, String> {
+    let inner = path
+        .trim()
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+        .ok_or_else(|| format!("Expected a path such as '{{a,b}}', got '{}'.", path))?;
+    Ok(match inner.trim().is_empty() {
+        true => Vec::new(),
+        false => inner
+            .split(',')
+            .map(|step| PathStep::Key(step.trim().trim_matches('"').to_string()))
+            .collect(),
+    })
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str, at: usize) -> String {
+        format!("Invalid JSON: {} at position {}.", message, at)
+    }
+
+    fn end(&self) -> String {
+        self.error("unexpected end of input", self.text.len())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.chars.peek(), Some((_, ' ' | '\t' | '\n' | '\r'))) {
+            self.chars.next();
+        }
+    }
+
+    fn expect_word(&mut self, word: &str, at: usize) -> Result<(), String> {
+        if self.text[at..].starts_with(word) {
+            self.chars.nth(word.len() - 1);
+            Ok(())
+        } else {
+            Err(self.error("unknown literal", at))
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Json, String> {
+        if depth > MAX_DEPTH {
+            return Err(format!(
+                "Invalid JSON: nested deeper than {} levels.",
+                MAX_DEPTH
+            ));
+        }
+        self.skip_whitespace();
+        let Some(&(at, ch)) = self.chars.peek() else {
+            return Err(self.end());
+        };
+        match ch {
+            'n' => self.expect_word("null", at).map(|_| Json::Null),
+            't' => self.expect_word("true", at).map(|_| Json::Bool(true)),
+            'f' => self.expect_word("false", at).map(|_| Json::Bool(false)),
+            '"' => self.string().map(Json::String),
+            '[' => self.array(depth),
+            '{' => self.object(depth),
+            '-' | '0'..='9' => self.number(at),
+            _ => Err(self.error(&format!("unexpected character '{}'", ch), at)),
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Json, String> {
+        self.chars.next(); // Consume "["
+        let mut elements = Vec::new();
+        self.skip_whitespace();
+        if matches!(self.chars.peek(), Some((_, ']'))) {
+            self.chars.next();
+            return Ok(Json::Array(elements));
+        }
+        loop {
+            elements.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some((_, ',')) => continue,
+                Some((_, ']')) => return Ok(Json::Array(elements)),
+                Some((at, _)) => return Err(self.error("expected ',' or ']'", at)),
+                None => return Err(self.end()),
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Json, String> {
+        self.chars.next(); // Consume "{"
+        let mut members: Vec<(String, Json)> = Vec::new();
+        self.skip_whitespace();
+        if matches!(self.chars.peek(), Some((_, '}'))) {
+            self.chars.next();
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = match self.chars.peek() {
+                Some((_, '"')) => self.string()?,
+                Some(&(at, _)) => return Err(self.error("expected a quoted key", at)),
+                None => return Err(self.end()),
+            };
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some((_, ':')) => {}
+                Some((at, _)) => return Err(self.error("expected ':'", at)),
+                None => return Err(self.end()),
+            }
+            let value = self.value(depth + 1)?;
+            match members.iter_mut().find(|(name, _)| *name == key) {
+                Some(member) => member.1 = value,
+                None => members.push((key, value)),
+            }
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some((_, ',')) => continue,
+                Some((_, '}')) => return Ok(Json::Object(members)),
+                Some((at, _)) => return Err(self.error("expected ',' or '}'", at)),
+                None => return Err(self.end()),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.chars.next(); // Consume the opening quote
+        let mut text = String::new();
+        loop {
+            let (at, ch) = self.chars.next().ok_or_else(|| self.end())?;
+            match ch {
+                '"' => return Ok(text),
+                '\\' => {
+                    let (at, escape) = self.chars.next().ok_or_else(|| self.end())?;
+                    match escape {
+                        '"' | '\\' | '/' => text.push(escape),
+                        'b' => text.push('\u{8}'),
+                        'f' => text.push('\u{c}'),
+                        'n' => text.push('\n'),
+                        'r' => text.push('\r'),
+                        't' => text.push('\t'),
+                        'u' => text.push(self.unicode_escape(at)?),
+                        _ => return Err(self.error("invalid escape", at)),
+                    }
+                }
+                ch if (ch as u32) < 0x20 => {
+                    return Err(self.error("control character in string", at))
+                }
+                ch => text.push(ch),
+            }
+        }
+    }
+
+    /// Reads the digits after `\u`, joining a surrogate pair into one character.
+    fn unicode_escape(&mut self, at: usize) -> Result<char, String> {
+        let first = self.hex4(at)?;
+        let code = match first {
+            0xD800..=0xDBFF => {
+                let low = match (self.chars.next(), self.chars.next()) {
+                    (Some((_, '\\')), Some((_, 'u'))) => self.hex4(at)?,
+                    _ => return Err(self.error("unpaired surrogate", at)),
+                };
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(self.error("unpaired surrogate", at));
+                }
+                0x10000 + ((first - 0xD800) << 10) + (low - 0xDC00)
+            }
+            code => code,
+        };
+        char::from_u32(code).ok_or_else(|| self.error("unpaired surrogate", at))
+    }
+
+    fn hex4(&mut self, at: usize) -> Result<u32, String> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self.chars.next().and_then(|(_, ch)| ch.to_digit(16));
+            code = code * 16 + digit.ok_or_else(|| self.error("invalid \\u escape", at))?;
+        }
+        Ok(code)
+    }
+
+    /// Checks the number against JSON's grammar and keeps its text.
+    fn number(&mut self, start: usize) -> Result<Json, String> {
+        let mut end = start;
+        while let Some(&(at, ch)) = self.chars.peek() {
+            if !matches!(ch, '-' | '+' | '.' | 'e' | 'E' | '0'..='9') {
+                break;
+            }
+            end = at + 1;
+            self.chars.next();
+        }
+        let text = &self.text[start..end];
+        let digits = text.strip_prefix('-').unwrap_or(text);
+        let (mantissa, exponent) = match digits.find(['e', 'E']) {
+            Some(at) => (&digits[..at], Some(&digits[at + 1..])),
+            None => (digits, None),
+        };
+        let (whole, fraction) = match mantissa.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (mantissa, None),
+        };
+        let all_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+        let valid = all_digits(whole)
+            && (whole == "0" || !whole.starts_with('0'))
+            && fraction.is_none_or(all_digits)
+            && exponent
+                .map(|exponent| exponent.strip_prefix(['+', '-']).unwrap_or(exponent))
+                .is_none_or(all_digits);
+        match valid {
+            true => Ok(Json::Number(text.to_string())),
+            false => Err(self.error(&format!("malformed number '{}'", text), start)),
+        }
+    }
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Number(text) => write!(f, "{}", text),
+            Json::String(text) => write_string(f, text),
+            Json::Array(elements) => {
+                write!(f, "[")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(members) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ": {}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut std::fmt::Formatter<'_>, text: &str) -> std::fmt::Result {
+    write!(f, "\"")?;
+    for ch in text.chars() {
+        match ch {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            ch if (ch as u32) < 0x20 => write!(f, "\\u{:04x}", ch as u32)?,
+            ch => write!(f, "{}", ch)?,
+        }
+    }
+    write!(f, "\"")
+}
@@ -1,6 +1,16 @@
+pub mod array;
 pub mod command;
+pub mod csv;
 pub mod data;
 pub mod database;
+pub mod datetime;
+pub mod decimal;
+pub mod expression;
+mod function;
+pub mod json;
 mod page;
+pub mod query;
+pub mod sequence;
 pub mod statement;
 pub mod table;
+pub mod uuid;
@@ -2,7 +2,7 @@ use super::data::Row;
 
 const MAX_ROWS_PER_PAGE: usize = 128;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Page {
     pub rows: [Option<Row>; MAX_ROWS_PER_PAGE],
     free_space: usize,
@@ -52,6 +52,7 @@ impl Page {
 
         Ok(())
     }
+    */
 
     pub fn update_row(&mut self, index: usize, row: Row) -> Result<(), String> {
         if self.rows.get(index).is_none() {
@@ -62,5 +63,4 @@ impl Page {
 
         Ok(())
     }
-    */
 }
@@ -0,0 +1,294 @@
+use std::cmp::Ordering;
+
+use super::data::{Collation, Column, DataType, Row, Value};
+use super::expression::Expression;
+use super::function::{call_aggregate, is_aggregate};
+use super::statement::{Select, SelectItem};
+
+/// Rows produced by a query along with the schema of its columns.
+#[derive(Debug)]
+pub struct ResultSet {
+    pub columns: Vec<Column>,
+    pub rows: Vec<Row>,
+}
+
+/// Outcome of a statement that changes a table.
+#[derive(Debug)]
+pub struct WriteResult {
+    pub affected: usize,
+    /// Rows as written, projected through the RETURNING list when there is one.
+    pub returning: Option<ResultSet>,
+    /// The identity value of the last row inserted, when the table has an
+    /// identity column.
+    pub last_insert_id: Option<i32>,
+}
+
+/// Binds calls that must run once per row, such as `nextval`, returning
+/// `None` for an expression that has none.
+pub type Bind<'a> = dyn FnMut(&Expression) -> Result<Option<Expression>, String> + 'a;
+
+/// Runs a SELECT over the rows of its FROM item, or over a single empty row
+/// when there is no FROM.
+pub fn execute_select(
+    source: Option<ResultSet>,
+    select: &Select,
+    bind: &mut Bind,
+) -> Result<ResultSet, String> {
+    let ResultSet { columns, rows } = source.unwrap_or(ResultSet {
+        columns: Vec::new(),
+        rows: vec![Row { values: Vec::new() }],
+    });
+
+    let mut filtered = Vec::new();
+    for row in rows {
+        let keep = match &select.where_clause {
+            Some(condition) => match evaluate_row(condition, &columns, &row.values, bind)? {
+                Value::Boolean(keep) => keep,
+                // An unknown condition filters the row out, as in standard SQL
+                Value::Null => false,
+                other => return Err(format!("WHERE clause must be boolean, got '{}'.", other)),
+            },
+            None => true,
+        };
+        if keep {
+            filtered.push(row);
+        }
+    }
+
+    // Aggregates such as SUM(x) collapse the rows into one
+    if let Some(items) = aggregate_items(&columns, &filtered, &select.columns, bind)? {
+        let mut result = project_rows(&[], vec![Row { values: Vec::new() }], &items, bind)?;
+        // The calls are now values, so their types come from the calls
+        for (column, item) in result.columns.iter_mut().zip(&select.columns) {
+            if let SelectItem::Expression { expression, .. } = item {
+                if let Some(data_type) = expression.result_type(&columns) {
+                    column.data_type = data_type;
+                }
+            }
+        }
+        return Ok(result);
+    }
+
+    let rows = sort_rows(&columns, filtered, select, bind)?;
+    project_rows(&columns, rows, &select.columns, bind)
+}
+
+fn evaluate_row(
+    expression: &Expression,
+    columns: &[Column],
+    values: &[Value],
+    bind: &mut Bind,
+) -> Result<Value, String> {
+    match bind(expression)? {
+        Some(bound) => bound.evaluate(columns, values),
+        None => expression.evaluate(columns, values),
+    }
+}
+
+/// Replaces each aggregate call in the select list with its value over
+/// `rows`, keeping the call as the column name. Returns `None` when the list
+/// has no aggregates; without GROUP BY, columns outside them are an error.
+fn aggregate_items(
+    columns: &[Column],
+    rows: &[Row],
+    items: &[SelectItem],
+    bind: &mut Bind,
+) -> Result<Option<Vec<SelectItem>>, String> {
+    let mut found = false;
+    let mut aggregated = Vec::new();
+    for item in items {
+        let SelectItem::Expression { expression, alias } = item else {
+            aggregated.push(item.clone());
+            continue;
+        };
+        let mut replaced = expression.clone();
+        replaced.replace_calls(&mut |name, args| {
+            if !is_aggregate(name) {
+                return Ok(None);
+            }
+            found = true;
+            let [arg] = args else {
+                return Err(format!(
+                    "Function {}() expects 1 arguments, got {}.",
+                    name,
+                    args.len()
+                ));
+            };
+            let values = rows
+                .iter()
+                .map(|row| evaluate_row(arg, columns, &row.values, bind))
+                .collect::<Result<Vec<_>, _>>()?;
+            call_aggregate(name, values).map(Some)
+        })?;
+        aggregated.push(SelectItem::Expression {
+            expression: replaced,
+            alias: Some(alias.clone().unwrap_or_else(|| expression.to_string())),
+        });
+    }
+    if !found {
+        return Ok(None);
+    }
+
+    for item in &aggregated {
+        let SelectItem::Expression { expression, .. } = item else {
+            return Err("SELECT * cannot be combined with aggregate functions.".to_string());
+        };
+        if let Some(name) = expression.column_names().first() {
+            return Err(format!(
+                "Column '{}' must be used inside an aggregate function.",
+                name
+            ));
+        }
+    }
+    Ok(Some(aggregated))
+}
+
+/// Evaluates a select list over `rows`, as for SELECT and RETURNING.
+pub fn project(
+    columns: &[Column],
+    rows: Vec<Row>,
+    items: &[SelectItem],
+) -> Result<ResultSet, String> {
+    project_rows(columns, rows, items, &mut |_| Ok(None))
+}
+
+fn project_rows(
+    columns: &[Column],
+    rows: Vec<Row>,
+    items: &[SelectItem],
+    bind: &mut Bind,
+) -> Result<ResultSet, String> {
+    let mut names = Vec::new();
+    for item in items {
+        match item {
+            SelectItem::Wildcard => names.extend(columns.iter().map(|c| c.name.clone())),
+            SelectItem::Expression {
+                alias: Some(alias), ..
+            } => names.push(alias.clone()),
+            SelectItem::Expression { expression, .. } => names.push(expression.to_string()),
+        }
+    }
+
+    let mut projected = Vec::new();
+    for row in rows {
+        let mut values = Vec::new();
+        for item in items {
+            match item {
+                SelectItem::Wildcard => values.extend(row.values.iter().cloned()),
+                SelectItem::Expression { expression, .. } => {
+                    values.push(evaluate_row(expression, columns, &row.values, bind)?)
+                }
+            }
+        }
+        projected.push(Row { values });
+    }
+
+    // Columns passed through keep their type and collation; anything else
+    // takes the type of what it computes, whatever rows it happened to see
+    let mut sources = Vec::new();
+    let mut types = Vec::new();
+    for item in items {
+        match item {
+            SelectItem::Wildcard => {
+                sources.extend(columns.iter().map(Some));
+                types.extend(columns.iter().map(|column| Some(column.data_type.clone())));
+            }
+            SelectItem::Expression { expression, .. } => {
+                sources.push(match expression {
+                    Expression::Column(name) => columns.iter().find(|column| &column.name == name),
+                    _ => None,
+                });
+                types.push(expression.result_type(columns));
+            }
+        }
+    }
+
+    let result_columns = names
+        .into_iter()
+        .zip(sources)
+        .zip(types)
+        .enumerate()
+        .map(|(index, ((name, source), data_type))| Column {
+            name,
+            data_type: data_type.unwrap_or_else(|| {
+                match DataType::of_values(projected.iter().map(|row| &row.values[index])) {
+                    // Text is never sized to the rows at hand
+                    DataType::Varchar(_) => DataType::Text,
+                    data_type => data_type,
+                }
+            }),
+            default: None,
+            not_null: false,
+            identity: None,
+            collation: source.map_or_else(Collation::default, |column| column.collation),
+        })
+        .collect();
+
+    Ok(ResultSet {
+        columns: result_columns,
+        rows: projected,
+    })
+}
+
+fn sort_rows(
+    columns: &[Column],
+    rows: Vec<Row>,
+    select: &Select,
+    bind: &mut Bind,
+) -> Result<Vec<Row>, String> {
+    if select.order_by.is_empty() {
+        return Ok(rows);
+    }
+
+    let mut keyed = rows
+        .into_iter()
+        .map(|row| {
+            let keys = select
+                .order_by
+                .iter()
+                .map(|order| evaluate_row(&order.expression, columns, &row.values, bind))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((keys, row))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let collations = select
+        .order_by
+        .iter()
+        .map(|order| order.expression.collation(columns).unwrap_or_default())
+        .collect::<Vec<_>>();
+
+    // sort_by can't fail, so remember the first comparison error instead
+    let mut error = None;
+    keyed.sort_by(|(left, _), (right, _)| {
+        for (((l, r), order), collation) in left
+            .iter()
+            .zip(right)
+            .zip(&select.order_by)
+            .zip(&collations)
+        {
+            // NULL sorts after every value, so it comes last ascending and first descending
+            let ordering = match (l, r) {
+                (Value::Null, Value::Null) => Ok(Ordering::Equal),
+                (Value::Null, _) => Ok(Ordering::Greater),
+                (_, Value::Null) => Ok(Ordering::Less),
+                _ => l.compare_with(r, *collation),
+            };
+            match ordering {
+                Ok(Ordering::Equal) => continue,
+                Ok(ordering) if order.descending => return ordering.reverse(),
+                Ok(ordering) => return ordering,
+                Err(e) => {
+                    error.get_or_insert(e);
+                    return Ordering::Equal;
+                }
+            }
+        }
+        Ordering::Equal
+    });
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(keyed.into_iter().map(|(_, row)| row).collect()),
+    }
+}
@@ -0,0 +1,53 @@
+/// A counter handing out INT values, made by CREATE SEQUENCE or owned by an
+/// identity column.
+///
+/// Values taken are never given back, so a failed insert leaves a gap.
+#[derive(Debug, Clone)]
+pub struct Sequence {
+    pub name: String,
+    /// The value the next call to `next_value` returns.
+    pub next: i64,
+    pub increment: i32,
+    /// The value last returned, as `currval` reports it.
+    current: Option<i32>,
+}
+
+impl Sequence {
+    pub fn new(name: String, start: i32, increment: i32) -> Result<Self, String> {
+        if increment == 0 {
+            return Err(format!(
+                "Sequence '{}' cannot have an increment of 0.",
+                name
+            ));
+        }
+        Ok(Self {
+            name,
+            next: start as i64,
+            increment,
+            current: None,
+        })
+    }
+
+    pub fn next_value(&mut self) -> Result<i32, String> {
+        let value = i32::try_from(self.next)
+            .map_err(|_| format!("Sequence '{}' has run out of values.", self.name))?;
+        self.next += self.increment as i64;
+        self.current = Some(value);
+        Ok(value)
+    }
+
+    pub fn current_value(&self) -> Result<i32, String> {
+        self.current.ok_or_else(|| {
+            format!(
+                "Sequence '{}' has not handed out a value yet in this session.",
+                self.name
+            )
+        })
+    }
+
+    /// Makes `value` the current value; the next one follows it.
+    pub fn set_value(&mut self, value: i32) {
+        self.current = Some(value);
+        self.next = value as i64 + self.increment as i64;
+    }
+}
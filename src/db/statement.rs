@@ -1,12 +1,21 @@
 use std::fmt::Display;
+use std::sync::Arc;
 
-use super::data::Column;
+use super::data::{Collation, Column, Constraint, ConstraintKind, DataType, EnumType, ForeignKey};
+use super::expression::Expression;
+use super::sequence::Sequence;
+use crate::parser::quote_identifier;
 
 #[derive(Debug)]
 pub enum Statement {
     CreateTable(CreateTable),
+    CreateTableAs(CreateTableAs),
+    CreateSequence(CreateSequence),
+    CreateType(CreateType),
+    AlterTable(AlterTable),
     Select(Select),
     Insert(Insert),
+    Copy(Copy),
     // Add other statement types here
 }
 
@@ -14,49 +23,477 @@ pub enum Statement {
 pub struct CreateTable {
     pub table_name: String,
     pub columns: Vec<Column>,
+    pub constraints: Vec<Constraint>,
 }
 
+/// `CREATE TABLE ... AS SELECT`, whose columns come from the query's result.
 #[derive(Debug)]
-pub struct Select {
+pub struct CreateTableAs {
+    pub table_name: String,
+    pub query: Select,
+}
+
+/// `CREATE SEQUENCE`; the sequence starts out as it will be stored.
+#[derive(Debug)]
+pub struct CreateSequence {
+    pub sequence: Sequence,
+}
+
+/// `CREATE TYPE ... AS ENUM`.
+#[derive(Debug)]
+pub struct CreateType {
+    pub enum_type: Arc<EnumType>,
+}
+
+#[derive(Debug)]
+pub enum AlterAction {
+    AddColumn {
+        column: Column,
+        constraints: Vec<Constraint>,
+    },
+    AddConstraint(Constraint),
+    DropColumn(String),
+    RenameColumn {
+        from: String,
+        to: String,
+    },
+    RenameTable(String),
+    AlterColumnType {
+        column: String,
+        data_type: DataType,
+    },
+}
+
+#[derive(Debug)]
+pub struct AlterTable {
     pub table_name: String,
-    pub columns: Vec<String>,
+    pub action: AlterAction,
+}
+
+#[derive(Debug, Clone)]
+pub enum SelectItem {
+    Wildcard,
+    Expression {
+        expression: Expression,
+        alias: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct OrderBy {
+    pub expression: Expression,
+    pub descending: bool,
+}
+
+/// What a SELECT reads its rows from.
+#[derive(Debug, Clone)]
+pub enum FromItem {
+    Table(String),
+    /// A table-valued function such as `json_each(doc)`; its arguments must
+    /// be constant.
+    Function {
+        name: String,
+        args: Vec<Expression>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Select {
+    /// `None` for a SELECT without FROM, which yields a single row.
+    pub from: Option<FromItem>,
+    pub columns: Vec<SelectItem>,
+    pub where_clause: Option<Expression>,
+    pub order_by: Vec<OrderBy>,
     // Add other SELECT statement components here
 }
 
+#[derive(Debug)]
+pub enum InsertValue {
+    Expression(Expression),
+    /// The `DEFAULT` keyword in a value position.
+    Default,
+}
+
+#[derive(Debug)]
+pub enum InsertSource {
+    Values(Vec<Vec<InsertValue>>),
+    Select(Select),
+}
+
+#[derive(Debug, Clone)]
+pub struct Assignment {
+    pub column: String,
+    pub value: Expression,
+}
+
+#[derive(Debug, Clone)]
+pub enum ConflictAction {
+    Nothing,
+    /// Assignments may read the proposed row through `excluded.column`.
+    Update(Vec<Assignment>),
+}
+
+/// `ON CONFLICT (columns) DO ...`: a row whose `target` values equal an
+/// existing row's is skipped or turned into an update of that row.
+#[derive(Debug, Clone)]
+pub struct OnConflict {
+    pub target: Vec<String>,
+    pub action: ConflictAction,
+}
+
 #[derive(Debug)]
 pub struct Insert {
     pub table_name: String,
-    pub values: Vec<String>,
+    /// Target columns in the order values are given; `None` means all columns.
+    pub columns: Option<Vec<String>>,
+    /// `OVERRIDING SYSTEM VALUE`, which lets values be given for GENERATED
+    /// ALWAYS identity columns.
+    pub overriding: bool,
+    pub source: InsertSource,
+    pub on_conflict: Option<OnConflict>,
+    pub returning: Option<Vec<SelectItem>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CopyDirection {
+    From,
+    To,
+}
+
+#[derive(Debug)]
+pub struct Copy {
+    pub table_name: String,
+    pub direction: CopyDirection,
+    pub file_name: String,
+    pub header: bool,
 }
 
 impl Display for CreateTable {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        writeln!(f, "CREATE TABLE {} (", self.table_name)?;
-        for column in &self.columns {
-            writeln!(f, "  {} {},", column.name, column.data_type)?;
+        writeln!(f, "CREATE TABLE {} (", quote_identifier(&self.table_name))?;
+        let definitions = self
+            .columns
+            .iter()
+            .map(|column| column.to_string())
+            .chain(self.constraints.iter().map(|c| c.to_string()))
+            .collect::<Vec<_>>();
+        for (index, definition) in definitions.iter().enumerate() {
+            let separator = if index + 1 < definitions.len() {
+                ","
+            } else {
+                ""
+            };
+            writeln!(f, "  {}{}", definition, separator)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl Display for Column {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", quote_identifier(&self.name), self.data_type)?;
+        if self.collation != Collation::Binary {
+            write!(f, " COLLATE {}", self.collation)?;
+        }
+        if let Some(default) = &self.default {
+            write!(f, " DEFAULT {}", default)?;
+        }
+        if let Some(identity) = &self.identity {
+            let generated = match identity.always {
+                true => "ALWAYS",
+                false => "BY DEFAULT",
+            };
+            write!(f, " GENERATED {} AS IDENTITY", generated)?;
+            let sequence = &identity.sequence;
+            let mut options = Vec::new();
+            if sequence.next != 1 {
+                options.push(format!("START WITH {}", sequence.next));
+            }
+            if sequence.increment != 1 {
+                options.push(format!("INCREMENT BY {}", sequence.increment));
+            }
+            if !options.is_empty() {
+                write!(f, " ({})", options.join(" "))?;
+            }
+        }
+        if self.not_null {
+            write!(f, " NOT NULL")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CONSTRAINT {} ", quote_identifier(&self.name))?;
+        match &self.kind {
+            ConstraintKind::Unique(columns) => {
+                write!(f, "UNIQUE (")?;
+                write_identifiers(f, columns)?;
+                write!(f, ")")
+            }
+            ConstraintKind::Check(expression) => write!(f, "CHECK ({})", expression),
+            ConstraintKind::ForeignKey(foreign_key) => {
+                write!(f, "FOREIGN KEY (")?;
+                write_identifiers(f, &foreign_key.columns)?;
+                write!(f, ") {}", foreign_key)
+            }
+        }
+    }
+}
+
+/// The `REFERENCES` clause shared by column and table constraints.
+impl Display for ForeignKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "REFERENCES {} (", quote_identifier(&self.table))?;
+        write_identifiers(f, &self.referenced)?;
+        write!(f, ")")
+    }
+}
+
+impl Display for CreateSequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "CREATE SEQUENCE {} START WITH {} INCREMENT BY {}",
+            quote_identifier(&self.sequence.name),
+            self.sequence.next,
+            self.sequence.increment
+        )
+    }
+}
+
+impl Display for CreateType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "CREATE TYPE {} AS ENUM (",
+            quote_identifier(&self.enum_type.name)
+        )?;
+        for (index, label) in self.enum_type.labels.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "'{}'", label.replace('\'', "''"))?;
         }
         write!(f, ")")
     }
 }
 
+impl Display for CreateTableAs {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "CREATE TABLE {} AS {}",
+            quote_identifier(&self.table_name),
+            self.query
+        )
+    }
+}
+
+impl Display for AlterTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ALTER TABLE {} ", quote_identifier(&self.table_name))?;
+        match &self.action {
+            AlterAction::AddColumn {
+                column,
+                constraints,
+            } => {
+                write!(f, "ADD COLUMN {}", column)?;
+                // Column constraints apply to the new column, so UNIQUE takes no list
+                for constraint in constraints {
+                    write!(f, " CONSTRAINT {} ", quote_identifier(&constraint.name))?;
+                    match &constraint.kind {
+                        ConstraintKind::Unique(_) => write!(f, "UNIQUE")?,
+                        ConstraintKind::Check(expression) => write!(f, "CHECK ({})", expression)?,
+                        ConstraintKind::ForeignKey(foreign_key) => write!(f, "{}", foreign_key)?,
+                    }
+                }
+                Ok(())
+            }
+            AlterAction::AddConstraint(constraint) => write!(f, "ADD {}", constraint),
+            AlterAction::DropColumn(column) => {
+                write!(f, "DROP COLUMN {}", quote_identifier(column))
+            }
+            AlterAction::RenameColumn { from, to } => write!(
+                f,
+                "RENAME COLUMN {} TO {}",
+                quote_identifier(from),
+                quote_identifier(to)
+            ),
+            AlterAction::RenameTable(name) => write!(f, "RENAME TO {}", quote_identifier(name)),
+            AlterAction::AlterColumnType { column, data_type } => write!(
+                f,
+                "ALTER COLUMN {} TYPE {}",
+                quote_identifier(column),
+                data_type
+            ),
+        }
+    }
+}
+
+fn write_identifiers(f: &mut std::fmt::Formatter<'_>, names: &[String]) -> std::fmt::Result {
+    let names = names
+        .iter()
+        .map(|name| quote_identifier(name))
+        .collect::<Vec<_>>();
+    write_list(f, &names)
+}
+
+fn write_list<T: Display>(f: &mut std::fmt::Formatter<'_>, items: &[T]) -> std::fmt::Result {
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+impl Display for SelectItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SelectItem::Wildcard => write!(f, "*"),
+            SelectItem::Expression {
+                expression,
+                alias: Some(alias),
+            } => write!(f, "{} AS {}", expression, quote_identifier(alias)),
+            SelectItem::Expression { expression, .. } => write!(f, "{}", expression),
+        }
+    }
+}
+
+impl Display for OrderBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.expression)?;
+        if self.descending {
+            write!(f, " DESC")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for FromItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FromItem::Table(name) => write!(f, "{}", quote_identifier(name)),
+            FromItem::Function { name, args } => {
+                write!(f, "{}(", name)?;
+                write_list(f, args)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
 impl Display for Select {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "SELECT ")?;
-        for column in &self.columns {
-            write!(f, "{}, ", column)?;
+        write_list(f, &self.columns)?;
+        if let Some(from) = &self.from {
+            write!(f, " FROM {}", from)?;
+        }
+        if let Some(where_clause) = &self.where_clause {
+            write!(f, " WHERE {}", where_clause)?;
+        }
+        if !self.order_by.is_empty() {
+            write!(f, " ORDER BY ")?;
+            write_list(f, &self.order_by)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for InsertValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InsertValue::Expression(expression) => write!(f, "{}", expression),
+            InsertValue::Default => write!(f, "DEFAULT"),
         }
-        write!(f, "FROM {}", self.table_name)
     }
 }
 
 impl Display for Insert {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "INSERT INTO {}", quote_identifier(&self.table_name))?;
+        if let Some(columns) = &self.columns {
+            let columns = columns
+                .iter()
+                .map(|column| quote_identifier(column))
+                .collect::<Vec<_>>();
+            write!(f, " (")?;
+            write_list(f, &columns)?;
+            write!(f, ")")?;
+        }
+        if self.overriding {
+            write!(f, " OVERRIDING SYSTEM VALUE")?;
+        }
+        match &self.source {
+            InsertSource::Values(rows) => {
+                write!(f, " VALUES ")?;
+                for (index, row) in rows.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "(")?;
+                    write_list(f, row)?;
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+            InsertSource::Select(select) => write!(f, " {}", select),
+        }?;
+        if let Some(on_conflict) = &self.on_conflict {
+            write!(f, " {}", on_conflict)?;
+        }
+        if let Some(returning) = &self.returning {
+            write!(f, " RETURNING ")?;
+            write_list(f, returning)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Assignment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {}", quote_identifier(&self.column), self.value)
+    }
+}
+
+impl Display for OnConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let target = self
+            .target
+            .iter()
+            .map(|column| quote_identifier(column))
+            .collect::<Vec<_>>();
+        write!(f, "ON CONFLICT (")?;
+        write_list(f, &target)?;
+        match &self.action {
+            ConflictAction::Nothing => write!(f, ") DO NOTHING"),
+            ConflictAction::Update(assignments) => {
+                write!(f, ") DO UPDATE SET ")?;
+                write_list(f, assignments)
+            }
+        }
+    }
+}
+
+impl Display for Copy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let direction = match self.direction {
+            CopyDirection::From => "FROM",
+            CopyDirection::To => "TO",
+        };
         write!(
             f,
-            "INSERT INTO {} VALUES ({})",
-            self.table_name,
-            self.values.join(", ")
-        )
+            "COPY {} {} '{}' WITH (FORMAT csv",
+            self.table_name, direction, self.file_name
+        )?;
+        if self.header {
+            write!(f, ", HEADER")?;
+        }
+        write!(f, ")")
     }
 }
 
@@ -64,8 +501,13 @@ impl Display for Statement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Statement::CreateTable(create_table) => write!(f, "{}", create_table),
+            Statement::CreateTableAs(create_table_as) => write!(f, "{}", create_table_as),
+            Statement::CreateSequence(create_sequence) => write!(f, "{}", create_sequence),
+            Statement::CreateType(create_type) => write!(f, "{}", create_type),
+            Statement::AlterTable(alter_table) => write!(f, "{}", alter_table),
             Statement::Select(select) => write!(f, "{}", select),
             Statement::Insert(insert) => write!(f, "{}", insert),
+            Statement::Copy(copy) => write!(f, "{}", copy),
         }
     }
 }
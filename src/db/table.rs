@@ -1,12 +1,26 @@
-use super::data::{Column, Row, Value};
+use super::data::{Collation, Column, Constraint, ConstraintKind, DataType, Row, Value};
+use super::expression::Expression;
 use super::page::Page;
+use super::statement::{Assignment, ConflictAction, OnConflict};
+use std::cmp::Ordering;
 use std::fmt::Display;
 
-#[derive(Debug)]
+/// Page index and slot of a stored row.
+pub type RowLocation = (usize, usize);
+
+/// A change planned by an insert, applied only once every row has been checked.
+pub enum RowWrite {
+    Insert(Vec<Value>),
+    Update(RowLocation, Vec<Value>),
+}
+
+#[derive(Debug, Clone)]
 pub struct Table {
     pub name: String,
     pub columns: Vec<Column>,
+    pub constraints: Vec<Constraint>,
     pages: Vec<Page>,
+    last_insert_id: Option<i32>,
 }
 
 impl Display for Table {
@@ -22,21 +36,451 @@ impl Display for Table {
 }
 
 impl Table {
-    pub fn new(name: String, columns: Vec<Column>) -> Self {
-        Self {
+    pub fn new(
+        name: String,
+        columns: Vec<Column>,
+        constraints: Vec<Constraint>,
+    ) -> Result<Self, String> {
+        check_schema(&columns, &constraints)?;
+        Ok(Self {
             name,
             columns,
+            constraints,
             pages: Vec::new(),
+            last_insert_id: None,
+        })
+    }
+
+    /// Inserts one row of text fields, as read from CSV, in column order.
+    pub fn insert(&mut self, values: &[String]) -> Result<(), String> {
+        let row = self.text_row(values);
+        self.insert_values(None, vec![row], None).map(|_| ())
+    }
+
+    /// Turns text fields, as read from CSV, into insert values in column order.
+    pub fn text_row(&self, values: &[String]) -> Vec<Option<Value>> {
+        // Text input has no NULL literal; an empty field stands in for it outside
+        // text columns, and takes the next value in an identity column
+        values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| match self.columns.get(index) {
+                Some(column) if column.identity.is_some() && value.is_empty() => None,
+                Some(Column {
+                    data_type: DataType::Varchar(_) | DataType::Text | DataType::Char(_),
+                    ..
+                })
+                | None => Some(Value::Varchar(value.clone())),
+                _ if value.is_empty() => Some(Value::Null),
+                _ => Some(Value::Varchar(value.clone())),
+            })
+            .collect()
+    }
+
+    /// Inserts rows of values for `columns`, or for every column when `None`.
+    ///
+    /// A `None` value, and any column left out of the list, takes the next
+    /// identity value, the column's default or NULL. All rows are converted,
+    /// conflicts resolved and constraints checked before any is stored, so a
+    /// bad row leaves the table unchanged. Returns the rows inserted, and those
+    /// updated by `ON CONFLICT DO UPDATE`, as stored.
+    pub fn insert_values(
+        &mut self,
+        columns: Option<&[String]>,
+        rows: Vec<Vec<Option<Value>>>,
+        on_conflict: Option<&OnConflict>,
+    ) -> Result<Vec<Row>, String> {
+        let mut evaluate_default = |default: &Expression| default.evaluate(&[], &[]);
+        let writes = self.plan_insert(columns, rows, false, on_conflict, &mut evaluate_default)?;
+        self.apply_writes(writes)
+    }
+
+    /// Converts rows and resolves conflicts as `insert_values` does, checking
+    /// this table's constraints but writing nothing. Foreign keys need other
+    /// tables, so the database checks those before applying the plan.
+    ///
+    /// Defaults are computed by `evaluate_default`, so the caller can supply
+    /// functions like `nextval` that need database state. Identity values are
+    /// used up even when the plan is never applied. `overriding` allows values
+    /// for a GENERATED ALWAYS identity column.
+    pub fn plan_insert(
+        &mut self,
+        columns: Option<&[String]>,
+        rows: Vec<Vec<Option<Value>>>,
+        overriding: bool,
+        on_conflict: Option<&OnConflict>,
+        evaluate_default: &mut impl FnMut(&Expression) -> Result<Value, String>,
+    ) -> Result<Vec<RowWrite>, String> {
+        let rows = rows
+            .into_iter()
+            .map(|values| self.convert_insert_values(values, columns, overriding, evaluate_default))
+            .collect::<Result<Vec<_>, _>>()?;
+        let writes = match on_conflict {
+            Some(on_conflict) => self.resolve_conflicts(rows, on_conflict)?,
+            None => rows.into_iter().map(RowWrite::Insert).collect(),
+        };
+        self.check_writes(&writes)?;
+        Ok(writes)
+    }
+
+    /// Stores planned writes and returns the rows as stored.
+    pub fn apply_writes(&mut self, writes: Vec<RowWrite>) -> Result<Vec<Row>, String> {
+        let identity = self
+            .columns
+            .iter()
+            .position(|column| column.identity.is_some());
+        let mut written = Vec::new();
+        for write in writes {
+            match write {
+                RowWrite::Insert(values) => {
+                    if let Some(Value::Int(id)) = identity.map(|index| &values[index]) {
+                        self.last_insert_id = Some(*id);
+                    }
+                    let row = Row { values };
+                    let page: &mut Page = self.find_or_create_page();
+                    page.insert_row(row.clone())?;
+                    written.push(row);
+                }
+                RowWrite::Update((page_index, slot), values) => {
+                    let row = Row { values };
+                    self.pages[page_index].update_row(slot, row.clone())?;
+                    written.push(row);
+                }
+            }
+        }
+        Ok(written)
+    }
+
+    /// The identity value of the row most recently inserted, if the table has
+    /// an identity column.
+    pub fn last_insert_id(&self) -> Option<i32> {
+        self.last_insert_id
+    }
+
+    /// Checks NOT NULL, CHECK and UNIQUE constraints against the table as it
+    /// would be after `writes`.
+    fn check_writes(&self, writes: &[RowWrite]) -> Result<(), String> {
+        let changed = writes
+            .iter()
+            .map(|write| match write {
+                RowWrite::Insert(values) | RowWrite::Update(_, values) => values.as_slice(),
+            })
+            .collect::<Vec<_>>();
+
+        // Only UNIQUE needs to see the rest of the table
+        let mut unchanged = Vec::new();
+        if has_unique(&self.constraints) {
+            for (location, row) in self.stored_rows() {
+                let updated = writes
+                    .iter()
+                    .any(|write| matches!(write, RowWrite::Update(l, _) if *l == location));
+                if !updated {
+                    unchanged.push(row.values.as_slice());
+                }
+            }
+        }
+        check_constraints(&self.columns, &self.constraints, &changed, &unchanged)
+    }
+
+    /// The values of every row as they would be after `writes`.
+    pub fn rows_after<'a>(&'a self, writes: &'a [RowWrite]) -> Vec<&'a [Value]> {
+        let mut rows = Vec::new();
+        for (location, row) in self.stored_rows() {
+            let update = writes.iter().find_map(|write| match write {
+                RowWrite::Update(l, values) if *l == location => Some(values),
+                _ => None,
+            });
+            rows.push(update.unwrap_or(&row.values).as_slice());
+        }
+        for write in writes {
+            if let RowWrite::Insert(values) = write {
+                rows.push(values.as_slice());
+            }
+        }
+        rows
+    }
+
+    fn stored_rows(&self) -> Vec<(RowLocation, &Row)> {
+        self.pages
+            .iter()
+            .enumerate()
+            .flat_map(|(page_index, page)| {
+                page.rows.iter().enumerate().filter_map(move |(slot, row)| {
+                    row.as_ref().map(|row| ((page_index, slot), row))
+                })
+            })
+            .collect()
+    }
+
+    /// Turns rows into inserts and updates of existing rows. The target must
+    /// match a UNIQUE constraint; rows conflict when every target column
+    /// compares equal. No row may be updated twice by one statement, as the
+    /// outcome would depend on the order of the values.
+    fn resolve_conflicts(
+        &self,
+        rows: Vec<Vec<Value>>,
+        on_conflict: &OnConflict,
+    ) -> Result<Vec<RowWrite>, String> {
+        let mut target = on_conflict.target.clone();
+        target.sort();
+        let matches_constraint = self
+            .constraints
+            .iter()
+            .any(|constraint| match &constraint.kind {
+                ConstraintKind::Unique(columns) => {
+                    let mut columns = columns.clone();
+                    columns.sort();
+                    columns == target
+                }
+                ConstraintKind::Check(_) | ConstraintKind::ForeignKey(_) => false,
+            });
+        if !matches_constraint {
+            return Err(format!(
+                "There is no unique constraint on ({}) to match ON CONFLICT.",
+                on_conflict.target.join(", ")
+            ));
+        }
+
+        let key = on_conflict
+            .target
+            .iter()
+            .map(|name| self.column_index(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        let existing = self.stored_rows();
+
+        let mut writes = Vec::new();
+        for values in rows {
+            let pending = writes.iter().any(|write| match write {
+                RowWrite::Insert(row) => same_key(&self.columns, &key, row, &values),
+                RowWrite::Update(..) => false,
+            });
+            let stored = existing
+                .iter()
+                .find(|(_, row)| same_key(&self.columns, &key, &row.values, &values));
+            let assignments = match &on_conflict.action {
+                ConflictAction::Nothing if pending || stored.is_some() => continue,
+                ConflictAction::Nothing => {
+                    writes.push(RowWrite::Insert(values));
+                    continue;
+                }
+                ConflictAction::Update(assignments) => assignments,
+            };
+
+            let updated_before = |location: &RowLocation| {
+                writes
+                    .iter()
+                    .any(|write| matches!(write, RowWrite::Update(l, _) if l == location))
+            };
+            match stored {
+                _ if pending => return Err(Self::updated_twice()),
+                Some((location, _)) if updated_before(location) => {
+                    return Err(Self::updated_twice())
+                }
+                Some((location, row)) => {
+                    let updated = self.apply_assignments(&row.values, &values, assignments)?;
+                    writes.push(RowWrite::Update(*location, updated));
+                }
+                None => writes.push(RowWrite::Insert(values)),
+            }
+        }
+        Ok(writes)
+    }
+
+    fn updated_twice() -> String {
+        "ON CONFLICT DO UPDATE cannot affect a row a second time.".to_string()
+    }
+
+    /// Evaluates `SET` assignments where columns refer to the stored row, or
+    /// to the proposed row when qualified with `excluded`.
+    fn apply_assignments(
+        &self,
+        current: &[Value],
+        excluded: &[Value],
+        assignments: &[Assignment],
+    ) -> Result<Vec<Value>, String> {
+        let qualified = |qualifier: &str| {
+            self.columns
+                .iter()
+                .map(|column| Column {
+                    name: format!("{}.{}", qualifier, column.name),
+                    ..column.clone()
+                })
+                .collect::<Vec<_>>()
+        };
+        let scope = [
+            self.columns.clone(),
+            qualified(&self.name),
+            qualified("excluded"),
+        ]
+        .concat();
+        let scope_values = [current, current, excluded].concat();
+
+        let mut updated = current.to_vec();
+        for assignment in assignments {
+            let index = self.column_index(&assignment.column)?;
+            let column = &self.columns[index];
+            if matches!(&column.identity, Some(identity) if identity.always) {
+                return Err(always_identity_error(&column.name));
+            }
+            updated[index] = assignment
+                .value
+                .evaluate(&scope, &scope_values)?
+                .coerce_to(&column.data_type)
+                .map_err(|e| {
+                    format!("Error converting value for column '{}': {}", column.name, e)
+                })?;
+        }
+        Ok(updated)
+    }
+
+    /// Adds a column, filling existing rows with its default, or numbering
+    /// them if it is an identity column.
+    pub fn add_column(
+        &mut self,
+        mut column: Column,
+        constraints: Vec<Constraint>,
+    ) -> Result<(), String> {
+        let fill = match &column.default {
+            Some(default) => default.evaluate(&[], &[])?,
+            None => Value::Null,
+        }
+        .coerce_to(&column.data_type)
+        .map_err(|e| format!("Error converting value for column '{}': {}", column.name, e))?;
+        let mut fills = Vec::new();
+        for _ in self.stored_rows() {
+            fills.push(match &mut column.identity {
+                Some(identity) => Value::Int(identity.sequence.next_value()?),
+                None => fill.clone(),
+            });
+        }
+
+        let columns = [self.columns.clone(), vec![column]].concat();
+        let constraints = [self.constraints.clone(), constraints].concat();
+        let mut fills = fills.into_iter();
+        self.rewrite_rows(columns, constraints, |mut values| {
+            values.push(fills.next().unwrap());
+            Ok(values)
+        })
+    }
+
+    /// Adds a table constraint, which every existing row must satisfy.
+    pub fn add_constraint(&mut self, constraint: Constraint) -> Result<(), String> {
+        let columns = self.columns.clone();
+        let constraints = [self.constraints.clone(), vec![constraint]].concat();
+        self.rewrite_rows(columns, constraints, Ok)
+    }
+
+    pub fn drop_column(&mut self, name: &str) -> Result<(), String> {
+        let index = self.column_index(name)?;
+        if self.columns.len() == 1 {
+            return Err(format!(
+                "Cannot drop '{}', the only column of '{}'.",
+                name, self.name
+            ));
+        }
+        if let Some(constraint) = self
+            .constraints
+            .iter()
+            .find(|constraint| constraint_columns(constraint).contains(&name.to_string()))
+        {
+            return Err(format!(
+                "Cannot drop '{}', it is used by constraint '{}'.",
+                name, constraint.name
+            ));
         }
+
+        let mut columns = self.columns.clone();
+        columns.remove(index);
+        self.rewrite_rows(columns, self.constraints.clone(), |mut values| {
+            values.remove(index);
+            Ok(values)
+        })
     }
 
-    pub fn insert(&mut self, values: &Vec<String>) -> Result<(), String> {
-        let row = self.convert_insert_values(values, None)?;
-        let page: &mut Page = self.find_or_create_page();
-        page.insert_row(Row { values: row })?;
+    pub fn rename_column(&mut self, from: &str, to: &str) -> Result<(), String> {
+        let index = self.column_index(from)?;
+        if self.column_index(to).is_ok() {
+            return Err(format!("Column '{}' already exists.", to));
+        }
+        self.columns[index].name = to.to_string();
+        for constraint in &mut self.constraints {
+            match &mut constraint.kind {
+                ConstraintKind::Unique(columns) => {
+                    for column in columns.iter_mut().filter(|column| *column == from) {
+                        *column = to.to_string();
+                    }
+                }
+                ConstraintKind::Check(expression) => expression.rename_column(from, to),
+                ConstraintKind::ForeignKey(foreign_key) => {
+                    for column in foreign_key
+                        .columns
+                        .iter_mut()
+                        .filter(|column| *column == from)
+                    {
+                        *column = to.to_string();
+                    }
+                }
+            }
+        }
         Ok(())
     }
 
+    /// Changes a column's type, converting every stored value with `Value::cast_to`.
+    pub fn alter_column_type(&mut self, name: &str, data_type: DataType) -> Result<(), String> {
+        let index = self.column_index(name)?;
+        if let Some(default) = &self.columns[index].default {
+            default
+                .evaluate(&[], &[])?
+                .coerce_to(&data_type)
+                .map_err(|e| format!("Default of column '{}' doesn't fit: {}", name, e))?;
+        }
+
+        let mut columns = self.columns.clone();
+        columns[index].data_type = data_type.clone();
+        self.rewrite_rows(columns, self.constraints.clone(), |mut values| {
+            let value = std::mem::replace(&mut values[index], Value::Null);
+            values[index] = value
+                .cast_to(&data_type)
+                .map_err(|e| format!("Cannot convert column '{}': {}", name, e))?;
+            Ok(values)
+        })
+    }
+
+    /// Replaces every stored row with `convert(row)` under a new schema. All
+    /// rows are converted and checked before any is written back, so an error
+    /// leaves the table unchanged.
+    fn rewrite_rows(
+        &mut self,
+        columns: Vec<Column>,
+        constraints: Vec<Constraint>,
+        mut convert: impl FnMut(Vec<Value>) -> Result<Vec<Value>, String>,
+    ) -> Result<(), String> {
+        check_schema(&columns, &constraints)?;
+        let mut rewritten = Vec::new();
+        for (location, row) in self.stored_rows() {
+            rewritten.push((location, convert(row.values.clone())?));
+        }
+        let changed = rewritten
+            .iter()
+            .map(|(_, values)| values.as_slice())
+            .collect::<Vec<_>>();
+        check_constraints(&columns, &constraints, &changed, &[])?;
+
+        for ((page_index, slot), values) in rewritten {
+            self.pages[page_index].update_row(slot, Row { values })?;
+        }
+        self.columns = columns;
+        self.constraints = constraints;
+        Ok(())
+    }
+
+    pub fn column_index(&self, name: &str) -> Result<usize, String> {
+        self.columns
+            .iter()
+            .position(|col| col.name == name)
+            .ok_or_else(|| format!("Column '{}' not found in table schema.", name))
+    }
+
     fn find_or_create_page(&mut self) -> &mut Page {
         let mut page_index = None;
         for (index, page) in self.pages.iter_mut().enumerate() {
@@ -46,7 +490,7 @@ impl Table {
             }
         }
 
-        if !page_index.is_some() {
+        if page_index.is_none() {
             self.pages.push(Page::new());
             page_index = Some(self.pages.len() - 1);
         }
@@ -71,14 +515,18 @@ impl Table {
     }
 
     fn convert_insert_values(
-        &self,
-        values: &Vec<String>,
-        columns: Option<Vec<String>>,
+        &mut self,
+        values: Vec<Option<Value>>,
+        columns: Option<&[String]>,
+        overriding: bool,
+        evaluate_default: &mut impl FnMut(&Expression) -> Result<Value, String>,
     ) -> Result<Vec<Value>, String> {
-        let columns_to_use = match columns {
-            Some(cols) => cols,
-            None => self.columns.iter().map(|col| col.name.clone()).collect(),
-        };
+        let all_columns = self
+            .columns
+            .iter()
+            .map(|col| col.name.clone())
+            .collect::<Vec<_>>();
+        let columns_to_use = columns.unwrap_or(&all_columns);
 
         if values.len() != columns_to_use.len() {
             return Err(format!(
@@ -88,15 +536,30 @@ impl Table {
             ));
         }
 
-        let mut transformed_values = Vec::new();
+        let mut given = vec![None; self.columns.len()];
+        for (value, column_name) in values.into_iter().zip(columns_to_use) {
+            let index = self.column_index(column_name)?;
+            if given[index].is_some() {
+                return Err(format!(
+                    "Column '{}' specified more than once.",
+                    column_name
+                ));
+            }
+            given[index] = Some(value);
+        }
 
-        for (value, column_name) in values.iter().zip(&columns_to_use) {
-            let column = self
-                .columns
-                .iter()
-                .find(|col| &col.name == column_name)
-                .ok_or_else(|| format!("Column '{}' not found in table schema.", column_name))?;
-            let transformed_value = Value::from_str(&column.data_type, value).map_err(|e| {
+        let mut transformed_values = Vec::new();
+        for (value, column) in given.into_iter().zip(&mut self.columns) {
+            let value = match (value.flatten(), &mut column.identity, &column.default) {
+                (Some(_), Some(identity), _) if identity.always && !overriding => {
+                    return Err(always_identity_error(&column.name))
+                }
+                (Some(value), _, _) => value,
+                (None, Some(identity), _) => Value::Int(identity.sequence.next_value()?),
+                (None, None, Some(default)) => evaluate_default(default)?,
+                (None, None, None) => Value::Null,
+            };
+            let transformed_value = value.coerce_to(&column.data_type).map_err(|e| {
                 format!("Error converting value for column '{}': {}", column.name, e)
             })?;
             transformed_values.push(transformed_value);
@@ -105,3 +568,162 @@ impl Table {
         Ok(transformed_values)
     }
 }
+
+fn always_identity_error(column: &str) -> String {
+    format!(
+        "Cannot set column '{}', it is GENERATED ALWAYS AS IDENTITY.",
+        column
+    )
+}
+
+/// Rows match on `key` when every key column compares equal under its
+/// collation; NULL matches nothing.
+fn same_key(columns: &[Column], key: &[usize], a: &[Value], b: &[Value]) -> bool {
+    key.iter().all(|&index| {
+        a[index] != Value::Null
+            && b[index] != Value::Null
+            && a[index].compare_with(&b[index], columns[index].collation) == Ok(Ordering::Equal)
+    })
+}
+
+fn has_unique(constraints: &[Constraint]) -> bool {
+    constraints
+        .iter()
+        .any(|constraint| matches!(constraint.kind, ConstraintKind::Unique(_)))
+}
+
+fn constraint_columns(constraint: &Constraint) -> Vec<String> {
+    match &constraint.kind {
+        ConstraintKind::Unique(columns) => columns.clone(),
+        ConstraintKind::Check(expression) => expression.column_names(),
+        ConstraintKind::ForeignKey(foreign_key) => foreign_key.columns.clone(),
+    }
+}
+
+/// Rejects duplicate column or constraint names, constraints on unknown
+/// columns, identity columns that aren't plain INT columns and collations on
+/// columns that don't hold text.
+fn check_schema(columns: &[Column], constraints: &[Constraint]) -> Result<(), String> {
+    for (index, column) in columns.iter().enumerate() {
+        if columns[..index]
+            .iter()
+            .any(|other| other.name == column.name)
+        {
+            return Err(format!("Column '{}' already exists.", column.name));
+        }
+        if column.identity.is_some() {
+            if column.data_type != DataType::Int {
+                return Err(format!(
+                    "Identity column '{}' must be INT, not {}.",
+                    column.name, column.data_type
+                ));
+            }
+            if column.default.is_some() {
+                return Err(format!(
+                    "Identity column '{}' cannot also have a DEFAULT.",
+                    column.name
+                ));
+            }
+        }
+    }
+    for column in columns {
+        let element_type = match &column.data_type {
+            DataType::Array(element) => element,
+            data_type => data_type,
+        };
+        if column.collation != Collation::Binary
+            && !matches!(
+                element_type,
+                DataType::Char(_) | DataType::Varchar(_) | DataType::Text
+            )
+        {
+            return Err(format!(
+                "Collation {} cannot apply to column '{}' of type {}.",
+                column.collation, column.name, column.data_type
+            ));
+        }
+    }
+    let identities = columns.iter().filter(|column| column.identity.is_some());
+    if identities.count() > 1 {
+        return Err("A table can have only one identity column.".to_string());
+    }
+    for (index, constraint) in constraints.iter().enumerate() {
+        if constraints[..index]
+            .iter()
+            .any(|other| other.name == constraint.name)
+        {
+            return Err(format!("Constraint '{}' already exists.", constraint.name));
+        }
+        for name in constraint_columns(constraint) {
+            if !columns.iter().any(|column| column.name == name) {
+                return Err(format!(
+                    "Constraint '{}' refers to unknown column '{}'.",
+                    constraint.name, name
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks every constraint for the `changed` rows. `unchanged` holds the rest
+/// of the table, which UNIQUE compares against.
+fn check_constraints(
+    columns: &[Column],
+    constraints: &[Constraint],
+    changed: &[&[Value]],
+    unchanged: &[&[Value]],
+) -> Result<(), String> {
+    for (index, row) in changed.iter().enumerate() {
+        for (column, value) in columns.iter().zip(row.iter()) {
+            if column.not_null && *value == Value::Null {
+                return Err(format!(
+                    "Null value in column '{}' violates NOT NULL constraint.",
+                    column.name
+                ));
+            }
+        }
+
+        for constraint in constraints {
+            match &constraint.kind {
+                ConstraintKind::Check(expression) => match expression.evaluate(columns, row)? {
+                    Value::Boolean(true) | Value::Null => {}
+                    Value::Boolean(false) => {
+                        return Err(format!(
+                            "Row violates check constraint '{}'.",
+                            constraint.name
+                        ))
+                    }
+                    other => {
+                        return Err(format!(
+                            "Check constraint '{}' must be boolean, got '{}'.",
+                            constraint.name, other
+                        ))
+                    }
+                },
+                ConstraintKind::Unique(names) => {
+                    let key = names
+                        .iter()
+                        .map(|name| columns.iter().position(|c| &c.name == name).unwrap())
+                        .collect::<Vec<_>>();
+                    let duplicate = changed[..index]
+                        .iter()
+                        .chain(unchanged)
+                        .any(|other| same_key(columns, &key, row, other));
+                    if duplicate {
+                        let values = key.iter().map(|&k| row[k].to_string()).collect::<Vec<_>>();
+                        return Err(format!(
+                            "Duplicate key ({}) = ({}) violates unique constraint '{}'.",
+                            names.join(", "),
+                            values.join(", "),
+                            constraint.name
+                        ));
+                    }
+                }
+                // Checked by the database, which can see the referenced table
+                ConstraintKind::ForeignKey(_) => {}
+            }
+        }
+    }
+    Ok(())
+}
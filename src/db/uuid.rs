@@ -0,0 +1,59 @@
+use std::fmt::Display;
+
+/// A 128-bit identifier, kept as its 16 bytes and written in the canonical
+/// `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx` form. Ordering is by bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Uuid([u8; 16]);
+
+impl Uuid {
+    /// Reads 32 hex digits in either case, hyphenated as in the canonical
+    /// form or not at all, optionally wrapped in braces.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = match text.strip_prefix('{') {
+            Some(inner) => inner.strip_suffix('}')?,
+            None => text,
+        };
+        let digits = match text.len() {
+            32 => text.to_string(),
+            36 if [8, 13, 18, 23]
+                .iter()
+                .all(|&at| text.as_bytes()[at] == b'-') =>
+            {
+                text.replace('-', "")
+            }
+            _ => return None,
+        };
+        // from_str_radix would also take a sign
+        if digits.len() != 32 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let mut bytes = [0; 16];
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(digits.get(index * 2..index * 2 + 2)?, 16).ok()?;
+        }
+        Some(Self(bytes))
+    }
+
+    /// A random (version 4) UUID, drawn from the operating system's secure
+    /// random source.
+    pub fn new_v4() -> Result<Self, String> {
+        let mut bytes = [0; 16];
+        getrandom::getrandom(&mut bytes)
+            .map_err(|e| format!("Cannot generate a random UUID: {}", e))?;
+        bytes[6] = (bytes[6] & 0x0f) | 0x40; // Version 4
+        bytes[8] = (bytes[8] & 0x3f) | 0x80; // RFC 4122 variant
+        Ok(Self(bytes))
+    }
+}
+
+impl Display for Uuid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, byte) in self.0.iter().enumerate() {
+            if matches!(index, 4 | 6 | 8 | 10) {
+                write!(f, "-")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use std::fmt::Display;
+
+use super::token::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseErrorKind {
+    /// The input is malformed where the span points.
+    Syntax,
+    /// A quote, literal or comment is still open at the end of the input.
+    Unterminated,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub message: String,
+    pub span: Span,
+}
+
+impl ParseError {
+    /// Whether more input could complete the statement, e.g. an open quote or comment.
+    pub fn is_unterminated(&self) -> bool {
+        self.kind == ParseErrorKind::Unterminated
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
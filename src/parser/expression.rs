@@ -0,0 +1,595 @@
+use super::statement::parse_data_type;
+use super::stream::TokenStream;
+use super::token::Token;
+use crate::db::data::{Collation, DataType, Value};
+use crate::db::expression::{BinaryOperator, Expression, UnaryOperator};
+
+/*
+```
+<expression> ::= <expression> <binary_operator> <expression>
+               | <expression> <comparison> ("ANY" | "ALL") "(" <expression> ")"
+               | <expression> "[" <expression> "]"
+               | <expression> "COLLATE" <collation>
+               | <expression> "::" <data_type>
+               | <expression> ["NOT"] "BETWEEN" <expression> "AND" <expression>
+               | <expression> ["NOT"] "IN" "(" <expression> ("," <expression>)* ")"
+               | <expression> ["NOT"] "LIKE" <expression>
+               | <expression> "IS" ["NOT"] "NULL"
+               | ("-" | "+" | "NOT") <expression>
+               | "(" <expression> ")"
+               | <identifier> "(" [<expression> ("," <expression>)*] ")"
+               | "EXTRACT" "(" <identifier> "FROM" <expression> ")"
+               | ("CAST" | "TRY_CAST") "(" <expression> "AS" <data_type> ")"
+               | <identifier> ["." <identifier>] | <number> | <literal> | <hex_literal>
+               | ("DATE" | "TIMESTAMP" | "TIMESTAMPTZ" | "TIME" | "INTERVAL" | "JSON" | "JSONB"
+                  | "UUID") <literal>
+               | "ARRAY" "[" [<expression> ("," <expression>)*] "]"
+               | "TRUE" | "FALSE" | "NULL"
+<binary_operator> ::= "OR" | "AND" | <comparison>
+                    | "||" | "->" | "->>" | "#>" | "#>>" | "+" | "-" | "*" | "/" | "%"
+<comparison> ::= "=" | "<>" | "!=" | "<" | "<=" | ">" | ">="
+<collation> ::= "BINARY" | "NOCASE" | "UNICODE"
+```
+
+Precedence from loosest to tightest: OR, AND, NOT, comparisons,
+BETWEEN/IN/LIKE/IS, || and the JSON operators, + -, * / %, unary minus,
+subscripts, COLLATE and "::". Binary operators are left associative.
+*/
+
+const NOT_PRECEDENCE: u8 = 3;
+const COMPARISON_PRECEDENCE: u8 = 4;
+const PREDICATE_PRECEDENCE: u8 = 5;
+const UNARY_MINUS_PRECEDENCE: u8 = 9;
+
+pub fn parse_expression<'a>(iter: &mut TokenStream<'a>) -> Result<Expression, String> {
+    parse_expression_bp(iter, 0)
+}
+
+fn is_keyword(token: Option<&Token>, expected: &str) -> bool {
+    matches!(token, Some(Token::Keyword(keyword)) if keyword.to_uppercase() == expected)
+}
+
+fn binary_operator(token: &Token) -> Option<BinaryOperator> {
+    match token {
+        Token::Operator(op) => match op.as_str() {
+            "+" => Some(BinaryOperator::Add),
+            "-" => Some(BinaryOperator::Subtract),
+            "*" => Some(BinaryOperator::Multiply),
+            "/" => Some(BinaryOperator::Divide),
+            "%" => Some(BinaryOperator::Modulo),
+            "||" => Some(BinaryOperator::Concat),
+            "->" => Some(BinaryOperator::JsonGet),
+            "->>" => Some(BinaryOperator::JsonGetText),
+            "#>" => Some(BinaryOperator::JsonPath),
+            "#>>" => Some(BinaryOperator::JsonPathText),
+            "=" => Some(BinaryOperator::Equal),
+            "<>" | "!=" => Some(BinaryOperator::NotEqual),
+            "<" => Some(BinaryOperator::Less),
+            "<=" => Some(BinaryOperator::LessOrEqual),
+            ">" => Some(BinaryOperator::Greater),
+            ">=" => Some(BinaryOperator::GreaterOrEqual),
+            _ => None,
+        },
+        Token::Keyword(keyword) => match keyword.to_uppercase().as_str() {
+            "AND" => Some(BinaryOperator::And),
+            "OR" => Some(BinaryOperator::Or),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Parses operators that bind tighter than `min_precedence`.
+fn parse_expression_bp<'a>(
+    iter: &mut TokenStream<'a>,
+    min_precedence: u8,
+) -> Result<Expression, String> {
+    let mut left = parse_prefix(iter)?;
+    loop {
+        match iter.peek() {
+            Some(Token::Punctuation('[')) => {
+                iter.next(); // Consume "["
+                let index = parse_expression(iter)?;
+                if iter.next() != Some(&Token::Punctuation(']')) {
+                    return Err("Expected ']' after array index".to_string());
+                }
+                left = Expression::Subscript {
+                    expression: Box::new(left),
+                    index: Box::new(index),
+                };
+            }
+            // COLLATE is not reserved so it can still name columns
+            Some(Token::Identifier(word)) if word.to_uppercase() == "COLLATE" => {
+                iter.next(); // Consume "COLLATE"
+                left = Expression::Collate {
+                    expression: Box::new(left),
+                    collation: parse_collation(iter)?,
+                };
+            }
+            Some(Token::Operator(op)) if op == "::" => {
+                iter.next(); // Consume "::"
+                left = Expression::Cast {
+                    expression: Box::new(left),
+                    data_type: parse_data_type(iter)?,
+                    try_cast: false,
+                };
+            }
+            _ => break,
+        }
+    }
+
+    while let Some(token) = iter.peek() {
+        if let Some(operator) = binary_operator(token) {
+            if operator.precedence() <= min_precedence {
+                break;
+            }
+            iter.next(); // Consume the operator
+
+            // ANY (or SOME) and ALL are not reserved, but right after a
+            // comparison they always start a quantified comparison
+            let quantifier = match (iter.peek(), iter.peek_second()) {
+                (Some(Token::Identifier(word)), Some(Token::Punctuation('(')))
+                    if operator.precedence() == COMPARISON_PRECEDENCE =>
+                {
+                    match word.to_uppercase().as_str() {
+                        "ANY" | "SOME" => Some(false),
+                        "ALL" => Some(true),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            if let Some(all) = quantifier {
+                iter.next(); // Consume "ANY" or "ALL"
+                iter.next(); // Consume "("
+                let array = parse_expression(iter)?;
+                if iter.next() != Some(&Token::Punctuation(')')) {
+                    return Err("Expected ')' after ANY/ALL".to_string());
+                }
+                left = Expression::Quantified {
+                    expression: Box::new(left),
+                    operator,
+                    array: Box::new(array),
+                    all,
+                };
+                continue;
+            }
+
+            let right = parse_expression_bp(iter, operator.precedence())?;
+            left = Expression::Binary {
+                left: Box::new(left),
+                operator,
+                right: Box::new(right),
+            };
+            continue;
+        }
+
+        if is_keyword(Some(token), "IS") {
+            if PREDICATE_PRECEDENCE <= min_precedence {
+                break;
+            }
+            iter.next(); // Consume "IS"
+            let negated = is_keyword(iter.peek(), "NOT");
+            if negated {
+                iter.next(); // Consume "NOT"
+            }
+            if !is_keyword(iter.next(), "NULL") {
+                return Err("Expected 'NULL' after IS".to_string());
+            }
+            left = Expression::IsNull {
+                expression: Box::new(left),
+                negated,
+            };
+            continue;
+        }
+
+        // BETWEEN, IN and LIKE, optionally preceded by NOT
+        let negated = is_keyword(Some(token), "NOT");
+        let predicate = match negated {
+            true => iter.peek_second(),
+            false => Some(token),
+        };
+        let predicate = match predicate {
+            Some(Token::Keyword(keyword)) => keyword.to_uppercase(),
+            _ => break,
+        };
+        if !matches!(predicate.as_str(), "BETWEEN" | "IN" | "LIKE")
+            || PREDICATE_PRECEDENCE <= min_precedence
+        {
+            break;
+        }
+        if negated {
+            iter.next(); // Consume "NOT"
+        }
+        iter.next(); // Consume the predicate keyword
+
+        let expression = Box::new(left);
+        left = match predicate.as_str() {
+            "BETWEEN" => {
+                let low = parse_expression_bp(iter, PREDICATE_PRECEDENCE)?;
+                if !is_keyword(iter.next(), "AND") {
+                    return Err("Expected 'AND' in BETWEEN".to_string());
+                }
+                let high = parse_expression_bp(iter, PREDICATE_PRECEDENCE)?;
+                Expression::Between {
+                    expression,
+                    low: Box::new(low),
+                    high: Box::new(high),
+                    negated,
+                }
+            }
+            "IN" => {
+                match iter.next() {
+                    Some(Token::Punctuation('(')) => {}
+                    _ => return Err("Expected '(' after IN".to_string()),
+                }
+                let list = parse_expression_list(iter)?;
+                Expression::InList {
+                    expression,
+                    list,
+                    negated,
+                }
+            }
+            _ => Expression::Like {
+                expression,
+                pattern: Box::new(parse_expression_bp(iter, PREDICATE_PRECEDENCE)?),
+                negated,
+            },
+        };
+    }
+
+    Ok(left)
+}
+
+/// Parses the collation name after COLLATE.
+pub fn parse_collation<'a>(iter: &mut TokenStream<'a>) -> Result<Collation, String> {
+    match iter.next() {
+        Some(Token::Identifier(name)) => Collation::parse(name),
+        _ => Err("Expected collation name after COLLATE".to_string()),
+    }
+}
+
+/// Parses `<expression> ("," <expression>)* ")"` after an opening parenthesis.
+pub fn parse_expression_list<'a>(iter: &mut TokenStream<'a>) -> Result<Vec<Expression>, String> {
+    let mut list = Vec::new();
+    loop {
+        list.push(parse_expression(iter)?);
+        match iter.next() {
+            Some(Token::Punctuation(',')) => continue,
+            Some(Token::Punctuation(')')) => break,
+            _ => return Err("Expected ',' or ')'".to_string()),
+        }
+    }
+    Ok(list)
+}
+
+fn parse_prefix<'a>(iter: &mut TokenStream<'a>) -> Result<Expression, String> {
+    match iter.next() {
+        Some(Token::Numeric(number)) => Ok(Expression::Literal(Value::parse_number(number)?)),
+        Some(Token::Literal(text)) => Ok(Expression::Literal(Value::Varchar(text.clone()))),
+        Some(Token::Keyword(keyword)) => match keyword.to_uppercase().as_str() {
+            "TRUE" => Ok(Expression::Literal(Value::Boolean(true))),
+            "FALSE" => Ok(Expression::Literal(Value::Boolean(false))),
+            "NULL" => Ok(Expression::Literal(Value::Null)),
+            "NOT" => Ok(Expression::Unary {
+                operator: UnaryOperator::Not,
+                operand: Box::new(parse_expression_bp(iter, NOT_PRECEDENCE)?),
+            }),
+            _ => Err(format!("Unexpected keyword '{}' in expression", keyword)),
+        },
+        Some(Token::Operator(op)) if op == "-" => {
+            // Fold the sign into numeric literals so INT's minimum value parses
+            if let Some(Token::Numeric(number)) = iter.peek() {
+                iter.next();
+                return Ok(Expression::Literal(Value::parse_number(&format!(
+                    "-{}",
+                    number
+                ))?));
+            }
+            Ok(Expression::Unary {
+                operator: UnaryOperator::Minus,
+                operand: Box::new(parse_expression_bp(iter, UNARY_MINUS_PRECEDENCE)?),
+            })
+        }
+        Some(Token::Operator(op)) if op == "+" => parse_expression_bp(iter, UNARY_MINUS_PRECEDENCE),
+        Some(Token::Punctuation('(')) => {
+            let expression = parse_expression(iter)?;
+            match iter.next() {
+                Some(Token::Punctuation(')')) => Ok(expression),
+                _ => Err("Expected ')'".to_string()),
+            }
+        }
+        Some(Token::Identifier(name))
+            if name.to_uppercase() == "ARRAY" && iter.peek() == Some(&Token::Punctuation('[')) =>
+        {
+            iter.next(); // Consume "["
+            let mut elements = Vec::new();
+            if iter.peek() == Some(&Token::Punctuation(']')) {
+                iter.next(); // Consume "]"
+                return Ok(Expression::Array(elements));
+            }
+            loop {
+                elements.push(parse_expression(iter)?);
+                match iter.next() {
+                    Some(Token::Punctuation(',')) => continue,
+                    Some(Token::Punctuation(']')) => return Ok(Expression::Array(elements)),
+                    _ => return Err("Expected ',' or ']'".to_string()),
+                }
+            }
+        }
+        Some(Token::Identifier(name)) => {
+            if let Some(Token::Punctuation('(')) = iter.peek() {
+                iter.next(); // Consume "("
+
+                // EXTRACT(field FROM source) is stored as extract('field', source)
+                if name.to_uppercase() == "EXTRACT" && is_keyword(iter.peek_second(), "FROM") {
+                    let field = match iter.next() {
+                        Some(Token::Identifier(field)) => field.clone(),
+                        _ => return Err("Expected field name in EXTRACT".to_string()),
+                    };
+                    iter.next(); // Consume "FROM"
+                    let source = parse_expression(iter)?;
+                    if iter.next() != Some(&Token::Punctuation(')')) {
+                        return Err("Expected ')' after EXTRACT".to_string());
+                    }
+                    return Ok(Expression::Function {
+                        name: name.clone(),
+                        args: vec![Expression::Literal(Value::Varchar(field)), source],
+                    });
+                }
+                // CAST and TRY_CAST are not reserved so they can still name columns
+                if matches!(name.to_uppercase().as_str(), "CAST" | "TRY_CAST") {
+                    let expression = parse_expression(iter)?;
+                    if !is_keyword(iter.next(), "AS") {
+                        return Err(format!("Expected 'AS' in {}", name.to_uppercase()));
+                    }
+                    let data_type = parse_data_type(iter)?;
+                    if iter.next() != Some(&Token::Punctuation(')')) {
+                        return Err(format!("Expected ')' after {}", name.to_uppercase()));
+                    }
+                    return Ok(Expression::Cast {
+                        expression: Box::new(expression),
+                        data_type,
+                        try_cast: name.to_uppercase() == "TRY_CAST",
+                    });
+                }
+                let args = match iter.peek() {
+                    Some(Token::Punctuation(')')) => {
+                        iter.next();
+                        Vec::new()
+                    }
+                    _ => parse_expression_list(iter)?,
+                };
+                return Ok(Expression::Function {
+                    name: name.clone(),
+                    args,
+                });
+            }
+            if let Some(Token::Punctuation('.')) = iter.peek() {
+                iter.next(); // Consume "."
+                return match iter.next() {
+                    Some(Token::Identifier(column)) => Ok(Expression::QualifiedColumn {
+                        table: name.clone(),
+                        column: column.clone(),
+                    }),
+                    _ => Err("Expected column name after '.'".to_string()),
+                };
+            }
+            Ok(Expression::Column(name.clone()))
+        }
+        Some(Token::DataType(name)) => {
+            let data_type = match name.to_uppercase().as_str() {
+                "DATE" => DataType::Date,
+                "TIMESTAMP" => DataType::Timestamp,
+                "TIMESTAMPTZ" => DataType::TimestampTz,
+                "TIME" => DataType::Time,
+                "INTERVAL" => DataType::Interval,
+                "JSON" | "JSONB" => DataType::Json,
+                "UUID" => DataType::Uuid,
+                _ => return Err("Expected expression".to_string()),
+            };
+            match iter.next() {
+                Some(Token::Literal(text)) => {
+                    Ok(Expression::Literal(Value::from_str(&data_type, text)?))
+                }
+                _ => Err(format!("Expected quoted value after {}", data_type)),
+            }
+        }
+        Some(Token::HexLiteral(bytes)) => Ok(Expression::Literal(Value::Blob(bytes.clone()))),
+        Some(_) => Err("Expected expression".to_string()),
+        None => Err("Unexpected end of input".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::decimal::Decimal;
+    use crate::parser::token::Span;
+    use crate::parser::tokenizer::tokenize;
+
+    fn parse(text: &str) -> Expression {
+        let tokens = tokenize(text).unwrap();
+        let mut iter = TokenStream::new(&tokens, Span::end_of(text));
+        let expression = parse_expression(&mut iter).unwrap();
+        assert_eq!(iter.next(), None, "trailing tokens in {}", text);
+        expression
+    }
+
+    fn evaluate(text: &str) -> Value {
+        parse(text).evaluate(&[], &[]).unwrap()
+    }
+
+    /// Renders with every binary node parenthesized to expose the tree shape.
+    fn shape(expression: &Expression) -> String {
+        match expression {
+            Expression::Binary {
+                left,
+                operator,
+                right,
+            } => format!("({} {} {})", shape(left), operator, shape(right)),
+            Expression::Unary { operator, operand } => format!("({}{})", operator, shape(operand)),
+            Expression::Quantified {
+                expression,
+                operator,
+                array,
+                all,
+            } => {
+                let quantifier = if *all { "ALL" } else { "ANY" };
+                format!(
+                    "({} {} {}({}))",
+                    shape(expression),
+                    operator,
+                    quantifier,
+                    shape(array)
+                )
+            }
+            other => other.to_string(),
+        }
+    }
+
+    #[test]
+    fn precedence_and_associativity() {
+        assert_eq!(shape(&parse("1 + 2 * 3")), "(1 + (2 * 3))");
+        assert_eq!(shape(&parse("1 - 2 - 3")), "((1 - 2) - 3)");
+        assert_eq!(shape(&parse("a || b = c || d")), "((a || b) = (c || d))");
+        assert_eq!(
+            shape(&parse("NOT a = 1 AND b OR c")),
+            "(((NOT (a = 1)) AND b) OR c)"
+        );
+        assert_eq!(shape(&parse("-a * b")), "((-a) * b)");
+        assert_eq!(shape(&parse("(1 + 2) * 3")), "((1 + 2) * 3)");
+        assert_eq!(
+            shape(&parse("j -> 'a' ->> 'b' = 'x'")),
+            "(((j -> 'a') ->> 'b') = 'x')"
+        );
+        assert_eq!(shape(&parse("-a[1] * 2")), "((-a[1]) * 2)");
+        assert_eq!(
+            shape(&parse("a + 1 = ANY(b) AND c")),
+            "(((a + 1) = ANY(b)) AND c)"
+        );
+    }
+
+    #[test]
+    fn predicates() {
+        assert_eq!(
+            parse("x NOT BETWEEN 1 + 1 AND 5 AND y").to_string(),
+            "x NOT BETWEEN 1 + 1 AND 5 AND y"
+        );
+        assert_eq!(
+            evaluate("3 BETWEEN 1 AND 5 AND 2 IN (1, 2)"),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            evaluate("'abc' LIKE 'a_c' AND 'abc' NOT LIKE 'b%'"),
+            Value::Boolean(true)
+        );
+        assert_eq!(evaluate("1 <> 2 AND 1 != 1"), Value::Boolean(false));
+    }
+
+    #[test]
+    fn like_patterns() {
+        let like = |value: &str, pattern: &str| {
+            evaluate(&format!("'{}' LIKE '{}'", value, pattern)) == Value::Boolean(true)
+        };
+        assert!(like("", ""));
+        assert!(like("", "%%"));
+        assert!(!like("", "_"));
+        assert!(like("abc", "%"));
+        assert!(like("abc", "a%c"));
+        assert!(like("abcbc", "%bc"));
+        assert!(like("abcbd", "a%b_"));
+        assert!(like("mississippi", "m%iss%ppi"));
+        assert!(!like("mississippi", "m%iss%ppix"));
+        assert!(!like("abc", "ab"));
+        assert!(!like("ab", "abc"));
+        assert!(like("héllo", "h_llo"));
+        // Many '%' against a long miss used to take exponential time
+        let value = "a".repeat(200);
+        assert!(!like(&value, &format!("{}b", "a%".repeat(30))));
+        assert!(like(&value, &"a%".repeat(30)));
+    }
+
+    #[test]
+    fn arithmetic_and_functions() {
+        assert_eq!(evaluate("7 / 2 + 7 % 2"), Value::Int(4));
+        assert_eq!(
+            evaluate("1 + 0.5"),
+            Value::Decimal(Decimal::parse("1.5").unwrap())
+        );
+        assert_eq!(evaluate("0.1 + 0.2 = 0.3"), Value::Boolean(true));
+        assert_eq!(evaluate("1.50 * 2").to_string(), "3.00");
+        assert_eq!(evaluate("-1 / 3.0").to_string(), "-0.3333333");
+        assert_eq!(evaluate("round(2.345, 2) - 2").to_string(), "0.35");
+        assert_eq!(evaluate("-2147483648"), Value::Int(i32::MIN));
+        assert_eq!(
+            evaluate("lower('A' || 'B')"),
+            Value::Varchar("ab".to_string())
+        );
+        assert!(parse("2147483647 + 1").evaluate(&[], &[]).is_err());
+        assert_eq!(evaluate("2147483648 + 1"), Value::BigInt(2147483649));
+        assert!(parse("9223372036854775807 + 1").evaluate(&[], &[]).is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for text in [
+            "a - (b - c)",
+            "-(-1)",
+            "(a OR b) AND c",
+            "f(x, 1 + 2) * 3",
+            "x IS NOT NULL",
+            "TIMESTAMP '2024-01-31 10:15:30.250' + INTERVAL '-1 years 2 days -00:00:01.5'",
+            "TIME '23:30:00' - INTERVAL '1 mon'",
+            "EXTRACT('day', DATE '2024-02-29')",
+            "JSON '{\"a\": [1, 2.50]}' #> '{a,1}' ->> 0",
+            "j -> -1 #>> '{x}'",
+            "m[i + 1][2] < ALL(ARRAY[1, 2.5]) OR x = ANY('{a,b}')",
+            "UUID 'a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11' = ANY(ids)",
+            "(a || b) COLLATE NOCASE LIKE 'x%' AND tags[1] COLLATE UNICODE = 'é'",
+            "CAST(a + 1 AS DECIMAL(5, 2)) * 2 = TRY_CAST(b AS mood[])",
+        ] {
+            assert_eq!(parse(text).to_string(), text);
+            assert_eq!(parse(&parse(text).to_string()), parse(text));
+        }
+    }
+
+    #[test]
+    fn casts() {
+        assert_eq!(parse("-x::INT[] + 1"), parse("-CAST(x AS INT[]) + 1"),);
+        assert_eq!(
+            parse("'1'::BIGINT::VARCHAR(3) || 'b'"),
+            parse("CAST(CAST('1' AS BIGINT) AS VARCHAR(3)) || 'b'"),
+        );
+        assert_eq!(evaluate("'12'::INT + 2.5::INT"), Value::Int(15));
+        assert_eq!(evaluate("TRY_CAST('twelve' AS INT)"), Value::Null);
+        assert!(parse("CAST(DATE '2024-01-01' AS INT)")
+            .evaluate(&[], &[])
+            .is_err());
+    }
+
+    #[test]
+    fn null_logic() {
+        assert_eq!(evaluate("NULL = NULL"), Value::Null);
+        assert_eq!(evaluate("1 + NULL"), Value::Null);
+        assert_eq!(evaluate("NOT NULL"), Value::Null);
+        assert_eq!(evaluate("NULL AND FALSE"), Value::Boolean(false));
+        assert_eq!(evaluate("NULL AND TRUE"), Value::Null);
+        assert_eq!(evaluate("NULL OR TRUE"), Value::Boolean(true));
+        assert_eq!(evaluate("2 IN (1, NULL)"), Value::Null);
+        assert_eq!(evaluate("1 NOT IN (1, NULL)"), Value::Boolean(false));
+        assert_eq!(
+            evaluate("NULL IS NULL AND 1 IS NOT NULL"),
+            Value::Boolean(true)
+        );
+        assert_eq!(evaluate("UPPER(NULL)"), Value::Null);
+        assert_eq!(
+            evaluate("CONCAT('a', NULL, 'b')"),
+            Value::Varchar("ab".to_string())
+        );
+        assert_eq!(
+            evaluate("DATE '2024-01-31' > '2024-01-01'"),
+            Value::Boolean(true)
+        );
+    }
+}
@@ -1,18 +1,27 @@
-use statement::{parse_create, parse_insert, parse_select};
-use token::Token;
-use tokenizer::tokenize;
+use statement::{parse_alter, parse_copy, parse_create, parse_insert, parse_select};
+use stream::TokenStream;
+use tokenizer::tokenize_partial;
 
 use crate::db::{command::Command, statement::Statement};
 
+mod error;
+mod expression;
 mod statement;
+mod stream;
 mod token;
 mod tokenizer;
 
-pub fn parse_statement(statement: &str) -> Result<Statement, String> {
-    let tokens = tokenize(statement);
-    let mut iter = tokens.iter().peekable();
+pub use error::{ParseError, ParseErrorKind};
+pub use token::{Span, Token};
+pub use tokenizer::{quote_identifier, tokenize, DATA_TYPES, KEYWORDS};
 
-    match iter.peek() {
+pub const COMMANDS: &[&str] = &[".exit", ".import", ".export", ".dump", ".read"];
+
+pub fn parse_statement(text: &str) -> Result<Statement, ParseError> {
+    let tokens = tokenize(text)?;
+    let mut iter = TokenStream::new(&tokens, Span::end_of(text));
+
+    let result = match iter.peek() {
         Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "CREATE" => {
             parse_create(&mut iter)
         }
@@ -22,13 +31,160 @@ pub fn parse_statement(statement: &str) -> Result<Statement, String> {
         Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "INSERT" => {
             parse_insert(&mut iter)
         }
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "COPY" => parse_copy(&mut iter),
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "ALTER" => {
+            parse_alter(&mut iter)
+        }
         _ => Err("Unknown statement".to_string()),
+    };
+    let statement = result.map_err(|message| ParseError {
+        kind: ParseErrorKind::Syntax,
+        message,
+        span: iter.error_span(),
+    })?;
+
+    // One trailing ";" may end the statement; anything else is left over
+    if iter.peek() == Some(&Token::Punctuation(';')) {
+        iter.next(); // Consume ";"
     }
+    if iter.peek().is_some() {
+        let span = iter.next_span();
+        return Err(ParseError {
+            kind: ParseErrorKind::Syntax,
+            message: format!(
+                "Unexpected '{}' after the end of the statement",
+                &text[span.start..span.end]
+            ),
+            span,
+        });
+    }
+    Ok(statement)
 }
 
 pub fn parse_command(input: &str) -> Command {
-    match input {
-        ".exit" => Command::Exit,
+    let args = input.split_whitespace().collect::<Vec<_>>();
+    match args.as_slice() {
+        [".exit"] => Command::Exit,
+        [".import", file_name, table_name] => Command::Import {
+            file_name: file_name.to_string(),
+            table_name: table_name.to_string(),
+        },
+        [".export", table_name, file_name] => Command::Export {
+            table_name: table_name.to_string(),
+            file_name: file_name.to_string(),
+        },
+        [".dump"] => Command::Dump { table_name: None },
+        [".dump", table_name] => Command::Dump {
+            table_name: Some(table_name.to_string()),
+        },
+        [".read", file_name] => Command::Read {
+            file_name: file_name.to_string(),
+        },
         _ => Command::Unknown(input.to_string()),
     }
 }
+
+/// Splits a script into statements on `;` tokens, so semicolons inside quotes
+/// and comments don't count.
+///
+/// Text the tokenizer rejects is skipped, so a bad statement doesn't swallow
+/// the ones after it; the parser reports the error when that statement runs.
+pub fn split_statements(script: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+
+    loop {
+        let (tokens, result) = tokenize_partial(&script[offset..]);
+        for token in tokens {
+            if token.token == Token::Punctuation(';') {
+                let end = offset + token.span.start;
+                statements.push(&script[start..end]);
+                start = end + 1;
+            }
+        }
+        match result {
+            // An open quote or comment runs to the end of the script
+            Err(e) if !e.is_unterminated() && e.span.end > 0 => offset += e.span.end,
+            _ => break,
+        }
+    }
+    statements.push(&script[start..]);
+
+    statements
+        .into_iter()
+        .map(|statement| statement.trim().to_string())
+        .filter(|statement| !statement.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text under the error's span and its message.
+    fn trailing_error(text: &str) -> (&str, String) {
+        let error = parse_statement(text).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::Syntax);
+        (&text[error.span.start..error.span.end], error.message)
+    }
+
+    #[test]
+    fn trailing_tokens_after_where() {
+        assert_eq!(
+            trailing_error("SELECT * FROM t WHERE a = 1 b = 3"),
+            (
+                "b",
+                "Unexpected 'b' after the end of the statement".to_string()
+            )
+        );
+        assert_eq!(trailing_error("SELECT * FROM t LIMIT 1").0, "LIMIT");
+    }
+
+    #[test]
+    fn trailing_tokens_after_order_by() {
+        assert_eq!(
+            trailing_error("SELECT a FROM t ORDER BY a DESC garbage").0,
+            "garbage"
+        );
+    }
+
+    #[test]
+    fn trailing_tokens_after_values() {
+        assert_eq!(trailing_error("INSERT INTO t VALUES (1, 2) (3, 4)").0, "(");
+        assert_eq!(
+            trailing_error("INSERT INTO t VALUES (1); SELECT 1").0,
+            "SELECT"
+        );
+    }
+
+    #[test]
+    fn one_trailing_semicolon_is_allowed() {
+        assert!(parse_statement("SELECT a FROM t ORDER BY a DESC;").is_ok());
+        assert_eq!(trailing_error("SELECT 1;;").0, ";");
+    }
+
+    #[test]
+    fn splits_on_semicolon_tokens() {
+        assert_eq!(
+            split_statements(
+                "SELECT 'a;b', \"c;d\"; -- e;f\n\
+                 SELECT E'g\\';h' /* i; /* j; */ k; */;\n\n;  SELECT 1"
+            ),
+            [
+                "SELECT 'a;b', \"c;d\"",
+                "-- e;f\nSELECT E'g\\';h' /* i; /* j; */ k; */",
+                "SELECT 1"
+            ]
+        );
+        // A stray character belongs to its own statement and doesn't hide the next one
+        assert_eq!(
+            split_statements("SELECT 1 # 2; SELECT 1.2.3; SELECT 4"),
+            ["SELECT 1 # 2", "SELECT 1.2.3", "SELECT 4"]
+        );
+        assert_eq!(
+            split_statements("SELECT 1; SELECT 'open; SELECT 2"),
+            ["SELECT 1", "SELECT 'open; SELECT 2"]
+        );
+    }
+}
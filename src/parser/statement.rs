@@ -1,35 +1,174 @@
-use std::iter::Peekable;
-
+use super::expression::{parse_collation, parse_expression, parse_expression_list};
+use super::stream::TokenStream;
 use super::token::Token;
-use crate::db::data::{Column, DataType};
-use crate::db::statement::{CreateTable, Insert, Select, Statement};
+use crate::db::data::{
+    Collation, Column, Constraint, ConstraintKind, DataType, EnumType, ForeignKey, Identity,
+};
+use crate::db::decimal::MAX_PRECISION;
+use crate::db::expression::Expression;
+use crate::db::sequence::Sequence;
+use crate::db::statement::{
+    AlterAction, AlterTable, Assignment, ConflictAction, Copy, CopyDirection, CreateSequence,
+    CreateTable, CreateTableAs, CreateType, FromItem, Insert, InsertSource, InsertValue,
+    OnConflict, OrderBy, Select, SelectItem, Statement,
+};
+use std::sync::Arc;
 
-pub fn parse_create<'a>(
-    iter: &mut Peekable<impl Iterator<Item = &'a Token>>,
-) -> Result<Statement, String> {
+pub fn parse_create<'a>(iter: &mut TokenStream<'a>) -> Result<Statement, String> {
     iter.next(); // Consume "CREATE"
     match iter.peek() {
         Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "TABLE" => {
             parse_create_table(iter)
         }
+        // SEQUENCE is not reserved so it can still name tables and columns
+        Some(Token::Identifier(word)) if word.to_uppercase() == "SEQUENCE" => {
+            parse_create_sequence(iter)
+        }
+        // TYPE is not reserved either
+        Some(Token::Identifier(word)) if word.to_uppercase() == "TYPE" => parse_create_type(iter),
         _ => Err("Unknow create statement".to_string()),
     }
 }
 
+/*
+```
+<create_sequence> ::= "CREATE" "SEQUENCE" <identifier> <sequence_options>
+<sequence_options> ::= ["START" ["WITH"] <integer>] ["INCREMENT" ["BY"] <integer>]
+<integer> ::= ["-"] <number>
+```
+*/
+
+fn parse_create_sequence<'a>(iter: &mut TokenStream<'a>) -> Result<Statement, String> {
+    iter.next(); // Consume "SEQUENCE"
+    let name = expect_identifier(iter, "sequence name")?;
+    let (start, increment) = parse_sequence_options(iter)?;
+    Ok(Statement::CreateSequence(CreateSequence {
+        sequence: Sequence::new(name, start, increment)?,
+    }))
+}
+
+/*
+```
+<create_type> ::= "CREATE" "TYPE" <identifier> "AS" "ENUM" "(" [<string> ("," <string>)*] ")"
+```
+*/
+
+fn parse_create_type<'a>(iter: &mut TokenStream<'a>) -> Result<Statement, String> {
+    iter.next(); // Consume "TYPE"
+    let name = expect_identifier(iter, "type name")?;
+    if !is_keyword(iter.next(), "AS") {
+        return Err("Expected AS after type name".to_string());
+    }
+    // ENUM is not reserved
+    match iter.next() {
+        Some(Token::Identifier(word)) if word.to_uppercase() == "ENUM" => {}
+        _ => return Err("Expected ENUM after AS".to_string()),
+    }
+    if iter.next() != Some(&Token::Punctuation('(')) {
+        return Err("Expected '(' after ENUM".to_string());
+    }
+    let mut labels: Vec<String> = Vec::new();
+    if iter.peek() == Some(&Token::Punctuation(')')) {
+        iter.next(); // Consume ")"
+    } else {
+        loop {
+            match iter.next() {
+                Some(Token::Literal(label)) if labels.contains(label) => {
+                    return Err(format!("Duplicate enum label '{}'", label))
+                }
+                Some(Token::Literal(label)) => labels.push(label.clone()),
+                _ => return Err("Expected quoted enum label".to_string()),
+            }
+            match iter.next() {
+                Some(Token::Punctuation(',')) => continue,
+                Some(Token::Punctuation(')')) => break,
+                _ => return Err("Expected ',' or ')'".to_string()),
+            }
+        }
+    }
+    if labels.len() > u16::MAX as usize {
+        return Err(format!("An enum has at most {} labels", u16::MAX));
+    }
+    Ok(Statement::CreateType(CreateType {
+        enum_type: Arc::new(EnumType { name, labels }),
+    }))
+}
+
+/// Parses optional START and INCREMENT clauses, returning the start value and
+/// the increment, each 1 unless given.
+fn parse_sequence_options<'a>(iter: &mut TokenStream<'a>) -> Result<(i32, i32), String> {
+    let mut start = 1;
+    let mut increment = 1;
+    loop {
+        match iter.peek() {
+            Some(Token::Identifier(word)) if word.to_uppercase() == "START" => {
+                iter.next(); // Consume "START"
+                if is_keyword(iter.peek(), "WITH") {
+                    iter.next(); // Consume "WITH"
+                }
+                start = parse_integer(iter)?;
+            }
+            Some(Token::Identifier(word)) if word.to_uppercase() == "INCREMENT" => {
+                iter.next(); // Consume "INCREMENT"
+                if is_keyword(iter.peek(), "BY") {
+                    iter.next(); // Consume "BY"
+                }
+                increment = parse_integer(iter)?;
+            }
+            _ => return Ok((start, increment)),
+        }
+    }
+}
+
+fn parse_integer<'a>(iter: &mut TokenStream<'a>) -> Result<i32, String> {
+    let negative = matches!(iter.peek(), Some(Token::Operator(op)) if op == "-");
+    if negative {
+        iter.next(); // Consume "-"
+    }
+    match iter.next() {
+        Some(Token::Numeric(number)) => {
+            let number = match negative {
+                true => format!("-{}", number),
+                false => number.clone(),
+            };
+            number
+                .parse::<i32>()
+                .map_err(|_| format!("Expected integer, got '{}'", number))
+        }
+        _ => Err("Expected integer".to_string()),
+    }
+}
+
 /*
 ```
 <create_table> ::= "CREATE" "TABLE" <identifier> "(" <column_definitions> ")"
-<column_definitions> ::= <column_definition> ("," <column_definition>)*
-<column_definition> ::= <identifier> <data_type>
-<data_type> ::= "INT" | "VARCHAR" "(" <number> ")" | "DATE" | "DOUBLE"
+                 | "CREATE" "TABLE" <identifier> "AS" <select>
+<column_definitions> ::= (<column_definition> | <table_constraint>)
+                         ("," (<column_definition> | <table_constraint>))*
+<column_definition> ::= <identifier> <data_type> <column_constraint>*
+<column_constraint> ::= "DEFAULT" <expression> | ["NOT"] "NULL"
+                      | "GENERATED" ("ALWAYS" | "BY" "DEFAULT") "AS" "IDENTITY"
+                        ["(" <sequence_options> ")"]
+                      | "AUTOINCREMENT" | "COLLATE" <collation>
+                      | ["CONSTRAINT" <identifier>]
+                        ("UNIQUE" | "CHECK" "(" <expression> ")" | <references>)
+<table_constraint> ::= ["CONSTRAINT" <identifier>]
+                       ("UNIQUE" <column_list> | "CHECK" "(" <expression> ")"
+                        | "FOREIGN" "KEY" <column_list> <references>)
+<references> ::= "REFERENCES" <identifier> <column_list>
+                 ["ON" "DELETE" ("RESTRICT" | "NO" "ACTION")]
+<column_list> ::= "(" <identifier> ("," <identifier>)* ")"
+<data_type> ::= "INT" | "VARCHAR" "(" <number> ")" | "CHAR" ["(" <number> ")"] | "DATE" | "DOUBLE"
+              | ("DECIMAL" | "NUMERIC") ["(" <number> ["," <number>] ")"]
+              | "TIMESTAMP" [("WITH" | "WITHOUT") "TIME" "ZONE"] | "TIMESTAMPTZ"
+              | "TIME" | "INTERVAL" | "TEXT" | "BLOB" | "BYTEA" | "JSON" | "JSONB"
+              | "UUID" | <identifier> | <data_type> "[" [<number>] "]"
 <identifier> ::= [a-zA-Z_][a-zA-Z0-9_]*
 <number> ::= [0-9]+
 ```
 */
 
-pub fn parse_create_table<'a>(
-    iter: &mut Peekable<impl Iterator<Item = &'a Token>>,
-) -> Result<Statement, String> {
+pub fn parse_create_table<'a>(iter: &mut TokenStream<'a>) -> Result<Statement, String> {
     iter.next(); // Consume "TABLE"
 
     // Expect table name
@@ -38,28 +177,48 @@ pub fn parse_create_table<'a>(
         _ => return Err("Expected table name".to_string()),
     };
 
+    if let Some(Token::Keyword(keyword)) = iter.peek() {
+        if keyword.to_uppercase() == "AS" {
+            iter.next(); // Consume "AS"
+            return match iter.peek() {
+                Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "SELECT" => {
+                    Ok(Statement::CreateTableAs(CreateTableAs {
+                        table_name,
+                        query: parse_select_query(iter)?,
+                    }))
+                }
+                _ => Err("Expected SELECT after AS".to_string()),
+            };
+        }
+    }
+
     // Expect "("
     match iter.next() {
         Some(Token::Punctuation('(')) => {}
         _ => return Err("Expected '('".to_string()),
     }
 
-    // Parse column definitions
+    // Parse column definitions and table constraints
     let mut columns = Vec::new();
+    let mut constraints = Vec::new();
     loop {
-        // Expect column name
-        let column_name = match iter.next() {
-            Some(Token::Identifier(name)) => name.clone(),
-            _ => return Err("Expected column name".to_string()),
-        };
-
-        // Expect data type
-        let data_type = parse_data_type(iter)?;
-
-        columns.push(Column {
-            name: column_name,
-            data_type,
-        });
+        let starts_constraint = matches!(
+            iter.peek(),
+            Some(Token::Keyword(keyword))
+                if matches!(
+                    keyword.to_uppercase().as_str(),
+                    "CONSTRAINT" | "UNIQUE" | "CHECK" | "FOREIGN"
+                )
+        );
+        if starts_constraint {
+            let constraint = parse_constraint(iter, &table_name, None, &constraints)?;
+            constraints.push(constraint);
+        } else {
+            let (column, column_constraints) =
+                parse_column_definition(iter, &table_name, &constraints)?;
+            columns.push(column);
+            constraints.extend(column_constraints);
+        }
 
         // Check for "," or ")"
         match iter.peek() {
@@ -78,19 +237,447 @@ pub fn parse_create_table<'a>(
     Ok(Statement::CreateTable(CreateTable {
         table_name,
         columns,
+        constraints,
     }))
 }
 
-fn parse_data_type<'a>(
-    iter: &mut Peekable<impl Iterator<Item = &'a Token>>,
-) -> Result<DataType, String> {
+/// Parses a column and its constraints. UNIQUE, CHECK and REFERENCES are
+/// returned as table constraints, named after `table_name` unless given a name.
+fn parse_column_definition<'a>(
+    iter: &mut TokenStream<'a>,
+    table_name: &str,
+    taken: &[Constraint],
+) -> Result<(Column, Vec<Constraint>), String> {
+    // Expect column name
+    let name = match iter.next() {
+        Some(Token::Identifier(name)) => name.clone(),
+        _ => return Err("Expected column name".to_string()),
+    };
+
+    // Expect data type
+    let data_type = parse_data_type(iter)?;
+
+    let mut default = None;
+    let mut not_null = false;
+    let mut identity = None;
+    let mut collation = Collation::default();
+    let mut constraints: Vec<Constraint> = Vec::new();
+    // GENERATED, AUTOINCREMENT and COLLATE are not reserved so they can still name columns
+    while let Some(Token::Keyword(word) | Token::Identifier(word)) = iter.peek() {
+        match word.to_uppercase().as_str() {
+            "DEFAULT" => {
+                iter.next(); // Consume "DEFAULT"
+                default = Some(parse_expression(iter)?);
+            }
+            "NOT" => {
+                iter.next(); // Consume "NOT"
+                if !is_keyword(iter.next(), "NULL") {
+                    return Err("Expected 'NULL' after NOT".to_string());
+                }
+                not_null = true;
+            }
+            "NULL" => {
+                iter.next(); // Consume "NULL"
+                not_null = false;
+            }
+            "CONSTRAINT" | "UNIQUE" | "CHECK" | "REFERENCES" => {
+                let taken = [taken, &constraints].concat();
+                let constraint = parse_constraint(iter, table_name, Some(&name), &taken)?;
+                constraints.push(constraint);
+            }
+            "GENERATED" | "AUTOINCREMENT" => {
+                let sequence_name = format!("{}_{}_seq", table_name, name);
+                identity = Some(parse_identity(iter, sequence_name)?);
+                not_null = true;
+            }
+            "COLLATE" => {
+                iter.next(); // Consume "COLLATE"
+                collation = parse_collation(iter)?;
+            }
+            _ => break,
+        }
+    }
+
+    let column = Column {
+        name,
+        data_type,
+        default,
+        not_null,
+        identity,
+        collation,
+    };
+    Ok((column, constraints))
+}
+
+/// Parses `GENERATED ... AS IDENTITY [(options)]` or `AUTOINCREMENT`, which
+/// is the same as `GENERATED BY DEFAULT AS IDENTITY`.
+fn parse_identity<'a>(
+    iter: &mut TokenStream<'a>,
+    sequence_name: String,
+) -> Result<Identity, String> {
+    let generated = match iter.next() {
+        Some(Token::Identifier(word)) => word.to_uppercase(),
+        _ => return Err("Expected GENERATED or AUTOINCREMENT".to_string()),
+    };
+    if generated == "AUTOINCREMENT" {
+        return Ok(Identity {
+            always: false,
+            sequence: Sequence::new(sequence_name, 1, 1)?,
+        });
+    }
+
+    let always = match iter.next() {
+        Some(Token::Identifier(word)) if word.to_uppercase() == "ALWAYS" => true,
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "BY" => {
+            if !is_keyword(iter.next(), "DEFAULT") {
+                return Err("Expected 'DEFAULT' after BY".to_string());
+            }
+            false
+        }
+        _ => return Err("Expected ALWAYS or BY DEFAULT after GENERATED".to_string()),
+    };
+    if !is_keyword(iter.next(), "AS") {
+        return Err("Expected 'AS'".to_string());
+    }
+    match iter.next() {
+        Some(Token::Identifier(word)) if word.to_uppercase() == "IDENTITY" => {}
+        _ => return Err("Expected 'IDENTITY'".to_string()),
+    }
+
+    let (start, increment) = match iter.peek() {
+        Some(Token::Punctuation('(')) => {
+            iter.next(); // Consume "("
+            let options = parse_sequence_options(iter)?;
+            match iter.next() {
+                Some(Token::Punctuation(')')) => {}
+                _ => return Err("Expected ')'".to_string()),
+            }
+            options
+        }
+        _ => (1, 1),
+    };
+    Ok(Identity {
+        always,
+        sequence: Sequence::new(sequence_name, start, increment)?,
+    })
+}
+
+/// Parses `["CONSTRAINT" name] (UNIQUE | CHECK | FOREIGN KEY | REFERENCES)`.
+/// A column constraint's UNIQUE or REFERENCES covers `column` and takes no
+/// column list of its own.
+fn parse_constraint<'a>(
+    iter: &mut TokenStream<'a>,
+    table_name: &str,
+    column: Option<&str>,
+    taken: &[Constraint],
+) -> Result<Constraint, String> {
+    let name = match iter.peek() {
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "CONSTRAINT" => {
+            iter.next(); // Consume "CONSTRAINT"
+            Some(expect_identifier(iter, "constraint name")?)
+        }
+        _ => None,
+    };
+
+    let kind = match iter.next() {
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "UNIQUE" => match column {
+            Some(column) => ConstraintKind::Unique(vec![column.to_string()]),
+            None => ConstraintKind::Unique(parse_column_list(iter, "UNIQUE")?),
+        },
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "CHECK" => {
+            match iter.next() {
+                Some(Token::Punctuation('(')) => {}
+                _ => return Err("Expected '(' after CHECK".to_string()),
+            }
+            let expression = parse_expression(iter)?;
+            match iter.next() {
+                Some(Token::Punctuation(')')) => {}
+                _ => return Err("Expected ')'".to_string()),
+            }
+            ConstraintKind::Check(expression)
+        }
+        Some(Token::Keyword(keyword))
+            if keyword.to_uppercase() == "FOREIGN" && column.is_none() =>
+        {
+            // KEY is not reserved so it can still name columns
+            match iter.next() {
+                Some(Token::Identifier(word)) if word.to_uppercase() == "KEY" => {}
+                _ => return Err("Expected 'KEY' after FOREIGN".to_string()),
+            }
+            let columns = parse_column_list(iter, "FOREIGN KEY")?;
+            if !is_keyword(iter.next(), "REFERENCES") {
+                return Err("Expected 'REFERENCES'".to_string());
+            }
+            ConstraintKind::ForeignKey(parse_references(iter, columns)?)
+        }
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "REFERENCES" => match column {
+            Some(column) => {
+                ConstraintKind::ForeignKey(parse_references(iter, vec![column.to_string()])?)
+            }
+            None => return Err("Expected FOREIGN KEY before REFERENCES".to_string()),
+        },
+        _ => return Err("Expected UNIQUE, CHECK or FOREIGN KEY".to_string()),
+    };
+
+    let name = name.unwrap_or_else(|| default_constraint_name(table_name, column, &kind, taken));
+    Ok(Constraint { name, kind })
+}
+
+/// Parses what follows "REFERENCES" for a key made of `columns`.
+fn parse_references<'a>(
+    iter: &mut TokenStream<'a>,
+    columns: Vec<String>,
+) -> Result<ForeignKey, String> {
+    let table = expect_identifier(iter, "referenced table name")?;
+    let referenced = parse_column_list(iter, "the referenced table")?;
+    if referenced.len() != columns.len() {
+        return Err(format!(
+            "Foreign key has {} columns but references {}",
+            columns.len(),
+            referenced.len()
+        ));
+    }
+
+    // Without DELETE there is nothing to cascade, so only RESTRICT and its
+    // synonym NO ACTION are accepted
+    if is_keyword(iter.peek(), "ON") {
+        iter.next(); // Consume "ON"
+        if !is_keyword(iter.next(), "DELETE") {
+            return Err("Expected 'DELETE' after ON".to_string());
+        }
+        let action = match iter.next() {
+            Some(Token::Keyword(keyword)) => match keyword.to_uppercase().as_str() {
+                "RESTRICT" => None,
+                "CASCADE" => Some("CASCADE"),
+                "SET" if is_keyword(iter.peek(), "NULL") => Some("SET NULL"),
+                "SET" if is_keyword(iter.peek(), "DEFAULT") => Some("SET DEFAULT"),
+                _ => return Err("Expected RESTRICT or NO ACTION".to_string()),
+            },
+            // ACTION is not reserved so it can still name columns
+            Some(Token::Identifier(word)) if word.to_uppercase() == "NO" => match iter.next() {
+                Some(Token::Identifier(word)) if word.to_uppercase() == "ACTION" => None,
+                _ => return Err("Expected 'ACTION' after NO".to_string()),
+            },
+            _ => return Err("Expected RESTRICT or NO ACTION".to_string()),
+        };
+        if let Some(action) = action {
+            return Err(format!(
+                "ON DELETE {} is not supported, only RESTRICT or NO ACTION",
+                action
+            ));
+        }
+    }
+    // Keys are checked at the end of every statement, and there are no
+    // transactions to defer them to
+    if matches!(iter.peek(), Some(Token::Identifier(word)) if word.to_uppercase() == "DEFERRABLE") {
+        return Err("DEFERRABLE foreign keys are not supported".to_string());
+    }
+
+    Ok(ForeignKey {
+        columns,
+        table,
+        referenced,
+    })
+}
+
+/// Parses `"(" <identifier> ("," <identifier>)* ")"`.
+fn parse_column_list<'a>(iter: &mut TokenStream<'a>, after: &str) -> Result<Vec<String>, String> {
+    match iter.next() {
+        Some(Token::Punctuation('(')) => {}
+        _ => return Err(format!("Expected '(' after {}", after)),
+    }
+    let mut columns = Vec::new();
+    loop {
+        columns.push(expect_identifier(iter, "column name")?);
+        match iter.next() {
+            Some(Token::Punctuation(',')) => continue,
+            Some(Token::Punctuation(')')) => break,
+            _ => return Err("Expected ',' or ')'".to_string()),
+        }
+    }
+    Ok(columns)
+}
+
+/// Names unnamed constraints like `t_a_b_key`, `t_a_check` and `t_a_fkey`, adding a number
+/// when the name is already taken.
+fn default_constraint_name(
+    table_name: &str,
+    column: Option<&str>,
+    kind: &ConstraintKind,
+    taken: &[Constraint],
+) -> String {
+    let base = match (kind, column) {
+        (ConstraintKind::Unique(columns), _) => format!("{}_{}_key", table_name, columns.join("_")),
+        (ConstraintKind::Check(_), Some(column)) => format!("{}_{}_check", table_name, column),
+        (ConstraintKind::Check(_), None) => format!("{}_check", table_name),
+        (ConstraintKind::ForeignKey(foreign_key), _) => {
+            format!("{}_{}_fkey", table_name, foreign_key.columns.join("_"))
+        }
+    };
+    let is_taken = |name: &str| taken.iter().any(|constraint| constraint.name == name);
+    if !is_taken(&base) {
+        return base;
+    }
+    (1..)
+        .map(|suffix| format!("{}{}", base, suffix))
+        .find(|name| !is_taken(name))
+        .unwrap()
+}
+
+/*
+```
+<alter_table> ::= "ALTER" "TABLE" <identifier> <alter_action>
+<alter_action> ::= "ADD" ["COLUMN"] <column_definition>
+                 | "ADD" <table_constraint>
+                 | "DROP" ["COLUMN"] <identifier>
+                 | "RENAME" ["COLUMN"] <identifier> "TO" <identifier>
+                 | "RENAME" "TO" <identifier>
+                 | "ALTER" ["COLUMN"] <identifier> "TYPE" <data_type>
+```
+*/
+
+pub fn parse_alter<'a>(iter: &mut TokenStream<'a>) -> Result<Statement, String> {
+    iter.next(); // Consume "ALTER"
+    match iter.next() {
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "TABLE" => {}
+        _ => return Err("Expected 'TABLE' after ALTER".to_string()),
+    }
+
+    let table_name = match iter.next() {
+        Some(Token::Identifier(name)) => name.clone(),
+        _ => return Err("Expected table name".to_string()),
+    };
+
+    let keyword = match iter.next() {
+        Some(Token::Keyword(keyword)) => keyword.to_uppercase(),
+        _ => return Err("Expected ADD, DROP, RENAME or ALTER".to_string()),
+    };
+    // A table constraint starts with a reserved word, which can't name a column
+    let adds_constraint = keyword == "ADD"
+        && ["CONSTRAINT", "UNIQUE", "CHECK", "FOREIGN"]
+            .iter()
+            .any(|expected| is_keyword(iter.peek(), expected));
+    // "RENAME TO" renames the table; otherwise "COLUMN" is optional
+    let renames_table = keyword == "RENAME" && is_keyword(iter.peek(), "TO");
+    if !renames_table && is_keyword(iter.peek(), "COLUMN") {
+        iter.next(); // Consume "COLUMN"
+    }
+
+    let action = match keyword.as_str() {
+        "ADD" if adds_constraint => {
+            AlterAction::AddConstraint(parse_constraint(iter, &table_name, None, &[])?)
+        }
+        "ADD" => {
+            let (column, constraints) = parse_column_definition(iter, &table_name, &[])?;
+            AlterAction::AddColumn {
+                column,
+                constraints,
+            }
+        }
+        "DROP" => AlterAction::DropColumn(expect_identifier(iter, "column name")?),
+        "RENAME" if renames_table => {
+            iter.next(); // Consume "TO"
+            AlterAction::RenameTable(expect_identifier(iter, "table name")?)
+        }
+        "RENAME" => {
+            let from = expect_identifier(iter, "column name")?;
+            if !is_keyword(iter.next(), "TO") {
+                return Err("Expected 'TO'".to_string());
+            }
+            AlterAction::RenameColumn {
+                from,
+                to: expect_identifier(iter, "column name")?,
+            }
+        }
+        "ALTER" => {
+            let column = expect_identifier(iter, "column name")?;
+            // TYPE is not reserved so it can still name columns
+            match iter.next() {
+                Some(Token::Identifier(word)) if word.to_uppercase() == "TYPE" => {}
+                _ => return Err("Expected 'TYPE'".to_string()),
+            }
+            AlterAction::AlterColumnType {
+                column,
+                data_type: parse_data_type(iter)?,
+            }
+        }
+        _ => return Err("Expected ADD, DROP, RENAME or ALTER".to_string()),
+    };
+
+    Ok(Statement::AlterTable(AlterTable { table_name, action }))
+}
+
+fn is_keyword(token: Option<&Token>, expected: &str) -> bool {
+    matches!(token, Some(Token::Keyword(keyword)) if keyword.to_uppercase() == expected)
+}
+
+fn expect_identifier<'a>(iter: &mut TokenStream<'a>, what: &str) -> Result<String, String> {
+    match iter.next() {
+        Some(Token::Identifier(name)) => Ok(name.clone()),
+        _ => Err(format!("Expected {}", what)),
+    }
+}
+
+pub fn parse_data_type<'a>(iter: &mut TokenStream<'a>) -> Result<DataType, String> {
+    let mut data_type = parse_element_type(iter)?;
+    // A size such as `INT[3]` is accepted but not enforced, as in PostgreSQL
+    while iter.peek() == Some(&Token::Punctuation('[')) {
+        iter.next(); // Consume "["
+        if let Some(Token::Numeric(_)) = iter.peek() {
+            iter.next();
+        }
+        if iter.next() != Some(&Token::Punctuation(']')) {
+            return Err("Expected ']' in array type".to_string());
+        }
+        data_type = DataType::Array(Box::new(data_type));
+    }
+    Ok(data_type)
+}
+
+fn parse_element_type<'a>(iter: &mut TokenStream<'a>) -> Result<DataType, String> {
     match iter.next() {
         Some(Token::DataType(data_type)) => match data_type.to_uppercase().as_str() {
+            "SMALLINT" => Ok(DataType::SmallInt),
             "INT" | "INTEGER" => Ok(DataType::Int),
+            "BIGINT" => Ok(DataType::BigInt),
             "DATE" => Ok(DataType::Date),
-            "FLOAT" => Ok(DataType::Float),
-            "CHAR" => Ok(DataType::Char),
+            "TIMESTAMP" => parse_timestamp_type(iter),
+            "TIMESTAMPTZ" => Ok(DataType::TimestampTz),
+            "TIME" => Ok(DataType::Time),
+            "INTERVAL" => Ok(DataType::Interval),
+            "FLOAT" | "REAL" => Ok(DataType::Float),
+            "DOUBLE" => {
+                // PRECISION is not reserved so it can still name columns
+                if matches!(iter.peek(), Some(Token::Identifier(word)) if word.to_uppercase() == "PRECISION")
+                {
+                    iter.next(); // Consume "PRECISION"
+                }
+                Ok(DataType::Double)
+            }
+            "DECIMAL" | "NUMERIC" => parse_decimal_type(iter),
+            "CHAR" => {
+                // The width is optional; CHAR alone holds one character
+                if !matches!(iter.peek(), Some(Token::Punctuation('('))) {
+                    return Ok(DataType::Char(1));
+                }
+                iter.next(); // Consume "("
+                let size = match iter.next() {
+                    Some(Token::Numeric(size)) => size
+                        .parse::<usize>()
+                        .map_err(|_| "Invalid size in CHAR".to_string())?,
+                    _ => return Err("Expected size in CHAR".to_string()),
+                };
+                match iter.next() {
+                    Some(Token::Punctuation(')')) => {}
+                    _ => return Err("Expected ')' after CHAR size".to_string()),
+                }
+                Ok(DataType::Char(size))
+            }
             "BOOLEAN" | "BOOL" => Ok(DataType::Boolean),
+            "TEXT" => Ok(DataType::Text),
+            "BLOB" | "BYTEA" => Ok(DataType::Blob),
+            "JSON" | "JSONB" => Ok(DataType::Json),
+            "UUID" => Ok(DataType::Uuid),
             "VARCHAR" => {
                 match iter.next() {
                     Some(Token::Punctuation('(')) => {}
@@ -115,40 +702,226 @@ fn parse_data_type<'a>(
             }
             _ => Err("Unknown data type".to_string()),
         },
+        // Any other name is an enum type, looked up when the statement runs
+        Some(Token::Identifier(name)) => Ok(DataType::Enum(Arc::new(EnumType {
+            name: name.clone(),
+            labels: Vec::new(),
+        }))),
         Some(_) => Err("Expected data type".to_string()),
         None => Err("Unexpected end of input".to_string()),
     }
 }
 
-pub fn parse_select<'a>(
-    iter: &mut Peekable<impl Iterator<Item = &'a Token>>,
-) -> Result<Statement, String> {
-    iter.next(); // Consume "SELECT"
+/// Parses the optional `WITH TIME ZONE` or `WITHOUT TIME ZONE` after
+/// TIMESTAMP. WITHOUT and ZONE are not reserved.
+fn parse_timestamp_type<'a>(iter: &mut TokenStream<'a>) -> Result<DataType, String> {
+    let with_time_zone = match iter.peek() {
+        Some(Token::Keyword(word)) if word.to_uppercase() == "WITH" => true,
+        Some(Token::Identifier(word)) if word.to_uppercase() == "WITHOUT" => false,
+        _ => return Ok(DataType::Timestamp),
+    };
+    iter.next(); // Consume "WITH" or "WITHOUT"
+    match (iter.next(), iter.next()) {
+        (Some(Token::DataType(time)), Some(Token::Identifier(zone)))
+            if time.to_uppercase() == "TIME" && zone.to_uppercase() == "ZONE" => {}
+        _ => return Err("Expected 'TIME ZONE' after TIMESTAMP WITH/WITHOUT".to_string()),
+    }
+    Ok(match with_time_zone {
+        true => DataType::TimestampTz,
+        false => DataType::Timestamp,
+    })
+}
 
-    let mut columns = Vec::new();
-    loop {
-        match iter.next() {
-            Some(Token::Identifier(column)) => columns.push(column.clone()),
-            Some(Token::Punctuation(',')) => continue,
-            Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "FROM" => break,
-            _ => return Err("Expected column name or 'FROM'".to_string()),
+/// Parses the optional `(precision [, scale])` after DECIMAL. Without one the
+/// type is NUMERIC, which keeps each value's own scale; a precision alone
+/// has a scale of 0.
+fn parse_decimal_type<'a>(iter: &mut TokenStream<'a>) -> Result<DataType, String> {
+    if iter.peek() != Some(&Token::Punctuation('(')) {
+        return Ok(DataType::Numeric);
+    }
+    iter.next(); // Consume "("
+
+    let parse_size = |iter: &mut TokenStream<'a>, what: &str| match iter.next() {
+        Some(Token::Numeric(size)) => size
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid {} in DECIMAL", what)),
+        _ => Err(format!("Expected {} in DECIMAL", what)),
+    };
+    let precision = parse_size(iter, "precision")?;
+    let scale = match iter.next() {
+        Some(Token::Punctuation(',')) => {
+            let scale = parse_size(iter, "scale")?;
+            match iter.next() {
+                Some(Token::Punctuation(')')) => scale,
+                _ => return Err("Expected ')' after DECIMAL scale".to_string()),
+            }
         }
+        Some(Token::Punctuation(')')) => 0,
+        _ => return Err("Expected ',' or ')' after DECIMAL precision".to_string()),
+    };
+
+    if !(1..=MAX_PRECISION).contains(&precision) {
+        return Err(format!(
+            "DECIMAL precision must be between 1 and {}",
+            MAX_PRECISION
+        ));
+    }
+    if scale > precision {
+        return Err("DECIMAL scale cannot exceed its precision".to_string());
     }
+    Ok(DataType::Decimal(precision, scale))
+}
 
-    let table_name = match iter.next() {
-        Some(Token::Identifier(name)) => name.clone(),
-        _ => return Err("Expected table name".to_string()),
+/*
+```
+<select> ::= "SELECT" <select_item> ("," <select_item>)* ["FROM" <from_item>]
+             ["WHERE" <expression>] ["ORDER" "BY" <order_item> ("," <order_item>)*]
+<select_item> ::= "*" | <expression> [["AS"] <identifier>]
+<from_item> ::= <identifier> | <identifier> "(" [<expression> ("," <expression>)*] ")"
+<order_item> ::= <expression> ["ASC" | "DESC"]
+```
+*/
+
+pub fn parse_select<'a>(iter: &mut TokenStream<'a>) -> Result<Statement, String> {
+    parse_select_query(iter).map(Statement::Select)
+}
+
+fn parse_select_query<'a>(iter: &mut TokenStream<'a>) -> Result<Select, String> {
+    iter.next(); // Consume "SELECT"
+
+    let columns = parse_select_items(iter)?;
+
+    let from = match iter.peek() {
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "FROM" => {
+            iter.next(); // Consume "FROM"
+            let name = match iter.next() {
+                Some(Token::Identifier(name)) => name.clone(),
+                _ => return Err("Expected table name".to_string()),
+            };
+            match iter.peek() {
+                Some(Token::Punctuation('(')) => {
+                    iter.next(); // Consume "("
+                    let args = match iter.peek() {
+                        Some(Token::Punctuation(')')) => {
+                            iter.next();
+                            Vec::new()
+                        }
+                        _ => parse_expression_list(iter)?,
+                    };
+                    Some(FromItem::Function { name, args })
+                }
+                _ => Some(FromItem::Table(name)),
+            }
+        }
+        _ => None,
+    };
+
+    let where_clause = match iter.peek() {
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "WHERE" => {
+            iter.next(); // Consume "WHERE"
+            Some(parse_expression(iter)?)
+        }
+        _ => None,
     };
 
-    Ok(Statement::Select(Select {
+    let mut order_by = Vec::new();
+    if let Some(Token::Keyword(keyword)) = iter.peek() {
+        if keyword.to_uppercase() == "ORDER" {
+            iter.next(); // Consume "ORDER"
+            match iter.next() {
+                Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "BY" => {}
+                _ => return Err("Expected 'BY' after ORDER".to_string()),
+            }
+            loop {
+                let expression = parse_expression(iter)?;
+                // ASC and DESC are not reserved so they can still name columns
+                let descending = match iter.peek() {
+                    Some(Token::Identifier(direction)) if direction.to_uppercase() == "ASC" => {
+                        iter.next();
+                        false
+                    }
+                    Some(Token::Identifier(direction)) if direction.to_uppercase() == "DESC" => {
+                        iter.next();
+                        true
+                    }
+                    _ => false,
+                };
+                order_by.push(OrderBy {
+                    expression,
+                    descending,
+                });
+
+                match iter.peek() {
+                    Some(Token::Punctuation(',')) => {
+                        iter.next(); // Consume ","
+                    }
+                    _ => break,
+                }
+            }
+        }
+    }
+
+    Ok(Select {
         columns,
-        table_name,
-    }))
+        from,
+        where_clause,
+        order_by,
+    })
+}
+
+/// Parses `<select_item> ("," <select_item>)*`, shared by SELECT and RETURNING.
+fn parse_select_items<'a>(iter: &mut TokenStream<'a>) -> Result<Vec<SelectItem>, String> {
+    let mut columns = Vec::new();
+    loop {
+        match iter.peek() {
+            Some(Token::Operator(op)) if op == "*" => {
+                iter.next(); // Consume "*"
+                columns.push(SelectItem::Wildcard);
+            }
+            _ => {
+                let expression = parse_expression(iter)?;
+                let alias = match iter.peek() {
+                    Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "AS" => {
+                        iter.next(); // Consume "AS"
+                        match iter.next() {
+                            Some(Token::Identifier(alias)) => Some(alias.clone()),
+                            _ => return Err("Expected alias after AS".to_string()),
+                        }
+                    }
+                    Some(Token::Identifier(alias)) => {
+                        iter.next();
+                        Some(alias.clone())
+                    }
+                    _ => None,
+                };
+                columns.push(SelectItem::Expression { expression, alias });
+            }
+        }
+
+        match iter.peek() {
+            Some(Token::Punctuation(',')) => {
+                iter.next(); // Consume ","
+            }
+            _ => break,
+        }
+    }
+    Ok(columns)
 }
 
-pub fn parse_insert<'a>(
-    iter: &mut Peekable<impl Iterator<Item = &'a Token>>,
-) -> Result<Statement, String> {
+/*
+```
+<insert> ::= "INSERT" "INTO" <identifier> ["(" <identifier> ("," <identifier>)* ")"]
+             ["OVERRIDING" "SYSTEM" "VALUE"] (["VALUES"] <row> ("," <row>)* | <select>) [<on_conflict>]
+             ["RETURNING" <select_item> ("," <select_item>)*]
+<row> ::= "(" <insert_value> ("," <insert_value>)* ")"
+<insert_value> ::= "DEFAULT" | <expression>
+<on_conflict> ::= "ON" "CONFLICT" "(" <identifier> ("," <identifier>)* ")"
+                  "DO" ("NOTHING" | "UPDATE" "SET" <assignment> ("," <assignment>)*)
+<assignment> ::= <identifier> "=" <expression>
+```
+*/
+
+pub fn parse_insert<'a>(iter: &mut TokenStream<'a>) -> Result<Statement, String> {
     iter.next(); // Consume "INSERT"
 
     match iter.next() {
@@ -161,32 +934,323 @@ pub fn parse_insert<'a>(
         _ => return Err("Expected table name".to_string()),
     };
 
+    // "VALUES" is optional, so a parenthesized list is only a column list
+    // when OVERRIDING, "VALUES" or a query follows it
+    let mut columns = None;
+    let mut rows = Vec::new();
+    let mut overriding = consume_overriding(iter)?;
+    if overriding {
+        consume_values_keyword(iter);
+    } else if !is_select(iter) && !consume_values_keyword(iter) {
+        let first = parse_insert_row(iter)?;
+        overriding = consume_overriding(iter)?;
+        if overriding {
+            consume_values_keyword(iter);
+        }
+        if overriding || is_select(iter) || consume_values_keyword(iter) {
+            let names = first
+                .into_iter()
+                .map(|value| match value {
+                    InsertValue::Expression(Expression::Column(name)) => Ok(name),
+                    _ => Err("Expected column name".to_string()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            columns = Some(names);
+        } else {
+            rows.push(first);
+        }
+    }
+
+    let source = if rows.is_empty() && is_select(iter) {
+        InsertSource::Select(parse_select_query(iter)?)
+    } else {
+        if rows.is_empty() {
+            rows.push(parse_insert_row(iter)?);
+        }
+        while let Some(Token::Punctuation(',')) = iter.peek() {
+            iter.next(); // Consume ","
+            rows.push(parse_insert_row(iter)?);
+        }
+        InsertSource::Values(rows)
+    };
+
+    let on_conflict = match iter.peek() {
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "ON" => {
+            Some(parse_on_conflict(iter)?)
+        }
+        _ => None,
+    };
+
+    let returning = match iter.peek() {
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "RETURNING" => {
+            iter.next(); // Consume "RETURNING"
+            Some(parse_select_items(iter)?)
+        }
+        _ => None,
+    };
+
+    Ok(Statement::Insert(Insert {
+        table_name,
+        columns,
+        overriding,
+        source,
+        on_conflict,
+        returning,
+    }))
+}
+
+fn parse_on_conflict<'a>(iter: &mut TokenStream<'a>) -> Result<OnConflict, String> {
+    iter.next(); // Consume "ON"
+    match iter.next() {
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "CONFLICT" => {}
+        _ => return Err("Expected 'CONFLICT' after ON".to_string()),
+    }
+
     match iter.next() {
         Some(Token::Punctuation('(')) => {}
-        _ => return Err("Expected '('".to_string()),
+        _ => return Err("Expected '(' and conflict columns".to_string()),
+    }
+    let mut target = Vec::new();
+    loop {
+        match iter.next() {
+            Some(Token::Identifier(name)) => target.push(name.clone()),
+            _ => return Err("Expected column name".to_string()),
+        }
+        match iter.next() {
+            Some(Token::Punctuation(',')) => continue,
+            Some(Token::Punctuation(')')) => break,
+            _ => return Err("Expected ',' or ')'".to_string()),
+        }
     }
 
-    let mut values = Vec::new();
+    match iter.next() {
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "DO" => {}
+        _ => return Err("Expected 'DO'".to_string()),
+    }
+    let action = match iter.next() {
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "NOTHING" => {
+            ConflictAction::Nothing
+        }
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "UPDATE" => {
+            match iter.next() {
+                Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "SET" => {}
+                _ => return Err("Expected 'SET' after DO UPDATE".to_string()),
+            }
+            ConflictAction::Update(parse_assignments(iter)?)
+        }
+        _ => return Err("Expected 'NOTHING' or 'UPDATE' after DO".to_string()),
+    };
+
+    Ok(OnConflict { target, action })
+}
+
+fn parse_assignments<'a>(iter: &mut TokenStream<'a>) -> Result<Vec<Assignment>, String> {
+    let mut assignments = Vec::new();
     loop {
+        let column = match iter.next() {
+            Some(Token::Identifier(name)) => name.clone(),
+            _ => return Err("Expected column name".to_string()),
+        };
         match iter.next() {
-            Some(Token::Literal(value)) => values.push(value.clone()),
-            Some(Token::Numeric(value)) => values.push(value.clone()),
-            Some(token) => return Err(format!("Expected value, got {:?}", token)),
-            _ => return Err("Unexpected end for value".to_string()),
+            Some(Token::Operator(op)) if op == "=" => {}
+            _ => return Err("Expected '=' after column name".to_string()),
         }
+        assignments.push(Assignment {
+            column,
+            value: parse_expression(iter)?,
+        });
 
         match iter.peek() {
             Some(Token::Punctuation(',')) => {
                 iter.next(); // Consume ","
-                continue;
             }
-            Some(Token::Punctuation(')')) => {
-                iter.next(); // Consume ")"
-                break;
+            _ => break,
+        }
+    }
+    Ok(assignments)
+}
+
+fn is_select<'a>(iter: &mut TokenStream<'a>) -> bool {
+    matches!(iter.peek(), Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "SELECT")
+}
+
+/// Consumes `OVERRIDING SYSTEM VALUE` if it comes next. None of these words
+/// are reserved.
+fn consume_overriding<'a>(iter: &mut TokenStream<'a>) -> Result<bool, String> {
+    match iter.peek() {
+        Some(Token::Identifier(word)) if word.to_uppercase() == "OVERRIDING" => {}
+        _ => return Ok(false),
+    }
+    iter.next(); // Consume "OVERRIDING"
+    for expected in ["SYSTEM", "VALUE"] {
+        match iter.next() {
+            Some(Token::Identifier(word)) if word.to_uppercase() == expected => {}
+            _ => return Err(format!("Expected '{}'", expected)),
+        }
+    }
+    Ok(true)
+}
+
+fn consume_values_keyword<'a>(iter: &mut TokenStream<'a>) -> bool {
+    match iter.peek() {
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "VALUES" => {
+            iter.next(); // Consume "VALUES"
+            true
+        }
+        _ => false,
+    }
+}
+
+fn parse_insert_row<'a>(iter: &mut TokenStream<'a>) -> Result<Vec<InsertValue>, String> {
+    match iter.next() {
+        Some(Token::Punctuation('(')) => {}
+        _ => return Err("Expected '('".to_string()),
+    }
+
+    let mut values = Vec::new();
+    loop {
+        match iter.peek() {
+            Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "DEFAULT" => {
+                iter.next(); // Consume "DEFAULT"
+                values.push(InsertValue::Default);
             }
+            _ => values.push(InsertValue::Expression(parse_expression(iter)?)),
+        }
+        match iter.next() {
+            Some(Token::Punctuation(',')) => continue,
+            Some(Token::Punctuation(')')) => break,
             _ => return Err("Expected ',' or ')'".to_string()),
         }
     }
+    Ok(values)
+}
+
+/*
+```
+<copy> ::= "COPY" <identifier> ("FROM" | "TO") <literal> ["WITH" "(" <copy_option> ("," <copy_option>)* ")"]
+<copy_option> ::= "FORMAT" "csv" | "HEADER" ["TRUE" | "FALSE"]
+```
+*/
+
+pub fn parse_copy<'a>(iter: &mut TokenStream<'a>) -> Result<Statement, String> {
+    iter.next(); // Consume "COPY"
+
+    let table_name = match iter.next() {
+        Some(Token::Identifier(name)) => name.clone(),
+        _ => return Err("Expected table name".to_string()),
+    };
+
+    let direction = match iter.next() {
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "FROM" => CopyDirection::From,
+        Some(Token::Keyword(keyword)) if keyword.to_uppercase() == "TO" => CopyDirection::To,
+        _ => return Err("Expected 'FROM' or 'TO'".to_string()),
+    };
+
+    let file_name = match iter.next() {
+        Some(Token::Literal(name)) => name.clone(),
+        _ => return Err("Expected quoted file name".to_string()),
+    };
 
-    Ok(Statement::Insert(Insert { table_name, values }))
+    let mut header = false;
+    if let Some(Token::Keyword(keyword)) = iter.peek() {
+        if keyword.to_uppercase() == "WITH" {
+            iter.next(); // Consume "WITH"
+            match iter.next() {
+                Some(Token::Punctuation('(')) => {}
+                _ => return Err("Expected '(' after WITH".to_string()),
+            }
+
+            loop {
+                match iter.next() {
+                    Some(Token::Identifier(option)) if option.to_uppercase() == "FORMAT" => {
+                        match iter.next() {
+                            Some(Token::Identifier(format)) if format.to_uppercase() == "CSV" => {}
+                            _ => return Err("Only FORMAT csv is supported".to_string()),
+                        }
+                    }
+                    Some(Token::Identifier(option)) if option.to_uppercase() == "HEADER" => {
+                        header = match iter.peek() {
+                            Some(Token::Keyword(value))
+                                if matches!(value.to_uppercase().as_str(), "TRUE" | "FALSE") =>
+                            {
+                                iter.next();
+                                value.to_uppercase() == "TRUE"
+                            }
+                            _ => true,
+                        };
+                    }
+                    _ => return Err("Expected COPY option".to_string()),
+                }
+
+                match iter.next() {
+                    Some(Token::Punctuation(',')) => continue,
+                    Some(Token::Punctuation(')')) => break,
+                    _ => return Err("Expected ',' or ')'".to_string()),
+                }
+            }
+        }
+    }
+
+    Ok(Statement::Copy(Copy {
+        table_name,
+        direction,
+        file_name,
+        header,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::statement::{Copy, CopyDirection, Statement};
+    use crate::parser::parse_statement;
+
+    fn copy(text: &str) -> Result<Copy, String> {
+        match parse_statement(text) {
+            Ok(Statement::Copy(copy)) => Ok(copy),
+            Ok(other) => panic!("unexpected statement: {}", other),
+            Err(e) => Err(e.message),
+        }
+    }
+
+    #[test]
+    fn copy_options() {
+        let plain = copy("COPY t FROM 'in.csv'").unwrap();
+        assert_eq!(plain.table_name, "t");
+        assert_eq!(plain.direction, CopyDirection::From);
+        assert_eq!(plain.file_name, "in.csv");
+        assert!(!plain.header);
+
+        // HEADER alone means HEADER TRUE, and the last option wins
+        let with_header = copy("COPY t TO 'out.csv' WITH (FORMAT csv, HEADER)").unwrap();
+        assert_eq!(with_header.direction, CopyDirection::To);
+        assert!(with_header.header);
+        assert!(
+            copy("COPY t TO 'out.csv' WITH (header true)")
+                .unwrap()
+                .header
+        );
+        assert!(
+            !copy("COPY t TO 'out.csv' WITH (HEADER FALSE)")
+                .unwrap()
+                .header
+        );
+        assert!(
+            !copy("COPY t TO 'o' WITH (HEADER, HEADER FALSE)")
+                .unwrap()
+                .header
+        );
+
+        assert_eq!(
+            copy("COPY t FROM 'in.json' WITH (FORMAT json)").err(),
+            Some("Only FORMAT csv is supported".to_string())
+        );
+        assert_eq!(
+            copy("COPY t FROM in.csv").err(),
+            Some("Expected quoted file name".to_string())
+        );
+        assert_eq!(
+            copy("COPY t FROM 'in.csv' WITH (HEADER").err(),
+            Some("Expected ',' or ')'".to_string())
+        );
+    }
 }
@@ -0,0 +1,98 @@
+use super::token::{Span, SpannedToken, Token};
+
+/// Iterator over the tokens of a statement that remembers how far the parser
+/// has looked, so errors can point at the offending token.
+pub struct TokenStream<'a> {
+    tokens: &'a [SpannedToken],
+    position: usize,
+    furthest: usize,
+    end: Span,
+}
+
+impl<'a> TokenStream<'a> {
+    pub fn new(tokens: &'a [SpannedToken], end: Span) -> Self {
+        TokenStream {
+            tokens,
+            position: 0,
+            furthest: 0,
+            end,
+        }
+    }
+
+    pub fn peek(&mut self) -> Option<&'a Token> {
+        self.furthest = self.furthest.max(self.position);
+        self.tokens.get(self.position).map(|t| &t.token)
+    }
+
+    /// Looks one token past `peek` without consuming anything.
+    pub fn peek_second(&mut self) -> Option<&'a Token> {
+        self.furthest = self.furthest.max(self.position + 1);
+        self.tokens.get(self.position + 1).map(|t| &t.token)
+    }
+
+    /// Span of the next token, or the end of input past the last token.
+    pub fn next_span(&self) -> Span {
+        self.tokens
+            .get(self.position)
+            .map(|t| t.span)
+            .unwrap_or(self.end)
+    }
+
+    /// Span of the furthest token examined, or the end of input past the last token.
+    pub fn error_span(&self) -> Span {
+        self.tokens
+            .get(self.furthest)
+            .map(|t| t.span)
+            .unwrap_or(self.end)
+    }
+}
+
+impl<'a> Iterator for TokenStream<'a> {
+    type Item = &'a Token;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.furthest = self.furthest.max(self.position);
+        let token = self.tokens.get(self.position)?;
+        self.position += 1;
+        Some(&token.token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_statement, tokenize};
+
+    #[test]
+    fn error_span_is_the_furthest_token_examined() {
+        let text = "SELECT a\nFROM t";
+        let tokens = tokenize(text).unwrap();
+        let mut iter = TokenStream::new(&tokens, Span::end_of(text));
+        assert_eq!(iter.error_span(), tokens[0].span);
+
+        iter.next();
+        // A peek that the parser turns down still counts as looked at
+        iter.peek_second();
+        assert_eq!(iter.error_span(), tokens[2].span);
+        iter.peek();
+        assert_eq!(iter.error_span(), tokens[2].span);
+
+        for _ in iter.by_ref() {}
+        let end = iter.error_span();
+        assert_eq!((end.start, end.line, end.column), (text.len(), 2, 7));
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_rejected_token() {
+        fn span(text: &str) -> (&str, usize) {
+            let error = parse_statement(text).unwrap_err();
+            (&text[error.span.start..error.span.end], error.span.line)
+        }
+        assert_eq!(span("SELECT a FROM t WHERE a = 1 ORDER x"), ("x", 1));
+        assert_eq!(
+            span("CREATE TABLE t (\n  a INT,\n  b VARCHAR(x)\n)"),
+            ("x", 3)
+        );
+        assert_eq!(span("INSERT INTO t VALUES (1,"), ("", 1));
+    }
+}
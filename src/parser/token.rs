@@ -5,6 +5,36 @@ pub enum Token {
     DataType(String),
     Punctuation(char),
     Literal(String),
+    HexLiteral(Vec<u8>),
     Operator(String),
     Numeric(String),
 }
+
+/// Byte range of a token within the source statement, plus the 1-based line
+/// and column (in characters) where it starts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    /// Empty span just past the last character of `source`.
+    pub fn end_of(source: &str) -> Span {
+        let line_start = source.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Span {
+            start: source.len(),
+            end: source.len(),
+            line: source.matches('\n').count() + 1,
+            column: source[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
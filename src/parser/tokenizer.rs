@@ -1,114 +1,215 @@
-use super::token::Token;
+use super::error::{ParseError, ParseErrorKind};
+use super::token::{Span, SpannedToken, Token};
+
+pub const KEYWORDS: &[&str] = &[
+    "CREATE",
+    "TABLE",
+    "SELECT",
+    "INSERT",
+    "UPDATE",
+    "DELETE",
+    "FROM",
+    "WHERE",
+    "AND",
+    "OR",
+    "NOT",
+    "IN",
+    "INTO",
+    "VALUES",
+    "SET",
+    "JOIN",
+    "ON",
+    "AS",
+    "ORDER",
+    "BY",
+    "GROUP",
+    "HAVING",
+    "LIMIT",
+    "OFFSET",
+    "DISTINCT",
+    "ALTER",
+    "DROP",
+    "ADD",
+    "COLUMN",
+    "INDEX",
+    "VIEW",
+    "TRIGGER",
+    "PROCEDURE",
+    "FUNCTION",
+    "DATABASE",
+    "SCHEMA",
+    "USE",
+    "SHOW",
+    "DESCRIBE",
+    "EXPLAIN",
+    "COPY",
+    "TO",
+    "WITH",
+    "TRUE",
+    "FALSE",
+    "BETWEEN",
+    "LIKE",
+    "IS",
+    "NULL",
+    "DEFAULT",
+    "CONFLICT",
+    "DO",
+    "NOTHING",
+    "RETURNING",
+    "RENAME",
+    "CONSTRAINT",
+    "UNIQUE",
+    "CHECK",
+    "FOREIGN",
+    "REFERENCES",
+    "CASCADE",
+    "RESTRICT",
+];
+
+pub const DATA_TYPES: &[&str] = &[
+    "SMALLINT",
+    "INT",
+    "INTEGER",
+    "BIGINT",
+    "VARCHAR",
+    "CHAR",
+    "TEXT",
+    "BLOB",
+    "BYTEA",
+    "DATE",
+    "TIMESTAMP",
+    "TIMESTAMPTZ",
+    "TIME",
+    "INTERVAL",
+    "JSON",
+    "JSONB",
+    "UUID",
+    "BOOL",
+    "BOOLEAN",
+    "FLOAT",
+    "REAL",
+    "DOUBLE",
+    "DECIMAL",
+    "NUMERIC",
+];
 
 struct Tokenizer<'a> {
-    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    source: &'a str,
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    line: usize,
+    line_start: usize,
+}
+
+/// Where a token starts: byte offset plus 1-based line and column.
+#[derive(Clone, Copy)]
+struct Position {
+    offset: usize,
+    line: usize,
+    column: usize,
 }
 
 impl<'a> Tokenizer<'a> {
     fn new(statement: &'a str) -> Self {
         Tokenizer {
-            chars: statement.chars().peekable(),
+            source: statement,
+            chars: statement.char_indices().peekable(),
+            line: 1,
+            line_start: 0,
         }
     }
 
-    fn is_keyword(word: &str) -> bool {
-        matches!(
-            word,
-            "CREATE"
-                | "TABLE"
-                | "SELECT"
-                | "INSERT"
-                | "UPDATE"
-                | "DELETE"
-                | "FROM"
-                | "WHERE"
-                | "AND"
-                | "OR"
-                | "NOT"
-                | "IN"
-                | "INTO"
-                | "VALUES"
-                | "SET"
-                | "JOIN"
-                | "ON"
-                | "AS"
-                | "ORDER"
-                | "BY"
-                | "GROUP"
-                | "HAVING"
-                | "LIMIT"
-                | "OFFSET"
-                | "DISTINCT"
-                | "ALTER"
-                | "DROP"
-                | "ADD"
-                | "COLUMN"
-                | "INDEX"
-                | "VIEW"
-                | "TRIGGER"
-                | "PROCEDURE"
-                | "FUNCTION"
-                | "DATABASE"
-                | "SCHEMA"
-                | "USE"
-                | "SHOW"
-                | "DESCRIBE"
-                | "EXPLAIN"
-        )
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|&(_, ch)| ch)
     }
 
-    fn is_data_type(word: &str) -> bool {
-        matches!(
-            word,
-            "INT"
-                | "INTEGER"
-                | "VARCHAR"
-                | "CHAR"
-                | "TEXT"
-                | "DATE"
-                | "TIMESTAMP"
-                | "BOOL"
-                | "BOOLEAN"
-                | "FLOAT"
-                | "DOUBLE"
-                | "DECIMAL"
-        )
+    fn bump(&mut self) -> Option<char> {
+        let (index, ch) = self.chars.next()?;
+        if ch == '\n' {
+            self.line += 1;
+            self.line_start = index + 1;
+        }
+        Some(ch)
     }
 
-    fn is_next_char_digit(&mut self) -> bool {
-        if let Some(&next_ch) = self.chars.peek() {
-            next_ch.is_numeric()
-        } else {
-            false
+    /// The character after the next one, without consuming anything.
+    fn peek_second(&mut self) -> Option<char> {
+        let offset = self.offset();
+        self.source[offset..].chars().nth(1)
+    }
+
+    /// Byte offset of the next unread character.
+    fn offset(&mut self) -> usize {
+        self.chars
+            .peek()
+            .map(|&(index, _)| index)
+            .unwrap_or(self.source.len())
+    }
+
+    fn position(&mut self) -> Position {
+        let offset = self.offset();
+        Position {
+            offset,
+            line: self.line,
+            column: self.source[self.line_start..offset].chars().count() + 1,
         }
     }
 
-    fn is_bool_literal(word: &str) -> bool {
-        matches!(word, "TRUE" | "FALSE")
+    fn span_from(&mut self, start: Position) -> Span {
+        Span {
+            start: start.offset,
+            end: self.offset(),
+            line: start.line,
+            column: start.column,
+        }
     }
 
-    fn parse_numeric(&mut self, initial_char: Option<char>) -> String {
-        let mut number = String::new();
-        if let Some(ch) = initial_char {
-            number.push(ch);
+    fn error(&mut self, message: String, start: Position) -> ParseError {
+        ParseError {
+            kind: ParseErrorKind::Syntax,
+            message,
+            span: self.span_from(start),
         }
-        while let Some(&ch) = self.chars.peek() {
+    }
+
+    /// Error for `what` still being open at the end of the input.
+    fn unterminated(&mut self, what: &str, start: Position) -> ParseError {
+        ParseError {
+            kind: ParseErrorKind::Unterminated,
+            message: format!("Unterminated {}", what),
+            span: self.span_from(start),
+        }
+    }
+
+    fn is_keyword(word: &str) -> bool {
+        KEYWORDS.contains(&word)
+    }
+
+    fn is_data_type(word: &str) -> bool {
+        DATA_TYPES.contains(&word)
+    }
+
+    fn parse_numeric(&mut self, start: Position) -> Result<String, ParseError> {
+        let mut number = String::new();
+        while let Some(ch) = self.peek() {
             if ch.is_numeric() || ch == '.' {
                 number.push(ch);
-                self.chars.next();
+                self.bump();
             } else {
                 break;
             }
         }
-        number
+        if number.matches('.').count() > 1 {
+            return Err(self.error(format!("Malformed number '{}'", number), start));
+        }
+        Ok(number)
     }
 
     fn parse_identifier_or_keyword(&mut self) -> String {
         let mut ident = String::new();
-        while let Some(&ch) = self.chars.peek() {
+        while let Some(ch) = self.peek() {
             if ch.is_alphanumeric() || ch == '_' {
                 ident.push(ch);
-                self.chars.next();
+                self.bump();
             } else {
                 break;
             }
@@ -116,91 +217,453 @@ impl<'a> Tokenizer<'a> {
         ident
     }
 
-    fn parse_literal(&mut self, quote: char) -> String {
+    fn parse_literal(&mut self, quote: char, start: Position) -> Result<String, ParseError> {
         let mut literal = String::new();
-        while let Some(&ch) = self.chars.peek() {
+        while let Some(ch) = self.bump() {
             if ch == quote {
-                self.chars.next(); // consume the closing quote
-                break;
+                if self.peek() != Some(quote) {
+                    return Ok(literal);
+                }
+                // A doubled quote is an escaped quote character
+                literal.push(quote);
+                self.bump();
             } else {
                 literal.push(ch);
-                self.chars.next();
             }
         }
-        literal
+        Err(self.unterminated("string literal", start))
+    }
+
+    /// Parses the body of an `E'...'` string, decoding backslash escapes.
+    fn parse_escape_literal(&mut self, start: Position) -> Result<String, ParseError> {
+        let mut literal = String::new();
+        while let Some(ch) = self.bump() {
+            match ch {
+                '\'' if self.peek() == Some('\'') => {
+                    literal.push('\'');
+                    self.bump();
+                }
+                '\'' => return Ok(literal),
+                '\\' => {
+                    let escape_start = self.position();
+                    let decoded = match self.bump() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('0') => '\0',
+                        Some('x') => self.parse_code_point(2, escape_start)?,
+                        Some('u') => self.parse_code_point(4, escape_start)?,
+                        Some(other) => other,
+                        None => break,
+                    };
+                    literal.push(decoded);
+                }
+                _ => literal.push(ch),
+            }
+        }
+        Err(self.unterminated("string literal", start))
+    }
+
+    fn parse_code_point(&mut self, digits: usize, start: Position) -> Result<char, ParseError> {
+        let mut hex = String::new();
+        for _ in 0..digits {
+            match self.peek() {
+                Some(ch) if ch.is_ascii_hexdigit() => {
+                    hex.push(ch);
+                    self.bump();
+                }
+                _ => break,
+            }
+        }
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error("Invalid escape sequence".to_string(), start))
+    }
+
+    /// Parses the body of an `X'...'` literal into bytes.
+    fn parse_hex_literal(&mut self, start: Position) -> Result<Vec<u8>, ParseError> {
+        let mut digits = String::new();
+        loop {
+            match self.bump() {
+                Some('\'') => break,
+                Some(ch) if ch.is_ascii_hexdigit() => digits.push(ch),
+                Some(ch) => return Err(self.error(format!("Invalid hex digit '{}'", ch), start)),
+                None => return Err(self.unterminated("hex literal", start)),
+            }
+        }
+        if !digits.len().is_multiple_of(2) {
+            return Err(self.error(
+                "Hex literal must have an even number of digits".to_string(),
+                start,
+            ));
+        }
+        Ok((0..digits.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap())
+            .collect())
+    }
+
+    /// Parses the body of a `"..."` identifier, where `""` stands for one quote.
+    fn parse_quoted_identifier(&mut self, start: Position) -> Result<String, ParseError> {
+        let mut ident = String::new();
+        while let Some(ch) = self.bump() {
+            if ch == '"' {
+                if self.peek() != Some('"') {
+                    if ident.is_empty() {
+                        return Err(self.error("Zero-length quoted identifier".to_string(), start));
+                    }
+                    return Ok(ident);
+                }
+                ident.push('"');
+                self.bump();
+            } else {
+                ident.push(ch);
+            }
+        }
+        Err(self.unterminated("quoted identifier", start))
+    }
+
+    fn skip_line_comment(&mut self) {
+        while let Some(ch) = self.bump() {
+            if ch == '\n' {
+                break;
+            }
+        }
     }
 
-    fn parse_operator(&mut self, initial_char: char) -> String {
+    /// Skips a block comment; nested `/* */` pairs must balance.
+    fn skip_block_comment(&mut self, start: Position) -> Result<(), ParseError> {
+        let mut depth = 0;
+        while let Some(ch) = self.bump() {
+            match (ch, self.peek()) {
+                ('/', Some('*')) => {
+                    self.bump();
+                    depth += 1;
+                }
+                ('*', Some('/')) => {
+                    self.bump();
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(self.unterminated("block comment", start))
+    }
+
+    /// Completes "->" or "#>", taking a second '>' for the text form.
+    fn json_arrow(&mut self, arrow: &str) -> String {
+        match self.peek() {
+            Some('>') => {
+                self.bump();
+                format!("{}>", arrow)
+            }
+            _ => arrow.to_string(),
+        }
+    }
+
+    /// Parses comparison operators: `=`, `<`, `>`, `<=`, `>=`, `<>` and `!=`.
+    fn parse_operator(
+        &mut self,
+        initial_char: char,
+        start: Position,
+    ) -> Result<String, ParseError> {
         let mut op = String::new();
         op.push(initial_char);
-        self.chars.next();
-        if let Some(&next_ch) = self.chars.peek() {
-            if next_ch == '=' {
-                op.push(next_ch);
-                self.chars.next();
+        self.bump();
+        match (initial_char, self.peek()) {
+            ('=', _) => {}
+            ('<', Some('>')) | (_, Some('=')) => {
+                op.push(self.bump().unwrap());
             }
+            ('!', _) => return Err(self.error("Unexpected character '!'".to_string(), start)),
+            _ => {}
         }
-        op
+        Ok(op)
     }
 
-    fn tokenize(&mut self) -> Vec<Token> {
-        let mut tokens = Vec::new();
-
-        while let Some(&ch) = self.chars.peek() {
-            match ch {
-                ' ' | '\t' | '\n' => {
-                    self.chars.next();
+    /// Pushes tokens onto `tokens` until the input ends or an error is found,
+    /// so the tokens before an error are kept.
+    fn tokenize_into(&mut self, tokens: &mut Vec<SpannedToken>) -> Result<(), ParseError> {
+        while let Some(ch) = self.peek() {
+            let start = self.position();
+            let token = match ch {
+                ' ' | '\t' | '\n' | '\r' => {
+                    self.bump();
+                    continue;
+                }
+                '(' | ')' | '[' | ']' | ',' | ';' | '.' => {
+                    self.bump();
+                    Token::Punctuation(ch)
+                }
+                '=' | '<' | '>' | '!' => Token::Operator(self.parse_operator(ch, start)?),
+                '+' | '*' | '%' => {
+                    self.bump();
+                    Token::Operator(ch.to_string())
+                }
+                '|' if self.peek_second() == Some('|') => {
+                    self.bump();
+                    self.bump();
+                    Token::Operator(String::from("||"))
+                }
+                // The cast operator "::"; ':' alone is not an operator
+                ':' if self.peek_second() == Some(':') => {
+                    self.bump();
+                    self.bump();
+                    Token::Operator(String::from("::"))
+                }
+                // JSON path operators "#>" and "#>>"; '#' alone is not an operator
+                '#' if self.peek_second() == Some('>') => {
+                    self.bump();
+                    self.bump();
+                    Token::Operator(self.json_arrow("#>"))
+                }
+                '\'' => {
+                    self.bump(); // consume the opening quote
+                    Token::Literal(self.parse_literal(ch, start)?)
                 }
-                '(' | ')' | ',' | ';' | '.' => {
-                    tokens.push(Token::Punctuation(ch));
-                    self.chars.next();
+                '"' => {
+                    self.bump(); // consume the opening quote
+                    Token::Identifier(self.parse_quoted_identifier(start)?)
                 }
-                '=' | '<' | '>' | '!' => {
-                    let op = self.parse_operator(ch);
-                    tokens.push(Token::Operator(op));
+                '/' if self.peek_second() == Some('*') => {
+                    self.skip_block_comment(start)?;
+                    continue;
                 }
-                '\'' | '"' => {
-                    self.chars.next(); // consume the opening quote
-                    let literal = self.parse_literal(ch);
-                    tokens.push(Token::Literal(literal));
+                '/' => {
+                    self.bump();
+                    Token::Operator(String::from("/"))
+                }
+                'E' | 'e' if self.peek_second() == Some('\'') => {
+                    self.bump(); // consume the prefix
+                    self.bump(); // consume the opening quote
+                    Token::Literal(self.parse_escape_literal(start)?)
+                }
+                'X' | 'x' if self.peek_second() == Some('\'') => {
+                    self.bump(); // consume the prefix
+                    self.bump(); // consume the opening quote
+                    Token::HexLiteral(self.parse_hex_literal(start)?)
                 }
                 '-' => {
-                    self.chars.next(); // consume the '-'
-                    if self.is_next_char_digit() {
-                        let number = self.parse_numeric(Some('-'));
-                        tokens.push(Token::Numeric(number));
+                    self.bump(); // consume the '-'
+                    if self.peek() == Some('-') {
+                        self.skip_line_comment();
+                        continue;
+                    }
+                    if self.peek() == Some('>') {
+                        self.bump();
+                        Token::Operator(self.json_arrow("->"))
                     } else {
-                        tokens.push(Token::Operator(String::from("-")));
+                        // Negative numbers are a unary minus applied by the parser
+                        Token::Operator(String::from("-"))
                     }
                 }
-                _ if ch.is_alphabetic() => {
+                _ if ch.is_alphabetic() || ch == '_' => {
                     let ident = self.parse_identifier_or_keyword();
                     let upper_ident = ident.to_uppercase();
                     if Tokenizer::is_keyword(&upper_ident) {
-                        tokens.push(Token::Keyword(ident));
+                        Token::Keyword(ident)
                     } else if Tokenizer::is_data_type(&upper_ident) {
-                        tokens.push(Token::DataType(ident));
-                    } else if Tokenizer::is_bool_literal(&upper_ident) {
-                        tokens.push(Token::Literal(ident));
+                        Token::DataType(ident)
                     } else {
-                        tokens.push(Token::Identifier(ident));
+                        Token::Identifier(ident)
                     }
                 }
-                _ if ch.is_numeric() => {
-                    let number = self.parse_numeric(None);
-                    tokens.push(Token::Numeric(number));
-                }
+                _ if ch.is_numeric() => Token::Numeric(self.parse_numeric(start)?),
                 _ => {
-                    self.chars.next();
+                    self.bump();
+                    return Err(self.error(format!("Unexpected character '{}'", ch), start));
                 }
-            }
+            };
+
+            let span = self.span_from(start);
+            tokens.push(SpannedToken { token, span });
         }
 
-        tokens
+        Ok(())
     }
 }
 
-pub fn tokenize(statement: &str) -> Vec<Token> {
-    let mut tokenizer = Tokenizer::new(statement);
-    tokenizer.tokenize()
+/// Quotes `name` when it would not otherwise tokenize back to the same identifier.
+pub fn quote_identifier(name: &str) -> String {
+    let upper = name.to_uppercase();
+    let plain = name
+        .chars()
+        .next()
+        .is_some_and(|ch| ch.is_alphabetic() || ch == '_')
+        && name.chars().all(|ch| ch.is_alphanumeric() || ch == '_')
+        && !Tokenizer::is_keyword(&upper)
+        && !Tokenizer::is_data_type(&upper);
+    if plain {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+pub fn tokenize(statement: &str) -> Result<Vec<SpannedToken>, ParseError> {
+    let mut tokens = Vec::new();
+    Tokenizer::new(statement).tokenize_into(&mut tokens)?;
+    Ok(tokens)
+}
+
+/// Like `tokenize`, but also returns the tokens read before an error.
+pub fn tokenize_partial(statement: &str) -> (Vec<SpannedToken>, Result<(), ParseError>) {
+    let mut tokens = Vec::new();
+    let result = Tokenizer::new(statement).tokenize_into(&mut tokens);
+    (tokens, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(statement: &str) -> Vec<Token> {
+        tokenize(statement)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.token)
+            .collect()
+    }
+
+    fn error(statement: &str) -> ParseError {
+        tokenize(statement).unwrap_err()
+    }
+
+    #[test]
+    fn tokens_carry_line_and_column() {
+        let tokens = tokenize("SELECT a\n  FROM t").unwrap();
+        let from = &tokens[2];
+        assert_eq!(from.token, Token::Keyword("FROM".to_string()));
+        assert_eq!(from.span.line, 2);
+        assert_eq!(from.span.column, 3);
+        assert_eq!(
+            &"SELECT a\n  FROM t"[from.span.start..from.span.end],
+            "FROM"
+        );
+    }
+
+    #[test]
+    fn unterminated_string_literal() {
+        let error = error("INSERT INTO t VALUES ('abc");
+        assert_eq!(error.message, "Unterminated string literal");
+        assert_eq!(error.kind, ParseErrorKind::Unterminated);
+        assert_eq!(error.span.column, 23);
+        assert_eq!(error.span.end, 26);
+    }
+
+    #[test]
+    fn stray_character() {
+        let error = error("SELECT a FROM t\nWHERE a # 1");
+        assert_eq!(error.message, "Unexpected character '#'");
+        assert_eq!(error.kind, ParseErrorKind::Syntax);
+        assert_eq!((error.span.line, error.span.column), (2, 9));
+    }
+
+    #[test]
+    fn malformed_number() {
+        let error = error("INSERT INTO t VALUES (1.2.3)");
+        assert_eq!(error.message, "Malformed number '1.2.3'");
+        assert_eq!(error.span.column, 23);
+        assert_eq!(
+            tokens("-1.5"),
+            vec![
+                Token::Operator("-".to_string()),
+                Token::Numeric("1.5".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn escaped_quotes_in_literals() {
+        assert_eq!(
+            tokens("'O''Brien'"),
+            vec![Token::Literal("O'Brien".to_string())]
+        );
+        assert_eq!(
+            tokens(r"E'a\nb\'c\x41\u00e9'"),
+            vec![Token::Literal("a\nb'cAé".to_string())]
+        );
+    }
+
+    #[test]
+    fn comments_are_skipped() {
+        assert_eq!(
+            tokens("SELECT a -- trailing ' quote\nFROM /* one /* nested */ */ t"),
+            tokens("SELECT a FROM t")
+        );
+        assert_eq!(
+            error("SELECT /* open").message,
+            "Unterminated block comment"
+        );
+        assert!(error("SELECT \"open").is_unterminated());
+        assert!(error("SELECT X'0").is_unterminated());
+        // Wrong input can't be completed by typing more
+        assert!(!error("SELECT X'0g'").is_unterminated());
+    }
+
+    #[test]
+    fn quoted_identifiers_may_be_keywords() {
+        assert_eq!(
+            tokens(r#"SELECT "Mixed Case", "select", "a""b" FROM t"#)[1..6],
+            [
+                Token::Identifier("Mixed Case".to_string()),
+                Token::Punctuation(','),
+                Token::Identifier("select".to_string()),
+                Token::Punctuation(','),
+                Token::Identifier("a\"b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn operators() {
+        let ops = tokens("a <> b != c <= d >= e || f + g * h / i % j - k = l")
+            .into_iter()
+            .filter_map(|t| match t {
+                Token::Operator(op) => Some(op),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ops,
+            ["<>", "!=", "<=", ">=", "||", "+", "*", "/", "%", "-", "="]
+        );
+        assert_eq!(error("a ! b").message, "Unexpected character '!'");
+        assert_eq!(error("a | b").message, "Unexpected character '|'");
+        let ops = tokens("j->'a'->>0#>p#>>q")
+            .into_iter()
+            .filter_map(|t| match t {
+                Token::Operator(op) => Some(op),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(ops, ["->", "->>", "#>", "#>>"]);
+        assert_eq!(
+            tokens("x::INT"),
+            vec![
+                Token::Identifier("x".to_string()),
+                Token::Operator("::".to_string()),
+                Token::DataType("INT".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn hex_literals() {
+        assert_eq!(
+            tokens("X'00aBff'"),
+            vec![Token::HexLiteral(vec![0, 0xab, 0xff])]
+        );
+        assert_eq!(
+            error("x'ABC'").message,
+            "Hex literal must have an even number of digits"
+        );
+        assert_eq!(error("X'0G'").message, "Invalid hex digit 'G'");
+    }
 }
@@ -0,0 +1,270 @@
+use std::borrow::Cow;
+use std::io::IsTerminal;
+
+use rustyline::completion::Completer;
+use rustyline::highlight::{CmdKind, Highlighter};
+use rustyline::hint::Hinter;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Context, Helper};
+
+use super::highlight::highlight_sql;
+use crate::db::database::Database;
+use crate::parser::{tokenize, Token, COMMANDS, DATA_TYPES, KEYWORDS};
+
+/// Keywords after which a table name is expected.
+const TABLE_KEYWORDS: &[&str] = &["FROM", "INTO", "TABLE", "JOIN", "UPDATE", "COPY"];
+
+/// Line editor helper providing tab completion, multi-line input and syntax
+/// highlighting.
+///
+/// Table and column names come from a snapshot of the schema taken with
+/// `update_schema` before each prompt. Colours are disabled when `NO_COLOR` is set.
+pub struct SqlrHelper {
+    tables: Vec<(String, Vec<String>)>,
+    color: bool,
+    interactive: bool,
+}
+
+impl SqlrHelper {
+    pub fn new() -> Self {
+        SqlrHelper {
+            tables: Vec::new(),
+            color: std::env::var_os("NO_COLOR").is_none(),
+            interactive: std::io::stdin().is_terminal(),
+        }
+    }
+
+    pub fn update_schema(&mut self, db: &Database) {
+        self.tables = db
+            .tables
+            .values()
+            .map(|table| {
+                let columns = table.columns.iter().map(|c| c.name.clone()).collect();
+                (table.name.clone(), columns)
+            })
+            .collect();
+        self.tables.sort();
+    }
+
+    fn table_names(&self) -> Vec<String> {
+        self.tables.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    fn candidates(&self, line: &str, before: &str) -> Vec<String> {
+        let spaced_before = spaced(before);
+        let words = spaced_before.split_whitespace().collect::<Vec<_>>();
+        let upper = |index: usize| words.get(index).map(|w| w.to_uppercase());
+
+        if line.trim_start().starts_with('.') {
+            return match words.as_slice() {
+                [] => COMMANDS.iter().map(|c| c.to_string()).collect(),
+                [".export"] | [".dump"] | [".import", _] => self.table_names(),
+                _ => Vec::new(),
+            };
+        }
+
+        let previous = words.len().checked_sub(1).and_then(upper);
+        if let Some(previous) = &previous {
+            if TABLE_KEYWORDS.contains(&previous.as_str()) {
+                return self.table_names();
+            }
+        }
+
+        // Column definitions in CREATE TABLE: "(" or "," followed by a column name
+        if upper(0).as_deref() == Some("CREATE") && words.len() >= 2 {
+            let before_previous = &words[words.len() - 2];
+            let previous = &words[words.len() - 1];
+            if (*before_previous == "(" || *before_previous == ",")
+                && !KEYWORDS.contains(&previous.to_uppercase().as_str())
+            {
+                return DATA_TYPES.iter().map(|t| t.to_string()).collect();
+            }
+        }
+
+        // The table may be named after the cursor, as in "SELECT | FROM t"
+        let spaced_line = spaced(line);
+        let mut candidates = KEYWORDS.iter().map(|k| k.to_string()).collect::<Vec<_>>();
+        for (table, columns) in &self.tables {
+            candidates.push(table.clone());
+            if spaced_line.split_whitespace().any(|word| word == table) {
+                candidates.extend(columns.iter().cloned());
+            }
+        }
+        candidates
+    }
+}
+
+/// Pads parentheses and commas with spaces so they split off as words.
+fn spaced(text: &str) -> String {
+    text.replace('(', " ( ")
+        .replace(')', " ) ")
+        .replace(',', " , ")
+}
+
+impl Completer for SqlrHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos]
+            .rfind(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '.'))
+            .map(|index| index + 1)
+            .unwrap_or(0);
+        let word = &line[start..pos];
+        let lowercase = !word.is_empty() && word.chars().all(|ch| !ch.is_uppercase());
+
+        let mut matches = self
+            .candidates(line, &line[..start])
+            .into_iter()
+            .filter(|candidate| candidate.to_uppercase().starts_with(&word.to_uppercase()))
+            .map(|candidate| {
+                // Follow the case the user typed for keywords and types
+                if lowercase
+                    && (KEYWORDS.contains(&candidate.as_str())
+                        || DATA_TYPES.contains(&candidate.as_str()))
+                {
+                    candidate.to_lowercase()
+                } else {
+                    candidate
+                }
+            })
+            .collect::<Vec<_>>();
+        matches.sort();
+        matches.dedup();
+        Ok((start, matches))
+    }
+}
+
+impl Validator for SqlrHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        // Piped input keeps line-at-a-time semantics so one bad line can't swallow the rest
+        if self.interactive && is_incomplete(ctx.input()) {
+            Ok(ValidationResult::Incomplete)
+        } else {
+            Ok(ValidationResult::Valid(None))
+        }
+    }
+}
+
+impl Hinter for SqlrHelper {
+    type Hint = String;
+}
+
+impl Highlighter for SqlrHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        if !self.color || line.trim_start().starts_with('.') {
+            return Cow::Borrowed(line);
+        }
+        Cow::Owned(highlight_sql(line))
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize, _kind: CmdKind) -> bool {
+        self.color
+    }
+}
+
+impl Helper for SqlrHelper {}
+
+/// A statement continues on the next line after a trailing backslash, inside an
+/// open quote or comment, or while parentheses are unbalanced.
+fn is_incomplete(input: &str) -> bool {
+    if input.trim_end().ends_with('\\') {
+        return true;
+    }
+    if input.trim_start().starts_with('.') {
+        return false;
+    }
+
+    match tokenize(input) {
+        Ok(tokens) => {
+            let depth = tokens.iter().fold(0i32, |depth, t| match t.token {
+                Token::Punctuation('(') => depth + 1,
+                Token::Punctuation(')') => depth - 1,
+                _ => depth,
+            });
+            depth > 0
+        }
+        Err(e) => e.is_unterminated(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustyline::history::DefaultHistory;
+
+    fn helper() -> SqlrHelper {
+        SqlrHelper {
+            tables: vec![
+                (
+                    "orders".to_string(),
+                    vec!["id".to_string(), "total".to_string()],
+                ),
+                (
+                    "users".to_string(),
+                    vec!["id".to_string(), "name".to_string()],
+                ),
+            ],
+            color: false,
+            interactive: true,
+        }
+    }
+
+    /// Completes at the `|` in `line`, returning where the word starts and the matches.
+    fn complete(line: &str) -> (usize, Vec<String>) {
+        let pos = line.find('|').unwrap();
+        let line = line.replace('|', "");
+        let history = DefaultHistory::new();
+        helper()
+            .complete(&line, pos, &Context::new(&history))
+            .unwrap()
+    }
+
+    #[test]
+    fn completes_by_context() {
+        assert_eq!(complete("SEL|"), (0, vec!["SELECT".to_string()]));
+        // Keywords follow the case being typed
+        assert_eq!(complete("select * fr|"), (9, vec!["from".to_string()]));
+        assert_eq!(
+            complete("SELECT * FROM |"),
+            (14, vec!["orders".to_string(), "users".to_string()])
+        );
+        assert_eq!(
+            complete("INSERT INTO u| VALUES"),
+            (12, vec!["users".to_string()])
+        );
+        // Columns are offered once their table is named, wherever the cursor is
+        assert_eq!(complete("SELECT na| FROM users").1, ["name"]);
+        assert_eq!(complete("SELECT na| FROM orders").1, Vec::<String>::new());
+        assert_eq!(complete("SELECT tot| FROM orders").1, ["total"]);
+        assert_eq!(
+            complete("CREATE TABLE t (id IN|").1,
+            ["INT", "INTEGER", "INTERVAL"]
+        );
+        assert_eq!(
+            complete(".ex|"),
+            (0, vec![".exit".to_string(), ".export".to_string()])
+        );
+        assert_eq!(complete(".dump o|"), (6, vec!["orders".to_string()]));
+    }
+
+    #[test]
+    fn continues_unfinished_statements() {
+        assert!(is_incomplete("SELECT 'it''s"));
+        assert!(is_incomplete("SELECT 1 /* note"));
+        assert!(is_incomplete("CREATE TABLE t (\n  id INT,"));
+        assert!(is_incomplete("SELECT 1 \\"));
+        assert!(!is_incomplete("SELECT 'a' /* done */ -- open 'quote"));
+        assert!(!is_incomplete("CREATE TABLE t (id INT);"));
+        // Statements run on Enter, so a missing ';' doesn't hold the line
+        assert!(!is_incomplete("SELECT 1"));
+        // Each statement on the line runs, the last one without its ';'
+        assert!(!is_incomplete("SELECT 1; SELECT 2"));
+        // Commands are single lines, even with an open quote
+        assert!(!is_incomplete(".import 'a.csv t"));
+    }
+}
@@ -1,12 +1,55 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+use crate::db::query::ResultSet;
+use crate::db::statement::CopyDirection;
 use crate::db::{command::Command, database::Database, statement::Statement};
-use crate::parser::{parse_command, parse_statement};
+use crate::parser::{parse_command, parse_statement, split_statements, ParseError};
 
-pub fn process_commands(command: &str) {
+/// Runs a line of input: a dot command, or every statement on it in turn.
+pub fn process_input(db: &mut Database, input: &str) {
+    match input.starts_with('.') {
+        true => process_commands(db, input),
+        false => {
+            for statement in split_statements(input) {
+                process_statement(db, &statement);
+            }
+        }
+    }
+}
+
+pub fn process_commands(db: &mut Database, command: &str) {
     match parse_command(command) {
         Command::Exit => {
             println!("Exiting the application.");
             std::process::exit(0);
         }
+        Command::Import {
+            file_name,
+            table_name,
+        } => import_file(db, &file_name, &table_name, true),
+        Command::Export {
+            table_name,
+            file_name,
+        } => export_file(db, &table_name, &file_name, true),
+        Command::Dump { table_name } => {
+            let mut stdout = std::io::stdout();
+            let result = match table_name {
+                Some(table_name) => db.dump_table(&table_name, &mut stdout),
+                None => db.dump(&mut stdout),
+            };
+            if let Err(e) = result {
+                println!("Error: {}", e);
+            }
+        }
+        Command::Read { file_name } => match std::fs::read_to_string(&file_name) {
+            Ok(script) => {
+                for statement in split_statements(&script) {
+                    process_statement(db, &statement);
+                }
+            }
+            Err(e) => println!("Error: cannot read '{}': {}", file_name, e),
+        },
         Command::Unknown(cmd) => {
             println!("Unknown command: {}", cmd);
         }
@@ -21,34 +64,197 @@ pub fn process_statement(db: &mut Database, statement: &str) {
         Ok(statement) => match statement {
             Statement::CreateTable(create_table) => {
                 println!("Create table: {}", create_table);
-                db.create_table(&create_table);
+                if let Err(e) = db.create_table(&create_table) {
+                    println!("Error: {}", e);
+                }
+            }
+            Statement::CreateSequence(create_sequence) => {
+                println!("Create sequence: {}", create_sequence);
+                if let Err(e) = db.create_sequence(&create_sequence) {
+                    println!("Error: {}", e);
+                }
+            }
+            Statement::CreateType(create_type) => {
+                println!("Create type: {}", create_type);
+                if let Err(e) = db.create_type(&create_type) {
+                    println!("Error: {}", e);
+                }
+            }
+            Statement::AlterTable(alter_table) => {
+                println!("Alter table: {}", alter_table);
+                if let Err(e) = db.alter_table(&alter_table) {
+                    println!("Error: {}", e);
+                }
+            }
+            Statement::CreateTableAs(create_table_as) => {
+                println!("Create table: {}", create_table_as);
+                if let Err(e) = db.create_table_as(&create_table_as) {
+                    println!("Error: {}", e);
+                }
             }
             Statement::Select(select) => {
                 println!("Select: {}", select);
-                let rows = db.select_from_table(&select).unwrap();
-                // print table header
-                let columns = db.get_table_columns(&select.table_name).unwrap();
-                for column in &columns {
-                    print!("|{:<20}", column.name);
-                }
-                println!("|");
-                println!("{}", "-".repeat(21 * columns.len()) + "-");
-
-                // print table rows
-                for row in rows {
-                    for val in &row.values {
-                        print!("|{:<20}", val.to_string());
-                    }
-                    println!("|");
+                match db.select_from_table(&select) {
+                    Ok(result) => print_result_set(&result),
+                    Err(e) => println!("Error: {}", e),
                 }
             }
             Statement::Insert(insert) => {
                 println!("Insert: {}", insert);
-                if let Err(e) = db.insert_into_table(&insert) {
-                    println!("Error: {}", e);
+                match db.insert_into_table(&insert) {
+                    Ok(result) => {
+                        if let Some(returning) = &result.returning {
+                            print_result_set(returning);
+                        }
+                        println!("{} rows affected.", result.affected);
+                        if let Some(id) = result.last_insert_id {
+                            println!("Last insert id: {}.", id);
+                        }
+                    }
+                    Err(e) => println!("Error: {}", e),
+                }
+            }
+            Statement::Copy(copy) => {
+                println!("Copy: {}", copy);
+                match copy.direction {
+                    CopyDirection::From => {
+                        import_file(db, &copy.file_name, &copy.table_name, copy.header)
+                    }
+                    CopyDirection::To => {
+                        export_file(db, &copy.table_name, &copy.file_name, copy.header)
+                    }
                 }
             }
         },
+        Err(e) => print_parse_error(statement, &e),
+    }
+}
+
+fn print_result_set(result: &ResultSet) {
+    // print table header
+    for column in &result.columns {
+        print!("|{:<20}", column.name);
+    }
+    println!("|");
+    println!("{}", "-".repeat(21 * result.columns.len()) + "-");
+
+    // print table rows
+    for row in &result.rows {
+        for val in &row.values {
+            print!("|{:<20}", val.to_string());
+        }
+        println!("|");
+    }
+}
+
+/// Echoes the line of `statement` containing the error with a caret underneath.
+fn print_parse_error(statement: &str, error: &ParseError) {
+    print!("{}", format_parse_error(statement, error));
+}
+
+fn format_parse_error(statement: &str, error: &ParseError) -> String {
+    let start = error.span.start.min(statement.len());
+    let line_start = statement[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = statement[start..]
+        .find('\n')
+        .map(|i| start + i)
+        .unwrap_or(statement.len());
+    let end = error.span.end.clamp(start, line_end);
+
+    let padding = statement[line_start..start].chars().count();
+    let width = statement[start..end].chars().count().max(1);
+    format!(
+        "Error at line {}, column {}: {}\n  {}\n  {}{}\n",
+        error.span.line,
+        error.span.column,
+        error,
+        &statement[line_start..line_end],
+        " ".repeat(padding),
+        "^".repeat(width)
+    )
+}
+
+fn import_file(db: &mut Database, file_name: &str, table_name: &str, header: bool) {
+    let file = match File::open(file_name) {
+        Ok(file) => file,
+        Err(e) => {
+            println!("Error: cannot open '{}': {}", file_name, e);
+            return;
+        }
+    };
+
+    match db.import_csv(table_name, BufReader::new(file), header) {
+        Ok(summary) => {
+            if summary.table_created {
+                println!("Created table '{}'.", table_name);
+            }
+            for (line, error) in &summary.errors {
+                println!("Error on line {}: {}", line, error);
+            }
+            println!(
+                "Imported {} rows, skipped {}.",
+                summary.rows_imported,
+                summary.errors.len()
+            );
+        }
+        Err(e) => println!("Error: {}", e),
+    }
+}
+
+fn export_file(db: &Database, table_name: &str, file_name: &str, header: bool) {
+    let file = match File::create(file_name) {
+        Ok(file) => file,
+        Err(e) => {
+            println!("Error: cannot create '{}': {}", file_name, e);
+            return;
+        }
+    };
+
+    match db.export_csv(table_name, &mut BufWriter::new(file), header) {
+        Ok(count) => println!("Exported {} rows.", count),
         Err(e) => println!("Error: {}", e),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caret(statement: &str) -> String {
+        format_parse_error(statement, &parse_statement(statement).unwrap_err())
+    }
+
+    #[test]
+    fn runs_every_statement_on_a_line() {
+        let mut db = Database::new();
+        process_input(&mut db, "CREATE TABLE a (x INT); CREATE TABLE b (y INT);");
+        assert!(db.tables.contains_key("a"));
+        assert!(db.tables.contains_key("b"));
+        // A bad statement doesn't keep the ones after it from running
+        process_input(&mut db, "CREATE TABLE a (x INT); CREATE TABLE c (z INT)");
+        assert!(db.tables.contains_key("c"));
+    }
+
+    #[test]
+    fn caret_under_the_error() {
+        assert_eq!(
+            caret("SELECT a\nFROM t\nORDER name"),
+            "Error at line 3, column 7: Expected 'BY' after ORDER\n  ORDER name\n        ^^^^\n"
+        );
+        // Columns count characters, so text before the error may be non-ASCII
+        let statement = "SELECT 'héllo', 'wörld' FROM t ORDER x";
+        assert_eq!(
+            caret(statement),
+            format!(
+                "Error at line 1, column 38: Expected 'BY' after ORDER\n  {}\n  {}^\n",
+                statement,
+                " ".repeat(37)
+            )
+        );
+        // Past the last token the caret sits at the end of the last line
+        assert_eq!(
+            caret("INSERT INTO t\nVALUES (1,"),
+            "Error at line 2, column 11: Unexpected end of input\n  VALUES (1,\n            ^\n"
+        );
+    }
+}
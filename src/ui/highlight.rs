@@ -0,0 +1,62 @@
+use crate::parser::{tokenize, Token};
+
+const RESET: &str = "\x1b[0m";
+
+fn color(token: &Token) -> &'static str {
+    match token {
+        Token::Keyword(_) => "\x1b[1;34m",
+        Token::DataType(_) => "\x1b[36m",
+        Token::Literal(_) | Token::HexLiteral(_) | Token::Numeric(_) => "\x1b[32m",
+        Token::Identifier(_) => "\x1b[33m",
+        Token::Punctuation(_) | Token::Operator(_) => "",
+    }
+}
+
+/// Wraps each token of `line` in ANSI colour codes, leaving the text itself unchanged.
+///
+/// Lines that don't tokenize yet (e.g. an open quote while typing) are left plain.
+pub fn highlight_sql(line: &str) -> String {
+    let tokens = match tokenize(line) {
+        Ok(tokens) => tokens,
+        Err(_) => return line.to_string(),
+    };
+    let mut highlighted = String::with_capacity(line.len());
+    let mut last = 0;
+
+    for token in tokens {
+        highlighted.push_str(&line[last..token.span.start]);
+        let text = &line[token.span.start..token.span.end];
+        match color(&token.token) {
+            "" => highlighted.push_str(text),
+            code => {
+                highlighted.push_str(code);
+                highlighted.push_str(text);
+                highlighted.push_str(RESET);
+            }
+        }
+        last = token.span.end;
+    }
+    highlighted.push_str(&line[last..]);
+    highlighted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlights_tokens_and_keeps_the_text() {
+        assert_eq!(
+            highlight_sql("SELECT name, 'it''s' FROM t"),
+            "\x1b[1;34mSELECT\x1b[0m \x1b[33mname\x1b[0m, \x1b[32m'it''s'\x1b[0m \
+             \x1b[1;34mFROM\x1b[0m \x1b[33mt\x1b[0m"
+        );
+        // Comments aren't tokens, so they pass through plain
+        assert_eq!(
+            highlight_sql("x INT -- note 'quoted'\n/* SELECT */ 1"),
+            "\x1b[33mx\x1b[0m \x1b[36mINT\x1b[0m -- note 'quoted'\n/* SELECT */ \x1b[32m1\x1b[0m"
+        );
+        // An open quote can't be tokenized yet
+        assert_eq!(highlight_sql("SELECT 'abc"), "SELECT 'abc");
+    }
+}
@@ -1,35 +1,102 @@
-use std::io::{self, Write};
+use std::path::PathBuf;
 
-pub fn print_prompt() {
-    print!("sqlr> ");
-    io::stdout().flush().unwrap();
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::Editor;
+
+use super::completion::SqlrHelper;
+use crate::db::database::Database;
+
+const PROMPT: &str = "sqlr> ";
+const CONTINUATION_PROMPT: &str = "   -> ";
+const HISTORY_FILE: &str = ".sqlr_history";
+
+pub struct LineReader {
+    editor: Editor<SqlrHelper, DefaultHistory>,
+    history_path: Option<PathBuf>,
 }
 
-pub fn read_user_input() -> String {
-    let mut statement = String::new();
+impl LineReader {
+    pub fn new() -> Self {
+        let mut editor = Editor::new().expect("Failed to initialize line editor");
+        editor.set_helper(Some(SqlrHelper::new()));
+
+        let history_path =
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE));
+        if let Some(path) = &history_path {
+            // A missing history file just means this is the first session
+            let _ = editor.load_history(path);
+        }
+
+        LineReader {
+            editor,
+            history_path,
+        }
+    }
+
+    /// Reads one statement or command, returning `None` at end of input.
+    pub fn read_user_input(&mut self, db: &Database) -> Option<String> {
+        if let Some(helper) = self.editor.helper_mut() {
+            helper.update_schema(db);
+        }
 
-    loop {
-        let mut user_input = String::new();
-        io::stdin()
-            .read_line(&mut user_input)
-            .expect("Failed to read line");
+        let mut statement = String::new();
+        let mut prompt = PROMPT;
 
-        // Remove the newline character from the input
-        let user_input = user_input.trim_end();
+        loop {
+            let user_input = match self.editor.readline(prompt) {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => return Some(String::new()),
+                Err(ReadlineError::Eof) => return None,
+                Err(e) => {
+                    println!("Error: {}", e);
+                    return None;
+                }
+            };
 
-        match user_input.ends_with('\\') {
-            true => {
-                // Remove the backslash and continue reading the next line
-                statement.push_str(&user_input[..user_input.len() - 1]);
-                statement.push(' ');
+            match append_input(&mut statement, &user_input) {
+                true => prompt = CONTINUATION_PROMPT,
+                false => break,
             }
-            false => {
-                statement.push_str(user_input);
-                statement.push(' ');
-                break;
+        }
+
+        let statement = statement.trim().to_string();
+        if !statement.is_empty() {
+            let _ = self.editor.add_history_entry(statement.as_str());
+            if let Some(path) = &self.history_path {
+                // Saved after every entry so history survives `.exit`
+                let _ = self.editor.save_history(path);
             }
         }
+        Some(statement)
     }
+}
 
-    statement.trim().to_string()
+/// Joins the lines of `user_input` onto `statement`, dropping line-ending
+/// backslashes. Returns whether the last line asks for another prompt.
+fn append_input(statement: &mut String, user_input: &str) -> bool {
+    // Line breaks are kept, as they may be inside a quoted value
+    for line in user_input.lines() {
+        statement.push_str(line.trim_end().strip_suffix('\\').unwrap_or(line));
+        statement.push('\n');
+    }
+    user_input.trim_end().ends_with('\\')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joins_continuation_lines() {
+        let mut statement = String::new();
+        assert!(append_input(&mut statement, "SELECT a, \\"));
+        assert!(!append_input(&mut statement, "b\\\nFROM t\nWHERE a = 'x'"));
+        assert_eq!(statement, "SELECT a, \nb\nFROM t\nWHERE a = 'x'\n");
+
+        // An open quote or comment continues in the same buffer, line breaks included
+        let mut statement = String::new();
+        assert!(!append_input(&mut statement, "SELECT 'a  \nb' /* c\nd */;"));
+        assert_eq!(statement, "SELECT 'a  \nb' /* c\nd */;\n");
+    }
 }
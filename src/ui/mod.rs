@@ -1,18 +1,15 @@
-use handlers::{process_commands, process_statement};
-use input::{print_prompt, read_user_input};
+use handlers::process_input;
+use input::LineReader;
 
 use crate::db::database::Database;
+mod completion;
 mod handlers;
+mod highlight;
 mod input;
 
 pub fn run_ui_loop(db: &mut Database) {
-    loop {
-        print_prompt();
-        let user_input = read_user_input();
-
-        match user_input.starts_with('.') {
-            true => process_commands(&user_input),
-            false => process_statement(db, &user_input),
-        }
+    let mut reader = LineReader::new();
+    while let Some(user_input) = reader.read_user_input(db) {
+        process_input(db, &user_input);
     }
 }